@@ -1,8 +1,10 @@
 //! Criterion benchmarks comparing GPU vs CPU SHA-3 implementations
 
+use std::collections::BTreeMap;
+
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use sha3::{Digest, Sha3_256};
-use sha3_core::Sha3Variant;
+use sha3_core::{BatchHashParams, Sha3Variant};
 use sha3_wgpu::{GpuContext, GpuSha3Hasher};
 
 /// Benchmark CPU SHA-3 (reference implementation)
@@ -162,6 +164,17 @@ fn benchmark_single_vs_batch(c: &mut Criterion) {
         });
     });
 
+    // GPU single-hash fast path, one dispatch per input
+    let gpu_hasher = create_gpu_hasher();
+    group.bench_function("GPU_one_fast_x100", |b| {
+        b.iter(|| {
+            for input in &data {
+                let result = pollster::block_on(gpu_hasher.hash_one_fast(black_box(input)));
+                black_box(result.unwrap());
+            }
+        });
+    });
+
     group.finish();
 }
 
@@ -249,7 +262,7 @@ fn benchmark_industry_standard(c: &mut Criterion) {
             |b, data| {
                 b.iter_batched(
                     // Setup: create hasher (done once per batch, not timed)
-                    || create_gpu_hasher(),
+                    create_gpu_hasher,
                     // Measured: only the hash computation
                     |mut hasher| {
                         let result = pollster::block_on(bench_gpu_sha3(&mut hasher, black_box(data)));
@@ -264,12 +277,168 @@ fn benchmark_industry_standard(c: &mut Criterion) {
     group.finish();
 }
 
+fn benchmark_dispatch_dimensions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha3_dispatch_dimensions");
+    group.sample_size(10);
+
+    let batch_size = 1_000_000;
+    let data: Vec<Vec<u8>> = (0..batch_size).map(|i: u32| i.to_le_bytes().repeat(8)).collect();
+    let input_refs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+    group.throughput(Throughput::Elements(batch_size as u64));
+
+    // 1M hashes = 3907 workgroups: one row (1D) vs rows of 64 workgroups (2D)
+    for (name, threshold) in [("1D", None), ("2D", Some(64))] {
+        let mut hasher = create_gpu_hasher();
+        if let Some(threshold) = threshold {
+            hasher = hasher.with_dispatch_2d_threshold(threshold);
+        }
+        group.bench_with_input(BenchmarkId::new(name, batch_size), &input_refs, |b, data| {
+            b.iter(|| {
+                let result = pollster::block_on(bench_gpu_sha3(&mut hasher, black_box(data)));
+                black_box(result);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn benchmark_prepared_shape(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha3_prepared_shape");
+
+    // Fixed 64-byte inputs: the absorb schedule is computed once for the prepared hasher
+    let batch_size = 1000;
+    let data: Vec<Vec<u8>> = (0..batch_size).map(|i: u32| i.to_le_bytes().repeat(16)).collect();
+    let input_refs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+    group.throughput(Throughput::Elements(batch_size as u64));
+
+    let mut hasher = create_gpu_hasher();
+    let shape = BatchHashParams::new(Sha3Variant::Sha3_256, batch_size as usize, 64);
+    let expected = pollster::block_on(bench_gpu_sha3(&mut hasher, &input_refs));
+    let prepared = hasher.prepared(&shape).unwrap();
+    assert_eq!(pollster::block_on(prepared.hash(&input_refs)).unwrap(), expected);
+
+    group.bench_function("hash_batch_64B", |b| {
+        b.iter(|| {
+            let result = pollster::block_on(hasher.hash_batch(black_box(&input_refs)));
+            black_box(result.unwrap());
+        });
+    });
+    group.bench_function("prepared_64B", |b| {
+        b.iter(|| {
+            let result = pollster::block_on(prepared.hash(black_box(&input_refs)));
+            black_box(result.unwrap());
+        });
+    });
+
+    group.finish();
+}
+
+/// Hash a mixed-length corpus with one `hash_batch` call per length, scattering back
+async fn hash_per_length(hasher: &GpuSha3Hasher, data: &[&[u8]]) -> Vec<u8> {
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, input) in data.iter().enumerate() {
+        groups.entry(input.len()).or_default().push(i);
+    }
+    let mut output = vec![0u8; data.len() * 32];
+    for indices in groups.values() {
+        let inputs: Vec<&[u8]> = indices.iter().map(|&i| data[i]).collect();
+        let digests = hasher.hash_batch(&inputs).await.unwrap();
+        for (&i, digest) in indices.iter().zip(digests.chunks(32)) {
+            output[i * 32..(i + 1) * 32].copy_from_slice(digest);
+        }
+    }
+    output
+}
+
+fn benchmark_mixed_length_strategies(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha3_mixed_length_strategies");
+    group.sample_size(10);
+
+    // Many length groups (61 lengths from 16 to 976 bytes), and one outlier among
+    // same-length inputs: the two shapes where the strategies diverge most
+    let many_groups: Vec<Vec<u8>> =
+        (0..2000usize).map(|i| vec![i as u8; 16 + (i * 37 % 61) * 16]).collect();
+    let one_outlier: Vec<Vec<u8>> =
+        (0..2000usize).map(|i| vec![i as u8; if i == 1000 { 4096 } else { 64 }]).collect();
+
+    let hasher = create_gpu_hasher();
+    for (name, data) in [("many_groups", &many_groups), ("one_outlier", &one_outlier)] {
+        let input_refs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+        let total_bytes: usize = data.iter().map(Vec::len).sum();
+        group.throughput(Throughput::Bytes(total_bytes as u64));
+
+        let expected: Vec<u8> = bench_cpu_sha3(data).concat();
+        assert_eq!(pollster::block_on(hasher.hash_batch_grouped(&input_refs)).unwrap(), expected);
+        assert_eq!(pollster::block_on(hash_per_length(&hasher, &input_refs)), expected);
+
+        // All groups recorded into one submission
+        group.bench_with_input(BenchmarkId::new("grouped", name), &input_refs, |b, data| {
+            b.iter(|| {
+                let result = pollster::block_on(hasher.hash_batch_grouped(black_box(data)));
+                black_box(result.unwrap());
+            });
+        });
+        // One submission and readback per length
+        group.bench_with_input(BenchmarkId::new("per_length", name), &input_refs, |b, data| {
+            b.iter(|| {
+                let result = pollster::block_on(hash_per_length(&hasher, black_box(data)));
+                black_box(result);
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("CPU", name), data, |b, data| {
+            b.iter(|| {
+                let results = bench_cpu_sha3(black_box(data));
+                black_box(results);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+fn benchmark_concurrent_batches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sha3_concurrent_batches");
+    group.sample_size(10);
+
+    // 8 concurrent 1000-hash batches: one staging buffer leaves all but the first to
+    // dynamic buffers, a ring of 8 reads every batch back through the persistent buffers
+    let batches: Vec<Vec<Vec<u8>>> = (0..8u32)
+        .map(|b| (0..1000u32).map(|i| (b * 1000 + i).to_le_bytes().repeat(16)).collect())
+        .collect();
+    let input_refs: Vec<Vec<_>> =
+        batches.iter().map(|batch| batch.iter().map(|v| v.as_slice()).collect()).collect();
+    group.throughput(Throughput::Elements(8 * 1000));
+
+    for staging_buffers in [1, 2, 4, 8] {
+        let hasher = create_gpu_hasher().with_staging_buffers(staging_buffers);
+        group.bench_with_input(
+            BenchmarkId::new("staging_buffers", staging_buffers),
+            &input_refs,
+            |b, data| {
+                b.iter(|| {
+                    let results = pollster::block_on(futures::future::join_all(
+                        data.iter().map(|inputs| hasher.hash_batch(black_box(inputs))),
+                    ));
+                    black_box(results);
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     benchmark_batch_sizes,
     benchmark_input_sizes,
     benchmark_single_vs_batch,
     benchmark_large_batch,
-    benchmark_industry_standard
+    benchmark_industry_standard,
+    benchmark_dispatch_dimensions,
+    benchmark_prepared_shape,
+    benchmark_mixed_length_strategies,
+    benchmark_concurrent_batches
 );
 criterion_main!(benches);
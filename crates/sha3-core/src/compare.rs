@@ -0,0 +1,17 @@
+//! Digest comparison that doesn't leak where two values differ
+
+/// Whether `a` and `b` hold the same bytes, in time independent of their contents
+///
+/// Use this rather than `==` when checking a MAC or digest against an expected value:
+/// `==` returns at the first differing byte, so its timing reveals how long a prefix of a
+/// forged tag is correct. Every byte pair is visited and their differences are OR-ed
+/// together. Lengths aren't secret, so inputs of different lengths return `false` at once.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    // Hiding the accumulator at every step keeps the optimizer from turning the fold back
+    // into an early-exit comparison
+    let diff = a.iter().zip(b).fold(0u8, |diff, (x, y)| std::hint::black_box(diff | (x ^ y)));
+    diff == 0
+}
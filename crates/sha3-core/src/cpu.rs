@@ -0,0 +1,246 @@
+//! Reference SHA-3 hashing on the CPU, with no GPU dependencies
+//!
+//! Enabled by the `cpu` feature. [`CpuSha3Hasher`] has the same batch methods as the GPU
+//! hashers in `sha3-wgpu`, so it can serve as a lightweight fallback (e.g. on WebAssembly
+//! targets without WebGPU) or as the reference in tests.
+//!
+//! ```rust
+//! use sha3_core::cpu::CpuSha3Hasher;
+//! use sha3_core::Sha3Variant;
+//!
+//! let hasher = CpuSha3Hasher::new(Sha3Variant::Sha3_256);
+//! let digests = pollster::block_on(hasher.hash_batch(&[b"abc", b"xyz"])).unwrap();
+//! assert_eq!(digests.len(), 2 * 32);
+//! ```
+
+use sha3::digest::{Digest, ExtendableOutput, Update, XofReader};
+
+use crate::encoding;
+use crate::error::Sha3Error;
+use crate::types::{BatchHashParams, Sha3Variant};
+
+/// SHA-3 batch hasher running on the CPU
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuSha3Hasher {
+    variant: Sha3Variant,
+}
+
+impl CpuSha3Hasher {
+    /// Create a CPU hasher for a specific variant
+    pub fn new(variant: Sha3Variant) -> Self {
+        Self { variant }
+    }
+
+    /// Hash a batch of inputs (all must be the same length)
+    /// Returns a flattened vector of all output hashes
+    pub async fn hash_batch(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, Sha3Error> {
+        let input_length = inputs.first().map_or(0, |input| input.len());
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(Sha3Error::InvalidInputLength(input_length));
+        }
+
+        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
+        self.hash_batch_with_params(inputs, &params).await
+    }
+
+    /// Hash a batch with custom parameters (for SHAKE variants with custom output length)
+    pub async fn hash_batch_with_params(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, Sha3Error> {
+        hash_batch(&[], inputs, params)
+    }
+
+    /// Get the SHA-3 variant this hasher uses
+    pub fn variant(&self) -> Sha3Variant {
+        self.variant
+    }
+}
+
+/// Hash each `prefix || input` on the CPU using the reference `sha3` crate
+///
+/// Returns the flattened digests, in input order. A batch with a
+/// [`domain_separator`](BatchHashParams::domain_separator) override runs a plain Keccak
+/// sponge instead, since the reference hashers pad with fixed bytes.
+///
+/// # Errors
+///
+/// Returns `InvalidInputLength` if `inputs` doesn't match `params.num_hashes` and
+/// `params.input_length`, and the errors of
+/// [`total_output_bytes`](BatchHashParams::total_output_bytes)
+pub fn hash_batch(
+    prefix: &[u8],
+    inputs: &[&[u8]],
+    params: &BatchHashParams,
+) -> Result<Vec<u8>, Sha3Error> {
+    if inputs.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if inputs.len() != params.num_hashes {
+        return Err(Sha3Error::InvalidInputLength(params.num_hashes));
+    }
+
+    if !inputs.iter().all(|input| input.len() == params.input_length) {
+        return Err(Sha3Error::InvalidInputLength(params.input_length));
+    }
+
+    let output_bytes = params.get_output_bytes()?;
+    let mut output = Vec::with_capacity(params.total_output_bytes()?);
+
+    if let Some(domain_separator) = params.domain_separator {
+        // The reference hashers pad with fixed bytes, so run the sponge directly
+        let rate_bytes = params.variant.rate_bytes();
+        let header = if params.has_cshake_header() {
+            encoding::cshake_header(&params.function_name, &params.customization, rate_bytes)
+        } else {
+            Vec::new()
+        };
+        for input in inputs {
+            let message = [&header[..], prefix, input].concat();
+            output.extend(sponge(&message, rate_bytes, domain_separator, output_bytes));
+        }
+        return Ok(output);
+    }
+
+    match params.variant {
+        Sha3Variant::Sha3_224 => {
+            for input in inputs {
+                let mut hasher = sha3::Sha3_224::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let digest = Digest::finalize(hasher);
+                output.extend_from_slice(digest.as_ref());
+            }
+        }
+        Sha3Variant::Sha3_256 => {
+            for input in inputs {
+                let mut hasher = sha3::Sha3_256::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let digest = Digest::finalize(hasher);
+                output.extend_from_slice(digest.as_ref());
+            }
+        }
+        Sha3Variant::Sha3_384 => {
+            for input in inputs {
+                let mut hasher = sha3::Sha3_384::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let digest = Digest::finalize(hasher);
+                output.extend_from_slice(digest.as_ref());
+            }
+        }
+        Sha3Variant::Sha3_512 => {
+            for input in inputs {
+                let mut hasher = sha3::Sha3_512::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let digest = Digest::finalize(hasher);
+                output.extend_from_slice(digest.as_ref());
+            }
+        }
+        Sha3Variant::Keccak256 => {
+            for input in inputs {
+                let mut hasher = sha3::Keccak256::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let digest = Digest::finalize(hasher);
+                output.extend_from_slice(digest.as_ref());
+            }
+        }
+        Sha3Variant::Keccak512 => {
+            for input in inputs {
+                let mut hasher = sha3::Keccak512::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let digest = Digest::finalize(hasher);
+                output.extend_from_slice(digest.as_ref());
+            }
+        }
+        Sha3Variant::Shake128 => {
+            for input in inputs {
+                let mut hasher = sha3::Shake128::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let mut reader = ExtendableOutput::finalize_xof(hasher);
+                let mut buf = vec![0u8; output_bytes];
+                reader.read(&mut buf);
+                output.extend_from_slice(&buf);
+            }
+        }
+        Sha3Variant::Shake256 => {
+            for input in inputs {
+                let mut hasher = sha3::Shake256::default();
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let mut reader = ExtendableOutput::finalize_xof(hasher);
+                let mut buf = vec![0u8; output_bytes];
+                reader.read(&mut buf);
+                output.extend_from_slice(&buf);
+            }
+        }
+        Sha3Variant::CShake128 => {
+            let core = sha3::CShake128Core::new_with_function_name(
+                &params.function_name,
+                &params.customization,
+            );
+            for input in inputs {
+                let mut hasher = sha3::CShake128::from_core(core.clone());
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let mut reader = ExtendableOutput::finalize_xof(hasher);
+                let mut buf = vec![0u8; output_bytes];
+                reader.read(&mut buf);
+                output.extend_from_slice(&buf);
+            }
+        }
+        Sha3Variant::CShake256 => {
+            let core = sha3::CShake256Core::new_with_function_name(
+                &params.function_name,
+                &params.customization,
+            );
+            for input in inputs {
+                let mut hasher = sha3::CShake256::from_core(core.clone());
+                Update::update(&mut hasher, prefix);
+                Update::update(&mut hasher, input);
+                let mut reader = ExtendableOutput::finalize_xof(hasher);
+                let mut buf = vec![0u8; output_bytes];
+                reader.read(&mut buf);
+                output.extend_from_slice(&buf);
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+/// Keccak sponge over `message` with the given rate, padded with `domain_separator` and
+/// pad10*1, squeezing `output_bytes` bytes
+fn sponge(message: &[u8], rate_bytes: usize, domain_separator: u8, output_bytes: usize) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.push(domain_separator);
+    padded.resize((padded.len() + rate_bytes - 1) / rate_bytes * rate_bytes, 0);
+    *padded.last_mut().expect("padded message is non-empty") |= 0x80;
+
+    let mut state = [0u64; 25];
+    for block in padded.chunks_exact(rate_bytes) {
+        for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+            *lane ^= u64::from_le_bytes(bytes.try_into().expect("8-byte lane"));
+        }
+        keccak::f1600(&mut state);
+    }
+
+    let mut output = Vec::with_capacity(output_bytes);
+    loop {
+        let block: Vec<u8> =
+            state[..rate_bytes / 8].iter().flat_map(|lane| lane.to_le_bytes()).collect();
+        let take = (output_bytes - output.len()).min(rate_bytes);
+        output.extend_from_slice(&block[..take]);
+        if output.len() == output_bytes {
+            return output;
+        }
+        keccak::f1600(&mut state);
+    }
+}
@@ -0,0 +1,52 @@
+//! NIST SP 800-185 string encodings, shared by cSHAKE, KMAC and the framed hashing helpers
+
+/// Encode `x` as its minimal big-endian bytes preceded by their count (`left_encode`)
+pub fn left_encode(x: u64) -> Vec<u8> {
+    let (bytes, skip) = be_bytes(x);
+    let mut encoded = Vec::with_capacity(9 - skip);
+    encoded.push((8 - skip) as u8);
+    encoded.extend_from_slice(&bytes[skip..]);
+    encoded
+}
+
+/// Encode `x` as its minimal big-endian bytes followed by their count (`right_encode`)
+pub fn right_encode(x: u64) -> Vec<u8> {
+    let (bytes, skip) = be_bytes(x);
+    let mut encoded = Vec::with_capacity(9 - skip);
+    encoded.extend_from_slice(&bytes[skip..]);
+    encoded.push((8 - skip) as u8);
+    encoded
+}
+
+/// Encode `s` as `left_encode(bit length of s) || s` (`encode_string`)
+pub fn encode_string(s: &[u8]) -> Vec<u8> {
+    let mut encoded = left_encode(s.len() as u64 * 8);
+    encoded.extend_from_slice(s);
+    encoded
+}
+
+/// Prepend `left_encode(w)` to `x` and zero-pad the result to a multiple of `w` bytes
+/// (`bytepad`)
+///
+/// # Panics
+///
+/// Panics if `w` is zero.
+pub fn bytepad(x: &[u8], w: usize) -> Vec<u8> {
+    assert!(w > 0, "bytepad width must be positive");
+    let mut padded = left_encode(w as u64);
+    padded.extend_from_slice(x);
+    padded.resize((padded.len() + w - 1) / w * w, 0);
+    padded
+}
+
+/// cSHAKE header for function name `n` and customization `s` at `rate_bytes`:
+/// `bytepad(encode_string(n) || encode_string(s), rate_bytes)`, a whole number of blocks
+pub fn cshake_header(n: &[u8], s: &[u8], rate_bytes: usize) -> Vec<u8> {
+    bytepad(&[encode_string(n), encode_string(s)].concat(), rate_bytes)
+}
+
+/// Big-endian bytes of `x` and the number of leading zero bytes to skip (at most 7, so
+/// zero still encodes as one byte)
+fn be_bytes(x: u64) -> ([u8; 8], usize) {
+    (x.to_be_bytes(), (x.leading_zeros() as usize / 8).min(7))
+}
@@ -9,6 +9,19 @@ pub enum Sha3Error {
     #[error("Invalid input length: {0}")]
     InvalidInputLength(usize),
 
+    /// A SHAKE batch without an output length; SHAKE variants have no default digest size
+    #[error("Missing output length: SHAKE variants need an explicit output length")]
+    MissingOutputLength,
+
+    /// Invalid output length requested (e.g. zero bytes)
+    #[error("Invalid output length: {0}")]
+    InvalidOutputLength(usize),
+
+    /// A padding domain byte with the top bit set, which would collide with pad10*1's final
+    /// `0x80` bit
+    #[error("Invalid domain separator: {0:#04x} (must be below 0x80)")]
+    InvalidDomainSeparator(u8),
+
     /// GPU operation failed with the given error message
     #[error("GPU operation failed: {0}")]
     GpuError(String),
@@ -16,4 +29,10 @@ pub enum Sha3Error {
     /// WASM operation failed with the given error message
     #[error("WASM operation failed: {0}")]
     WasmError(String),
+
+    /// A variant name that doesn't match any [`Sha3Variant`](crate::Sha3Variant)
+    #[error(
+        "Invalid SHA-3 variant: {0}. Valid options: sha3-224, sha3-256, sha3-384, sha3-512, shake128, shake256, keccak256, keccak512, cshake128, cshake256"
+    )]
+    UnknownVariant(String),
 }
@@ -18,9 +18,14 @@
 
 #![warn(missing_docs)]
 
+pub mod compare;
+#[cfg(feature = "cpu")]
+pub mod cpu;
+pub mod encoding;
 pub mod error;
 pub mod types;
 
+pub use compare::ct_eq;
 pub use error::Sha3Error;
 pub use types::*;
 
@@ -36,6 +41,10 @@ mod tests {
         assert_eq!(Sha3Variant::Sha3_512.output_bits(), 512);
         assert_eq!(Sha3Variant::Shake128.output_bits(), 0);
         assert_eq!(Sha3Variant::Shake256.output_bits(), 0);
+        assert_eq!(Sha3Variant::Keccak256.output_bits(), 256);
+        assert_eq!(Sha3Variant::Keccak512.output_bits(), 512);
+        assert_eq!(Sha3Variant::CShake128.output_bits(), 0);
+        assert_eq!(Sha3Variant::CShake256.output_bits(), 0);
     }
 
     #[test]
@@ -46,6 +55,8 @@ mod tests {
         assert_eq!(Sha3Variant::Sha3_512.output_bytes(), 64);
         assert_eq!(Sha3Variant::Shake128.output_bytes(), 0);
         assert_eq!(Sha3Variant::Shake256.output_bytes(), 0);
+        assert_eq!(Sha3Variant::Keccak256.output_bytes(), 32);
+        assert_eq!(Sha3Variant::Keccak512.output_bytes(), 64);
     }
 
     #[test]
@@ -56,6 +67,10 @@ mod tests {
         assert_eq!(Sha3Variant::Sha3_512.rate_bytes(), 72);
         assert_eq!(Sha3Variant::Shake128.rate_bytes(), 168);
         assert_eq!(Sha3Variant::Shake256.rate_bytes(), 136);
+        assert_eq!(Sha3Variant::Keccak256.rate_bytes(), 136);
+        assert_eq!(Sha3Variant::Keccak512.rate_bytes(), 72);
+        assert_eq!(Sha3Variant::CShake128.rate_bytes(), 168);
+        assert_eq!(Sha3Variant::CShake256.rate_bytes(), 136);
     }
 
     #[test]
@@ -69,6 +84,19 @@ mod tests {
         assert_eq!(Sha3Variant::Shake256.capacity_bytes(), 200 - 136);
     }
 
+    #[test]
+    fn test_sha3_variant_padded_length() {
+        assert_eq!(Sha3Variant::Sha3_256.padded_length(0), 136);
+        assert_eq!(Sha3Variant::Sha3_256.padded_length(1), 136);
+        assert_eq!(Sha3Variant::Sha3_256.padded_length(135), 136);
+        assert_eq!(Sha3Variant::Sha3_256.padded_length(136), 272);
+        assert_eq!(Sha3Variant::Sha3_256.padded_length(137), 272);
+        assert_eq!(Sha3Variant::Sha3_512.padded_length(0), 72);
+        assert_eq!(Sha3Variant::Sha3_512.padded_length(144), 216);
+        assert_eq!(Sha3Variant::Shake128.padded_length(168 * 3 - 1), 168 * 3);
+        assert_eq!(Sha3Variant::Shake128.padded_length(168 * 3), 168 * 4);
+    }
+
     #[test]
     fn test_sha3_variant_domain_separator() {
         assert_eq!(Sha3Variant::Sha3_224.domain_separator(), 0x06);
@@ -77,6 +105,135 @@ mod tests {
         assert_eq!(Sha3Variant::Sha3_512.domain_separator(), 0x06);
         assert_eq!(Sha3Variant::Shake128.domain_separator(), 0x1F);
         assert_eq!(Sha3Variant::Shake256.domain_separator(), 0x1F);
+        assert_eq!(Sha3Variant::Keccak256.domain_separator(), 0x01);
+        assert_eq!(Sha3Variant::Keccak512.domain_separator(), 0x01);
+        assert_eq!(Sha3Variant::CShake128.domain_separator(), 0x04);
+        assert_eq!(Sha3Variant::CShake256.domain_separator(), 0x04);
+    }
+
+    #[test]
+    fn test_batch_hash_params_cshake_domain() {
+        // Without strings cSHAKE is SHAKE, padding included
+        let plain = BatchHashParams::new(Sha3Variant::CShake128, 1, 8);
+        assert!(!plain.has_cshake_header());
+        assert_eq!(plain.domain_separator(), 0x1F);
+
+        let custom = plain.clone().with_customization("Email Signature");
+        assert!(custom.has_cshake_header());
+        assert_eq!(custom.domain_separator(), 0x04);
+        let named = BatchHashParams::new(Sha3Variant::CShake256, 1, 8).with_function_name("KMAC");
+        assert_eq!(named.domain_separator(), 0x04);
+
+        // Strings only matter for cSHAKE
+        let shake = BatchHashParams::new(Sha3Variant::Shake128, 1, 8).with_customization("x");
+        assert!(!shake.has_cshake_header());
+        assert_eq!(shake.domain_separator(), 0x1F);
+    }
+
+    #[cfg(feature = "cpu")]
+    #[test]
+    fn test_cpu_hasher_known_answers() {
+        use cpu::CpuSha3Hasher;
+
+        let sha3 = CpuSha3Hasher::new(Sha3Variant::Sha3_256);
+        assert_eq!(sha3.variant(), Sha3Variant::Sha3_256);
+        let digests = pollster::block_on(sha3.hash_batch(&[b"abc", b"abc"])).unwrap();
+        let abc = [
+            0x3a, 0x98, 0x5d, 0xa7, 0x4f, 0xe2, 0x25, 0xb2, 0x04, 0x5c, 0x17, 0x2d, 0x6b, 0xd3,
+            0x90, 0xbd, 0x85, 0x5f, 0x08, 0x6e, 0x3e, 0x9d, 0x52, 0x5b, 0x46, 0xbf, 0xe2, 0x45,
+            0x11, 0x43, 0x15, 0x32,
+        ];
+        assert_eq!(digests, [abc, abc].concat());
+
+        // The override sponge agrees with the reference Keccak-256 ("" hashes to c5d246...)
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 1, 0).with_domain_separator(0x01);
+        let overridden = pollster::block_on(sha3.hash_batch_with_params(&[b""], &params)).unwrap();
+        let keccak = CpuSha3Hasher::new(Sha3Variant::Keccak256);
+        assert_eq!(overridden, pollster::block_on(keccak.hash_batch(&[b""])).unwrap());
+        assert_eq!(overridden[..4], [0xc5, 0xd2, 0x46, 0x01]);
+
+        let shake = CpuSha3Hasher::new(Sha3Variant::Shake128);
+        assert!(matches!(
+            pollster::block_on(shake.hash_batch(&[b"abc"])),
+            Err(Sha3Error::MissingOutputLength)
+        ));
+        assert!(matches!(
+            pollster::block_on(sha3.hash_batch(&[b"a", b"bc"])),
+            Err(Sha3Error::InvalidInputLength(1))
+        ));
+    }
+
+    #[test]
+    fn test_batch_hash_params_domain_separator_override() {
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 1, 8);
+        assert_eq!(params.domain_separator, None);
+        assert_eq!(params.domain_separator(), 0x06);
+
+        // The override wins over the variant's byte, and over cSHAKE's choice
+        assert_eq!(params.with_domain_separator(0x01).domain_separator(), 0x01);
+        let cshake = BatchHashParams::new(Sha3Variant::CShake128, 1, 8)
+            .with_customization("x")
+            .with_domain_separator(0x1F);
+        assert_eq!(cshake.domain_separator(), 0x1F);
+    }
+
+    #[test]
+    fn test_sha3_variant_sha3sum_name() {
+        assert_eq!(Sha3Variant::Sha3_224.sha3sum_name(), "SHA3-224");
+        assert_eq!(Sha3Variant::Sha3_256.sha3sum_name(), "SHA3-256");
+        assert_eq!(Sha3Variant::Sha3_384.sha3sum_name(), "SHA3-384");
+        assert_eq!(Sha3Variant::Sha3_512.sha3sum_name(), "SHA3-512");
+        assert_eq!(Sha3Variant::Shake128.sha3sum_name(), "SHAKE-128");
+        assert_eq!(Sha3Variant::Shake256.sha3sum_name(), "SHAKE-256");
+
+        assert_eq!(Sha3Variant::from_sha3sum_name("SHA3-384"), Some(Sha3Variant::Sha3_384));
+        assert_eq!(Sha3Variant::from_sha3sum_name("SHAKE-256"), Some(Sha3Variant::Shake256));
+        assert_eq!(Sha3Variant::from_sha3sum_name("KECCAK-256"), Some(Sha3Variant::Keccak256));
+        assert_eq!(Sha3Variant::from_sha3sum_name("sha3-256"), None);
+    }
+
+    #[test]
+    fn test_sha3_variant_display_round_trips() {
+        let variants = [
+            Sha3Variant::Sha3_224,
+            Sha3Variant::Sha3_256,
+            Sha3Variant::Sha3_384,
+            Sha3Variant::Sha3_512,
+            Sha3Variant::Shake128,
+            Sha3Variant::Shake256,
+            Sha3Variant::Keccak256,
+            Sha3Variant::Keccak512,
+            Sha3Variant::CShake128,
+            Sha3Variant::CShake256,
+        ];
+        for variant in variants {
+            assert_eq!(variant.to_string().parse::<Sha3Variant>().unwrap(), variant);
+        }
+        assert_eq!(Sha3Variant::Sha3_256.to_string(), "sha3-256");
+        assert_eq!("SHA3_256".parse::<Sha3Variant>().unwrap(), Sha3Variant::Sha3_256);
+        assert_eq!("Shake-128".parse::<Sha3Variant>().unwrap(), Sha3Variant::Shake128);
+        assert!(matches!(
+            "sha256".parse::<Sha3Variant>(),
+            Err(Sha3Error::UnknownVariant(name)) if name == "sha256"
+        ));
+    }
+
+    #[test]
+    fn test_fixed_variant_output_bytes() {
+        fn output_bytes<V: FixedVariant>() -> usize {
+            assert_eq!(V::OUTPUT_BYTES, V::VARIANT.output_bytes());
+            V::OUTPUT_BYTES
+        }
+        assert_eq!(output_bytes::<Sha3_224Marker>(), 28);
+        assert_eq!(output_bytes::<Sha3_256Marker>(), 32);
+        assert_eq!(output_bytes::<Sha3_384Marker>(), 48);
+        assert_eq!(output_bytes::<Sha3_512Marker>(), 64);
+        assert_eq!(output_bytes::<Keccak256Marker>(), 32);
+        assert_eq!(output_bytes::<Keccak512Marker>(), 64);
+
+        // Usable where a constant is required
+        let digest = [0u8; Sha3_384Marker::OUTPUT_BYTES];
+        assert_eq!(digest.len(), 48);
     }
 
     #[test]
@@ -109,7 +266,35 @@ mod tests {
     #[test]
     fn test_batch_hash_params_get_output_bytes_shake_without_length() {
         let params = BatchHashParams::new(Sha3Variant::Shake128, 5, 32);
-        assert!(params.get_output_bytes().is_err());
+        assert!(matches!(params.get_output_bytes(), Err(Sha3Error::MissingOutputLength)));
+    }
+
+    #[test]
+    fn test_batch_hash_params_get_output_bytes_zero_length() {
+        let params = BatchHashParams::new(Sha3Variant::Shake256, 5, 32).with_output_length(0);
+        assert!(matches!(params.get_output_bytes(), Err(Sha3Error::InvalidOutputLength(0))));
+    }
+
+    #[test]
+    fn test_batch_hash_params_get_output_bytes_invalid_domain_separator() {
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 5, 32);
+        assert!(params.clone().with_domain_separator(0x7F).get_output_bytes().is_ok());
+        assert!(matches!(
+            params.with_domain_separator(0x80).get_output_bytes(),
+            Err(Sha3Error::InvalidDomainSeparator(0x80))
+        ));
+    }
+
+    #[test]
+    fn test_batch_hash_params_total_output_bytes() {
+        let params = BatchHashParams::new(Sha3Variant::Shake256, 5, 32).with_output_length(64);
+        assert_eq!(params.total_output_bytes().unwrap(), 5 * 64);
+
+        // Totals past the address space are rejected rather than overflowing
+        let absurd = params.clone().with_output_length(usize::MAX / 2);
+        assert!(matches!(absurd.total_output_bytes(), Err(Sha3Error::InvalidOutputLength(_))));
+        let shake = BatchHashParams::new(Sha3Variant::Shake256, 5, 32);
+        assert!(matches!(shake.total_output_bytes(), Err(Sha3Error::MissingOutputLength)));
     }
 
     #[test]
@@ -118,4 +303,121 @@ mod tests {
         let params = BatchHashParams::new(Sha3Variant::Sha3_256, 5, 32).with_output_length(16);
         assert_eq!(params.get_output_bytes().unwrap(), 16);
     }
+
+    #[test]
+    fn test_batch_hash_params_output_bytes_or() {
+        assert_eq!(BatchHashParams::new(Sha3Variant::Sha3_224, 1, 8).output_bytes_or(99), 28);
+        assert_eq!(BatchHashParams::new(Sha3Variant::Sha3_512, 1, 8).output_bytes_or(99), 64);
+        assert_eq!(BatchHashParams::new(Sha3Variant::Shake128, 1, 8).output_bytes_or(99), 99);
+        let shake = BatchHashParams::new(Sha3Variant::Shake256, 1, 8).with_output_length(20);
+        assert_eq!(shake.output_bytes_or(99), 20);
+    }
+
+    #[test]
+    fn test_batch_hash_params_split_output() {
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 3, 64);
+        let flat: Vec<u8> = (0..96).collect();
+        let digests: Vec<&[u8]> = params.split_output(&flat).unwrap().collect();
+        assert_eq!(digests.len(), 3);
+        assert!(digests.iter().all(|d| d.len() == 32));
+        assert_eq!(digests[2], &flat[64..]);
+
+        let shake = BatchHashParams::new(Sha3Variant::Shake256, 4, 8).with_output_length(20);
+        let flat = vec![0u8; 80];
+        let digests: Vec<&[u8]> = shake.split_output(&flat).unwrap().collect();
+        assert_eq!(digests.len(), 4);
+        assert!(digests.iter().all(|d| d.len() == 20));
+
+        // Wrong buffer size or unknown SHAKE output length
+        assert!(matches!(params.split_output(&flat), Err(Sha3Error::InvalidOutputLength(80))));
+        assert!(BatchHashParams::new(Sha3Variant::Shake128, 4, 8).split_output(&flat).is_err());
+
+        // A size that overflows is an error, not a wrapped length
+        let absurd = shake.with_output_length(usize::MAX);
+        assert!(matches!(absurd.split_output(&flat), Err(Sha3Error::InvalidOutputLength(_))));
+    }
+
+    #[test]
+    fn test_prepared_shape_block_schedule() {
+        let shape = PreparedShape::new(Sha3Variant::Sha3_256, 64);
+        assert_eq!(
+            (shape.absorb_blocks(), shape.padding_offset(), shape.padded_length()),
+            (1, 64, 136)
+        );
+
+        // A rate multiple needs a whole extra block for the padding
+        let shape = PreparedShape::new(Sha3Variant::Sha3_256, 136);
+        assert_eq!((shape.absorb_blocks(), shape.padded_length()), (2, 272));
+        let shape = PreparedShape::new(Sha3Variant::Sha3_512, 0);
+        assert_eq!(
+            (shape.absorb_blocks(), shape.padding_offset(), shape.padded_length()),
+            (1, 0, 72)
+        );
+        let shape = PreparedShape::new(Sha3Variant::Shake128, 169);
+        assert_eq!((shape.absorb_blocks(), shape.padded_length()), (2, 336));
+    }
+
+    #[test]
+    fn test_hash_result_hex_and_equality() {
+        use std::collections::HashSet;
+
+        let digest = HashResult::new(Sha3Variant::Sha3_256, vec![0x00, 0x0f, 0xa5, 0xff]);
+        assert_eq!(digest.as_hex(), "000fa5ff");
+        assert_eq!(digest.to_string(), "000fa5ff");
+        assert_eq!(digest.as_ref(), [0x00, 0x0f, 0xa5, 0xff]);
+        assert_eq!(HashResult::new(Sha3Variant::Shake128, Vec::new()).as_hex(), "");
+
+        // Same bytes from another variant are a different result
+        let keccak = HashResult::new(Sha3Variant::Keccak256, digest.bytes.clone());
+        assert_eq!(digest, HashResult::new(Sha3Variant::Sha3_256, vec![0x00, 0x0f, 0xa5, 0xff]));
+        assert_ne!(digest, keccak);
+        let set: HashSet<HashResult> = [digest.clone(), keccak, digest].into_iter().collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        let digest = HashResult::new(Sha3Variant::Sha3_256, vec![0xab; 32]);
+        assert!(ct_eq(&[], &[]));
+        assert!(ct_eq(&digest.bytes, &[0xab; 32]));
+        assert!(!ct_eq(&digest.bytes, &[0xac; 32]));
+
+        let mut last_differs = [0xab; 32];
+        last_differs[31] = 0;
+        assert!(!ct_eq(&digest.bytes, &last_differs));
+        assert!(!ct_eq(&digest.bytes, &[0xab; 31]));
+        assert!(!ct_eq(&digest.bytes, &[0xab; 33]));
+
+        assert!(digest.verify(&[0xab; 32]));
+        assert!(!digest.verify(&last_differs));
+        assert!(!digest.verify(b""));
+    }
+
+    #[test]
+    fn test_sp800_185_encodings() {
+        use encoding::*;
+
+        assert_eq!(left_encode(0), vec![1, 0]);
+        assert_eq!(left_encode(255), vec![1, 255]);
+        assert_eq!(left_encode(2048), vec![2, 0x08, 0x00]);
+        assert_eq!(left_encode(u64::MAX), [&[8u8][..], &[0xFF; 8]].concat());
+        assert_eq!(right_encode(0), vec![0, 1]);
+        assert_eq!(right_encode(256), vec![0x01, 0x00, 2]);
+
+        assert_eq!(encode_string(b""), vec![1, 0]);
+        assert_eq!(encode_string(b"KMAC"), [&[1u8, 32][..], b"KMAC"].concat());
+
+        let padded = bytepad(&encode_string(b"KMAC"), 168);
+        assert_eq!(padded.len(), 168);
+        assert_eq!(padded[..8], [1, 168, 1, 32, b'K', b'M', b'A', b'C']);
+        assert!(padded[8..].iter().all(|&b| b == 0));
+        // Already a multiple of the width after the prefix: no extra padding
+        assert_eq!(bytepad(&[7; 6], 8).len(), 8);
+
+        // cSHAKE header: bytepad(encode_string(N) || encode_string(S), rate)
+        let header = cshake_header(b"", b"Email Signature", 168);
+        assert_eq!(header.len(), 168);
+        assert_eq!(header[..6], [1, 168, 1, 0, 1, 120]);
+        assert_eq!(&header[6..21], b"Email Signature");
+    }
 }
@@ -3,8 +3,9 @@
 /// SHA-3 variant
 ///
 /// Represents the different SHA-3 hash function variants, including
-/// fixed-length SHA3 and variable-length SHAKE variants.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// fixed-length SHA3, variable-length SHAKE, and the original (pre-NIST) Keccak variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Sha3Variant {
     /// SHA3-224: 224-bit output (28 bytes)
     Sha3_224,
@@ -18,11 +19,19 @@ pub enum Sha3Variant {
     Shake128,
     /// SHAKE256: Variable-length output, 256-bit security
     Shake256,
+    /// Keccak-256: original Keccak padding (as used by Ethereum), 256-bit output (32 bytes)
+    Keccak256,
+    /// Keccak-512: original Keccak padding, 512-bit output (64 bytes)
+    Keccak512,
+    /// cSHAKE128: SHAKE128 with function-name and customization strings (SP 800-185)
+    CShake128,
+    /// cSHAKE256: SHAKE256 with function-name and customization strings (SP 800-185)
+    CShake256,
 }
 
 impl Sha3Variant {
     /// Returns the output size in bits (0 for variable-length variants)
-    pub fn output_bits(&self) -> usize {
+    pub const fn output_bits(&self) -> usize {
         match self {
             Sha3Variant::Sha3_224 => 224,
             Sha3Variant::Sha3_256 => 256,
@@ -30,11 +39,14 @@ impl Sha3Variant {
             Sha3Variant::Sha3_512 => 512,
             Sha3Variant::Shake128 => 0, // Variable length
             Sha3Variant::Shake256 => 0, // Variable length
+            Sha3Variant::Keccak256 => 256,
+            Sha3Variant::Keccak512 => 512,
+            Sha3Variant::CShake128 | Sha3Variant::CShake256 => 0, // Variable length
         }
     }
 
     /// Returns the output size in bytes (0 for variable-length variants)
-    pub fn output_bytes(&self) -> usize {
+    pub const fn output_bytes(&self) -> usize {
         self.output_bits() / 8
     }
 
@@ -42,12 +54,16 @@ impl Sha3Variant {
     /// The rate is the number of bytes absorbed/squeezed per permutation
     pub fn rate_bytes(&self) -> usize {
         match self {
-            Sha3Variant::Sha3_224 => 144, // 1152 bits = 144 bytes
-            Sha3Variant::Sha3_256 => 136, // 1088 bits = 136 bytes
-            Sha3Variant::Sha3_384 => 104, // 832 bits = 104 bytes
-            Sha3Variant::Sha3_512 => 72,  // 576 bits = 72 bytes
-            Sha3Variant::Shake128 => 168, // 1344 bits = 168 bytes
-            Sha3Variant::Shake256 => 136, // 1088 bits = 136 bytes
+            Sha3Variant::Sha3_224 => 144,  // 1152 bits = 144 bytes
+            Sha3Variant::Sha3_256 => 136,  // 1088 bits = 136 bytes
+            Sha3Variant::Sha3_384 => 104,  // 832 bits = 104 bytes
+            Sha3Variant::Sha3_512 => 72,   // 576 bits = 72 bytes
+            Sha3Variant::Shake128 => 168,  // 1344 bits = 168 bytes
+            Sha3Variant::Shake256 => 136,  // 1088 bits = 136 bytes
+            Sha3Variant::Keccak256 => 136, // Same sponge as SHA3-256
+            Sha3Variant::Keccak512 => 72,  // Same sponge as SHA3-512
+            Sha3Variant::CShake128 => 168, // Same sponge as SHAKE128
+            Sha3Variant::CShake256 => 136, // Same sponge as SHAKE256
         }
     }
 
@@ -57,7 +73,54 @@ impl Sha3Variant {
         200 - self.rate_bytes() // Total state is 1600 bits = 200 bytes
     }
 
+    /// Returns the length of an `input_len`-byte message after pad10*1 padding
+    ///
+    /// Always a non-zero multiple of the rate; an input that is already a rate multiple
+    /// (including the empty input) gains a whole block of padding.
+    pub fn padded_length(&self, input_len: usize) -> usize {
+        PreparedShape::new(*self, input_len).padded_length()
+    }
+
+    /// Returns the algorithm tag used in `sha3sum`/OpenSSL digest output (e.g. `SHA3-256`)
+    pub fn sha3sum_name(&self) -> &'static str {
+        match self {
+            Sha3Variant::Sha3_224 => "SHA3-224",
+            Sha3Variant::Sha3_256 => "SHA3-256",
+            Sha3Variant::Sha3_384 => "SHA3-384",
+            Sha3Variant::Sha3_512 => "SHA3-512",
+            Sha3Variant::Shake128 => "SHAKE-128",
+            Sha3Variant::Shake256 => "SHAKE-256",
+            Sha3Variant::Keccak256 => "KECCAK-256",
+            Sha3Variant::Keccak512 => "KECCAK-512",
+            Sha3Variant::CShake128 => "CSHAKE-128",
+            Sha3Variant::CShake256 => "CSHAKE-256",
+        }
+    }
+
+    /// Parses an algorithm tag as written by [`sha3sum_name`](Self::sha3sum_name)
+    ///
+    /// Matching is exact, as needed when reading checksum manifests back.
+    pub fn from_sha3sum_name(name: &str) -> Option<Self> {
+        [
+            Sha3Variant::Sha3_224,
+            Sha3Variant::Sha3_256,
+            Sha3Variant::Sha3_384,
+            Sha3Variant::Sha3_512,
+            Sha3Variant::Shake128,
+            Sha3Variant::Shake256,
+            Sha3Variant::Keccak256,
+            Sha3Variant::Keccak512,
+            Sha3Variant::CShake128,
+            Sha3Variant::CShake256,
+        ]
+        .into_iter()
+        .find(|variant| variant.sha3sum_name() == name)
+    }
+
     /// Returns the domain separation byte for this variant
+    ///
+    /// For cSHAKE this is the byte used behind a function-name/customization header; with
+    /// both strings empty cSHAKE is plain SHAKE (see [`BatchHashParams::domain_separator`]).
     pub fn domain_separator(&self) -> u8 {
         match self {
             Sha3Variant::Sha3_224
@@ -65,10 +128,93 @@ impl Sha3Variant {
             | Sha3Variant::Sha3_384
             | Sha3Variant::Sha3_512 => 0x06, // SHA-3
             Sha3Variant::Shake128 | Sha3Variant::Shake256 => 0x1F, // SHAKE
+            Sha3Variant::Keccak256 | Sha3Variant::Keccak512 => 0x01, // Original Keccak
+            Sha3Variant::CShake128 | Sha3Variant::CShake256 => 0x04, // cSHAKE
         }
     }
 }
 
+impl std::fmt::Display for Sha3Variant {
+    /// Writes the canonical lowercase name: `sha3-256`, `shake128`, `keccak256`, `cshake128`...
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Sha3Variant::Sha3_224 => "sha3-224",
+            Sha3Variant::Sha3_256 => "sha3-256",
+            Sha3Variant::Sha3_384 => "sha3-384",
+            Sha3Variant::Sha3_512 => "sha3-512",
+            Sha3Variant::Shake128 => "shake128",
+            Sha3Variant::Shake256 => "shake256",
+            Sha3Variant::Keccak256 => "keccak256",
+            Sha3Variant::Keccak512 => "keccak512",
+            Sha3Variant::CShake128 => "cshake128",
+            Sha3Variant::CShake256 => "cshake256",
+        })
+    }
+}
+
+impl std::str::FromStr for Sha3Variant {
+    type Err = crate::error::Sha3Error;
+
+    /// Parses a variant name, case-insensitively
+    ///
+    /// Accepts the [`Display`](std::fmt::Display) names, hyphenated (`sha3-256`,
+    /// OpenSSL-style `shake-128`) and underscored (`SHA3_256`, `shake_128`) spellings.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "sha3-224" | "sha3_224" => Ok(Sha3Variant::Sha3_224),
+            "sha3-256" | "sha3_256" => Ok(Sha3Variant::Sha3_256),
+            "sha3-384" | "sha3_384" => Ok(Sha3Variant::Sha3_384),
+            "sha3-512" | "sha3_512" => Ok(Sha3Variant::Sha3_512),
+            "shake128" | "shake-128" | "shake_128" => Ok(Sha3Variant::Shake128),
+            "shake256" | "shake-256" | "shake_256" => Ok(Sha3Variant::Shake256),
+            "keccak256" | "keccak-256" | "keccak_256" => Ok(Sha3Variant::Keccak256),
+            "keccak512" | "keccak-512" | "keccak_512" => Ok(Sha3Variant::Keccak512),
+            "cshake128" | "cshake-128" | "cshake_128" => Ok(Sha3Variant::CShake128),
+            "cshake256" | "cshake-256" | "cshake_256" => Ok(Sha3Variant::CShake256),
+            _ => Err(crate::error::Sha3Error::UnknownVariant(s.to_string())),
+        }
+    }
+}
+
+/// Type-level counterpart of a fixed-output [`Sha3Variant`]
+///
+/// Implemented by zero-sized marker types such as [`Sha3_256Marker`], so generic code can
+/// size digests statically: `[u8; Sha3_256Marker::OUTPUT_BYTES]` needs no runtime check.
+/// SHAKE variants have no fixed output length and therefore no marker.
+pub trait FixedVariant {
+    /// The runtime variant this marker stands for
+    const VARIANT: Sha3Variant;
+    /// Digest length in bytes, always equal to `VARIANT.output_bytes()`
+    const OUTPUT_BYTES: usize = Self::VARIANT.output_bytes();
+}
+
+macro_rules! fixed_variant_markers {
+    ($($(#[$doc:meta])* $marker:ident => $variant:ident,)*) => {$(
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+        pub struct $marker;
+
+        impl FixedVariant for $marker {
+            const VARIANT: Sha3Variant = Sha3Variant::$variant;
+        }
+    )*};
+}
+
+fixed_variant_markers! {
+    /// Marker for [`Sha3Variant::Sha3_224`] (28-byte digests)
+    Sha3_224Marker => Sha3_224,
+    /// Marker for [`Sha3Variant::Sha3_256`] (32-byte digests)
+    Sha3_256Marker => Sha3_256,
+    /// Marker for [`Sha3Variant::Sha3_384`] (48-byte digests)
+    Sha3_384Marker => Sha3_384,
+    /// Marker for [`Sha3Variant::Sha3_512`] (64-byte digests)
+    Sha3_512Marker => Sha3_512,
+    /// Marker for [`Sha3Variant::Keccak256`] (32-byte digests)
+    Keccak256Marker => Keccak256,
+    /// Marker for [`Sha3Variant::Keccak512`] (64-byte digests)
+    Keccak512Marker => Keccak512,
+}
+
 /// Parameters for a batch hashing operation
 #[derive(Debug, Clone)]
 pub struct BatchHashParams {
@@ -80,12 +226,28 @@ pub struct BatchHashParams {
     pub input_length: usize,
     /// Output length in bytes (for SHAKE variants, otherwise ignored)
     pub output_length: Option<usize>,
+    /// cSHAKE function-name string N (for cSHAKE variants, otherwise ignored)
+    pub function_name: Vec<u8>,
+    /// cSHAKE customization string S (for cSHAKE variants, otherwise ignored)
+    pub customization: Vec<u8>,
+    /// Padding domain byte replacing the variant's own (see
+    /// [`domain_separator`](Self::domain_separator)), for protocols with non-standard
+    /// padding
+    pub domain_separator: Option<u8>,
 }
 
 impl BatchHashParams {
     /// Creates new batch parameters
     pub fn new(variant: Sha3Variant, num_hashes: usize, input_length: usize) -> Self {
-        Self { variant, num_hashes, input_length, output_length: None }
+        Self {
+            variant,
+            num_hashes,
+            input_length,
+            output_length: None,
+            function_name: Vec::new(),
+            customization: Vec::new(),
+            domain_separator: None,
+        }
     }
 
     /// Sets custom output length (for SHAKE variants)
@@ -94,22 +256,204 @@ impl BatchHashParams {
         self
     }
 
+    /// Sets the cSHAKE function-name string (for cSHAKE variants)
+    pub fn with_function_name(mut self, function_name: impl Into<Vec<u8>>) -> Self {
+        self.function_name = function_name.into();
+        self
+    }
+
+    /// Sets the cSHAKE customization string (for cSHAKE variants)
+    pub fn with_customization(mut self, customization: impl Into<Vec<u8>>) -> Self {
+        self.customization = customization.into();
+        self
+    }
+
+    /// Pads with `domain_separator` instead of the variant's byte
+    ///
+    /// The byte is placed right after each input and combined with pad10*1's final `0x80`
+    /// bit, so it must be below `0x80`; e.g. `0x01` turns a SHA-3 variant into the original
+    /// Keccak with the same rate and digest length. A larger byte makes hashing fail with
+    /// `InvalidDomainSeparator`.
+    pub fn with_domain_separator(mut self, domain_separator: u8) -> Self {
+        self.domain_separator = Some(domain_separator);
+        self
+    }
+
+    /// Whether this batch is cSHAKE with a function-name or customization string, and so
+    /// needs the `bytepad` header absorbed before each input
+    pub fn has_cshake_header(&self) -> bool {
+        matches!(self.variant, Sha3Variant::CShake128 | Sha3Variant::CShake256)
+            && !(self.function_name.is_empty() && self.customization.is_empty())
+    }
+
+    /// Returns the padding domain byte for this batch
+    ///
+    /// The override set with [`with_domain_separator`](Self::with_domain_separator) if any,
+    /// otherwise the variant's own byte, except that cSHAKE with empty strings is exactly
+    /// SHAKE and pads with its `0x1F`.
+    pub fn domain_separator(&self) -> u8 {
+        if let Some(domain_separator) = self.domain_separator {
+            return domain_separator;
+        }
+        match self.variant {
+            Sha3Variant::CShake128 | Sha3Variant::CShake256 if !self.has_cshake_header() => {
+                Sha3Variant::Shake128.domain_separator()
+            }
+            variant => variant.domain_separator(),
+        }
+    }
+
     /// Returns the output length in bytes for this batch
     ///
     /// # Errors
     ///
-    /// Returns an error if this is a SHAKE variant without a specified output length
+    /// Returns `MissingOutputLength` if this is a SHAKE variant without a specified output
+    /// length, `InvalidOutputLength` if the specified length is zero, and
+    /// `InvalidDomainSeparator` if the padding override is `0x80` or above
     pub fn get_output_bytes(&self) -> Result<usize, crate::error::Sha3Error> {
+        if let Some(domain_separator) = self.domain_separator.filter(|&byte| byte >= 0x80) {
+            return Err(crate::error::Sha3Error::InvalidDomainSeparator(domain_separator));
+        }
         match self.output_length {
+            Some(0) => Err(crate::error::Sha3Error::InvalidOutputLength(0)),
             Some(len) => Ok(len),
             None => {
                 let default_len = self.variant.output_bytes();
                 if default_len > 0 {
                     Ok(default_len)
                 } else {
-                    Err(crate::error::Sha3Error::InvalidInputLength(0))
+                    Err(crate::error::Sha3Error::MissingOutputLength)
                 }
             }
         }
     }
+
+    /// Returns the combined output length of the whole batch in bytes
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of [`get_output_bytes`](Self::get_output_bytes), and
+    /// `InvalidOutputLength` if the total can't be allocated (it exceeds `isize::MAX`), so
+    /// absurd lengths are rejected before any buffer is sized from them
+    pub fn total_output_bytes(&self) -> Result<usize, crate::error::Sha3Error> {
+        let output_bytes = self.get_output_bytes()?;
+        self.num_hashes
+            .checked_mul(output_bytes)
+            .filter(|&total| total <= isize::MAX as usize)
+            .ok_or(crate::error::Sha3Error::InvalidOutputLength(output_bytes))
+    }
+
+    /// Returns the output length in bytes, or `default` if it can't be determined
+    ///
+    /// A size hint for pre-allocating buffers: identical to
+    /// [`get_output_bytes`](Self::get_output_bytes) except that a SHAKE variant without an
+    /// output length yields `default` instead of an error.
+    pub fn output_bytes_or(&self, default: usize) -> usize {
+        self.get_output_bytes().unwrap_or(default)
+    }
+
+    /// Returns the absorb schedule shared by every input of this batch
+    pub fn prepared_shape(&self) -> PreparedShape {
+        PreparedShape::new(self.variant, self.input_length)
+    }
+
+    /// Splits a flat batch output into one digest slice per hash, in input order
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the batch's output size can't be determined (see
+    /// [`total_output_bytes`](Self::total_output_bytes)), or `InvalidOutputLength` if `flat`
+    /// isn't exactly `num_hashes` digests long
+    pub fn split_output<'a>(
+        &self,
+        flat: &'a [u8],
+    ) -> Result<impl Iterator<Item = &'a [u8]>, crate::error::Sha3Error> {
+        let output_bytes = self.get_output_bytes()?;
+        if flat.len() != self.total_output_bytes()? {
+            return Err(crate::error::Sha3Error::InvalidOutputLength(flat.len()));
+        }
+        Ok((0..self.num_hashes).map(move |i| &flat[i * output_bytes..(i + 1) * output_bytes]))
+    }
+}
+
+/// Absorb schedule of a fixed input length: how many rate blocks the padded message spans
+/// and where its padding starts
+///
+/// Constant for inputs of one length, so batch code computes it once and hands it to the
+/// GPU instead of every invocation redoing the length math.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreparedShape {
+    rate_bytes: usize,
+    input_length: usize,
+    absorb_blocks: usize,
+}
+
+impl PreparedShape {
+    /// Schedule for `input_length`-byte messages hashed with `variant`
+    pub fn new(variant: Sha3Variant, input_length: usize) -> Self {
+        let rate_bytes = variant.rate_bytes();
+        // pad10*1 adds at least one byte, so a rate multiple gets a whole padding block
+        let absorb_blocks = input_length / rate_bytes + 1;
+        Self { rate_bytes, input_length, absorb_blocks }
+    }
+
+    /// Number of rate blocks absorbed (permutations before squeezing)
+    pub fn absorb_blocks(&self) -> usize {
+        self.absorb_blocks
+    }
+
+    /// Offset of the domain separation byte, the first padding byte
+    pub fn padding_offset(&self) -> usize {
+        self.input_length
+    }
+
+    /// Length of the padded message; its last byte carries the final padding bit
+    pub fn padded_length(&self) -> usize {
+        self.absorb_blocks * self.rate_bytes
+    }
+}
+
+/// A digest together with the variant that produced it
+///
+/// Equality and hashing cover both fields, so digests of the same bytes from different
+/// algorithms (e.g. SHA3-256 and Keccak-256 outputs) never compare equal, and results can
+/// key maps by content. Displays as lowercase hex. Serializable with the `serde` feature.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HashResult {
+    /// Variant the digest was computed with
+    pub variant: Sha3Variant,
+    /// Digest bytes (the variant's output size, or the requested length for SHAKE)
+    pub bytes: Vec<u8>,
+}
+
+impl HashResult {
+    /// Pair `bytes` with the `variant` that produced them
+    pub fn new(variant: Sha3Variant, bytes: Vec<u8>) -> Self {
+        Self { variant, bytes }
+    }
+
+    /// Lowercase hex encoding of the digest
+    pub fn as_hex(&self) -> String {
+        self.to_string()
+    }
+
+    /// Whether the digest equals `expected`, compared in constant time with [`ct_eq`]
+    ///
+    /// [`ct_eq`]: crate::ct_eq
+    pub fn verify(&self, expected: &[u8]) -> bool {
+        crate::ct_eq(&self.bytes, expected)
+    }
+}
+
+impl AsRef<[u8]> for HashResult {
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+impl std::fmt::Display for HashResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.bytes.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
 }
@@ -1,24 +1,20 @@
 //! WASM bindings for SHA-3 GPU acceleration
 //! Provides Node.js and Bun.js compatible API for batch SHA-3 hashing
 
-use js_sys::{Array, Uint8Array};
+use js_sys::{Array, Uint32Array, Uint8Array};
 use sha3_core::{BatchHashParams, Sha3Variant};
 use sha3_wgpu::{GpuContext, GpuSha3Hasher};
 use wasm_bindgen::prelude::*;
 
-/// Parse SHA-3 variant string to enum
+/// Default cap on `hashBatchWithLength` output per input (1 MiB)
+///
+/// Larger requests quickly exceed browser WebGPU buffer limits (often 128 MiB per storage
+/// binding), which otherwise surface as an opaque error from the GPU process.
+pub const DEFAULT_MAX_OUTPUT_LENGTH: usize = 1 << 20;
+
+/// Parse SHA-3 variant string to enum (see `Sha3Variant`'s `FromStr` for accepted names)
 fn parse_variant(variant: &str) -> Result<Sha3Variant, JsValue> {
-    match variant.to_lowercase().as_str() {
-        "sha3-224" | "sha3_224" => Ok(Sha3Variant::Sha3_224),
-        "sha3-256" | "sha3_256" => Ok(Sha3Variant::Sha3_256),
-        "sha3-384" | "sha3_384" => Ok(Sha3Variant::Sha3_384),
-        "sha3-512" | "sha3_512" => Ok(Sha3Variant::Sha3_512),
-        "shake128" => Ok(Sha3Variant::Shake128),
-        "shake256" => Ok(Sha3Variant::Shake256),
-        _ => Err(JsValue::from_str(&format!(
-            "Invalid SHA-3 variant: {variant}. Valid options: sha3-224, sha3-256, sha3-384, sha3-512, shake128, shake256"
-        ))),
-    }
+    variant.parse().map_err(|e: sha3_core::Sha3Error| JsValue::from_str(&e.to_string()))
 }
 
 /// GPU-accelerated SHA-3 hasher for WASM
@@ -26,6 +22,7 @@ fn parse_variant(variant: &str) -> Result<Sha3Variant, JsValue> {
 pub struct Sha3WasmHasher {
     hasher: GpuSha3Hasher,
     variant: Sha3Variant,
+    max_output_length: usize,
 }
 
 #[wasm_bindgen]
@@ -57,7 +54,7 @@ impl Sha3WasmHasher {
         let hasher = GpuSha3Hasher::new(context, variant_enum)
             .map_err(|e| JsValue::from_str(&format!("Failed to create hasher: {e}")))?;
 
-        Ok(Self { hasher, variant: variant_enum })
+        Ok(Self { hasher, variant: variant_enum, max_output_length: DEFAULT_MAX_OUTPUT_LENGTH })
     }
 
     /// Hash a single input
@@ -156,6 +153,61 @@ impl Sha3WasmHasher {
         Ok(result_array)
     }
 
+    /// Hash a batch, calling `callback(completed, total)` after each GPU submission
+    ///
+    /// Large batches are split into chunks the GPU buffers hold, so a UI can show progress
+    /// (see `GpuSha3Hasher::hash_batch_with_progress`). The callback runs between chunks on
+    /// the task awaiting this promise. If it throws, hashing continues and the first
+    /// exception rejects the promise once the batch is done.
+    ///
+    /// # Arguments
+    /// * `inputs` - JavaScript array of Uint8Array inputs (all the same length)
+    /// * `callback` - Function called with the inputs hashed so far and the batch size
+    ///
+    /// # Returns
+    /// Array of Uint8Array hashes (same order as inputs)
+    ///
+    /// # Example (JavaScript)
+    /// ```javascript
+    /// const hashes = await hasher.hashBatchWithProgress(inputs, (completed, total) => {
+    ///   progressBar.value = completed / total;
+    /// });
+    /// ```
+    #[wasm_bindgen(js_name = hashBatchWithProgress)]
+    pub async fn hash_batch_with_progress(
+        &mut self,
+        inputs: &Array,
+        callback: &js_sys::Function,
+    ) -> Result<Array, JsValue> {
+        // Convert JS arrays to Rust vectors
+        let rust_inputs: Vec<Vec<u8>> =
+            inputs.iter().map(|val| Uint8Array::from(val).to_vec()).collect();
+        let input_refs: Vec<&[u8]> = rust_inputs.iter().map(|v| v.as_slice()).collect();
+
+        let mut callback_error = None;
+        let result = self
+            .hasher
+            .hash_batch_with_progress(&input_refs, |completed, total| {
+                let args = (JsValue::from(completed as f64), JsValue::from(total as f64));
+                if let Err(err) = callback.call2(&JsValue::NULL, &args.0, &args.1) {
+                    callback_error.get_or_insert(err);
+                }
+            })
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Batch hashing failed: {e}")))?;
+        if let Some(err) = callback_error {
+            return Err(err);
+        }
+
+        // Split result into individual hashes
+        let result_array = Array::new();
+        for chunk in result.chunks(self.variant.output_bytes().max(1)) {
+            result_array.push(&Uint8Array::from(chunk));
+        }
+
+        Ok(result_array)
+    }
+
     /// Hash a batch with custom output length (for SHAKE variants only)
     ///
     /// # Arguments
@@ -164,12 +216,20 @@ impl Sha3WasmHasher {
     ///
     /// # Returns
     /// Array of Uint8Array hashes with specified length
+    ///
+    /// Fails upfront if `output_length` exceeds the hasher's limit (see `setMaxOutputLength`).
     #[wasm_bindgen(js_name = hashBatchWithLength)]
     pub async fn hash_batch_with_length(
         &mut self,
         inputs: &Array,
         output_length: usize,
     ) -> Result<Array, JsValue> {
+        if output_length > self.max_output_length {
+            return Err(JsValue::from_str(&format!(
+                "Output length {output_length} exceeds the maximum of {} bytes per input",
+                self.max_output_length
+            )));
+        }
         if inputs.length() == 0 {
             return Ok(Array::new());
         }
@@ -206,24 +266,93 @@ impl Sha3WasmHasher {
 
         // Split result into individual hashes
         let result_array = Array::new();
-        for chunk in result.chunks(output_length) {
-            result_array.push(&Uint8Array::from(chunk));
+        let digests = params
+            .split_output(&result)
+            .map_err(|e| JsValue::from_str(&format!("Batch hashing failed: {e}")))?;
+        for digest in digests {
+            result_array.push(&Uint8Array::from(digest));
+        }
+
+        Ok(result_array)
+    }
+
+    /// Hash a batch with a separate output length per input (for SHAKE variants only)
+    ///
+    /// # Arguments
+    /// * `inputs` - JavaScript array of Uint8Array inputs (lengths may differ)
+    /// * `lengths` - Desired output length in bytes for each input
+    ///
+    /// # Returns
+    /// Array of Uint8Array hashes, `hashes[i]` being `lengths[i]` bytes long
+    ///
+    /// # Example (JavaScript)
+    /// ```javascript
+    /// const inputs = [new TextEncoder().encode("a"), new TextEncoder().encode("bc")];
+    /// const hashes = await hasher.hashBatchVarLen(inputs, new Uint32Array([16, 64]));
+    /// ```
+    #[wasm_bindgen(js_name = hashBatchVarLen)]
+    pub async fn hash_batch_var_len(
+        &mut self,
+        inputs: &Array,
+        lengths: &Uint32Array,
+    ) -> Result<Array, JsValue> {
+        if !matches!(self.variant, Sha3Variant::Shake128 | Sha3Variant::Shake256) {
+            return Err(JsValue::from_str("hashBatchVarLen requires a SHAKE variant"));
+        }
+        if inputs.length() != lengths.length() {
+            return Err(JsValue::from_str(&format!(
+                "inputs.length ({}) must equal lengths.length ({})",
+                inputs.length(),
+                lengths.length()
+            )));
+        }
+
+        let output_lengths: Vec<usize> = lengths.to_vec().into_iter().map(|l| l as usize).collect();
+        if let Some(&length) = output_lengths.iter().find(|&&l| l > self.max_output_length) {
+            return Err(JsValue::from_str(&format!(
+                "Output length {length} exceeds the maximum of {} bytes per input",
+                self.max_output_length
+            )));
+        }
+
+        // Convert JS arrays to Rust vectors
+        let rust_inputs: Vec<Vec<u8>> =
+            (0..inputs.length()).map(|i| Uint8Array::from(inputs.get(i)).to_vec()).collect();
+        let input_refs: Vec<&[u8]> = rust_inputs.iter().map(|v| v.as_slice()).collect();
+
+        let digests = self
+            .hasher
+            .hash_batch_with_output_lengths(&input_refs, &output_lengths)
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Batch hashing failed: {e}")))?;
+
+        let result_array = Array::new();
+        for digest in &digests {
+            result_array.push(&Uint8Array::from(&digest[..]));
         }
 
         Ok(result_array)
     }
 
+    /// Set the largest output length `hashBatchWithLength` accepts per input
+    ///
+    /// Defaults to 1 MiB. Raise it only if the browser's WebGPU buffer limits allow
+    /// `inputs.length * outputLength` bytes of output.
+    #[wasm_bindgen(js_name = setMaxOutputLength)]
+    pub fn set_max_output_length(&mut self, max_output_length: usize) {
+        self.max_output_length = max_output_length;
+    }
+
+    /// Get the largest output length `hashBatchWithLength` accepts per input
+    #[wasm_bindgen(js_name = maxOutputLength)]
+    pub fn max_output_length(&self) -> usize {
+        self.max_output_length
+    }
+
     /// Get the SHA-3 variant name
     #[wasm_bindgen(js_name = getVariant)]
     pub fn get_variant(&self) -> String {
-        match self.variant {
-            Sha3Variant::Sha3_224 => "sha3-224".to_string(),
-            Sha3Variant::Sha3_256 => "sha3-256".to_string(),
-            Sha3Variant::Sha3_384 => "sha3-384".to_string(),
-            Sha3Variant::Sha3_512 => "sha3-512".to_string(),
-            Sha3Variant::Shake128 => "shake128".to_string(),
-            Sha3Variant::Shake256 => "shake256".to_string(),
-        }
+        self.variant.to_string()
     }
 
     /// Get the output size in bytes (0 for SHAKE variants)
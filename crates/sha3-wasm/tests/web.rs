@@ -1,8 +1,9 @@
 //! WASM tests using wasm-bindgen-test
 //! Comprehensive test suite for sha3-wasm JavaScript/WASM bindings
 
-use js_sys::{Array, Uint8Array};
+use js_sys::{Array, Uint32Array, Uint8Array};
 use sha3_wasm::{sha3, sha3_batch, Sha3WasmHasher};
+use wasm_bindgen::{closure::Closure, JsCast};
 use wasm_bindgen_test::*;
 
 wasm_bindgen_test_configure!(run_in_browser);
@@ -162,6 +163,50 @@ async fn test_hasher_new_variant_with_underscore() {
     assert_eq!(hasher.get_variant(), "sha3-256");
 }
 
+#[wasm_bindgen_test]
+async fn test_hasher_new_all_variant_aliases() {
+    // Every accepted spelling normalizes to the canonical variant name
+    let aliases = [
+        ("sha3-224", "sha3-224"),
+        ("SHA3-224", "sha3-224"),
+        ("sha3_224", "sha3-224"),
+        ("SHA3_224", "sha3-224"),
+        ("sha3-256", "sha3-256"),
+        ("SHA3-256", "sha3-256"),
+        ("sha3_256", "sha3-256"),
+        ("SHA3_256", "sha3-256"),
+        ("sha3-384", "sha3-384"),
+        ("SHA3-384", "sha3-384"),
+        ("sha3_384", "sha3-384"),
+        ("SHA3_384", "sha3-384"),
+        ("sha3-512", "sha3-512"),
+        ("SHA3-512", "sha3-512"),
+        ("sha3_512", "sha3-512"),
+        ("SHA3_512", "sha3-512"),
+        ("shake128", "shake128"),
+        ("SHAKE128", "shake128"),
+        ("shake-128", "shake128"),
+        ("SHAKE-128", "shake128"),
+        ("shake_128", "shake128"),
+        ("SHAKE_128", "shake128"),
+        ("shake256", "shake256"),
+        ("SHAKE256", "shake256"),
+        ("shake-256", "shake256"),
+        ("SHAKE-256", "shake256"),
+        ("shake_256", "shake256"),
+        ("SHAKE_256", "shake256"),
+        ("keccak256", "keccak256"),
+        ("KECCAK-256", "keccak256"),
+        ("keccak_512", "keccak512"),
+    ];
+
+    for (alias, canonical) in aliases {
+        let hasher = Sha3WasmHasher::new(alias).await;
+        assert!(hasher.is_ok(), "Alias {alias} was rejected");
+        assert_eq!(hasher.unwrap().get_variant(), canonical, "Alias {alias} mapped incorrectly");
+    }
+}
+
 // ============================================================================
 // Error Handling Tests
 // ============================================================================
@@ -447,6 +492,98 @@ async fn test_hash_batch_with_length_shake256() {
     }
 }
 
+#[wasm_bindgen_test]
+async fn test_hash_batch_with_length_over_limit_error() {
+    let mut hasher = Sha3WasmHasher::new("shake256").await.unwrap();
+    let inputs = to_js_array(&[b"test"]);
+    let limit = hasher.max_output_length();
+
+    let result = hasher.hash_batch_with_length(&inputs, limit + 1).await;
+    let message = result.err().and_then(|err| err.as_string()).expect("string error");
+    assert!(message.contains(&limit.to_string()), "Unexpected error message: {message}");
+
+    // A raised limit lets the same request through
+    hasher.set_max_output_length(limit + 1);
+    let hashes = hasher.hash_batch_with_length(&inputs, limit + 1).await.unwrap();
+    assert_eq!(Uint8Array::from(hashes.get(0)).length() as usize, limit + 1);
+}
+
+#[wasm_bindgen_test]
+async fn test_hash_batch_with_progress() {
+    let mut hasher = Sha3WasmHasher::new("sha3-256").await.unwrap();
+    let inputs = to_js_array(&[b"abc", b"def", b"ghi"]);
+    let reports = Array::new();
+    let report = {
+        let reports = reports.clone();
+        Closure::<dyn FnMut(f64, f64)>::new(move |completed: f64, total: f64| {
+            reports.push(&Array::of2(&completed.into(), &total.into()));
+        })
+    };
+
+    let hashes =
+        hasher.hash_batch_with_progress(&inputs, report.as_ref().unchecked_ref()).await.unwrap();
+    assert_eq!(hashes.length(), 3);
+    assert_eq!(
+        to_hex(&from_uint8_array(&Uint8Array::from(hashes.get(0)))),
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+    );
+    // The batch fits one submission, so the callback reports once, at completion
+    assert_eq!(reports.length(), 1);
+    let last = Array::from(&reports.get(0));
+    assert_eq!((last.get(0).as_f64(), last.get(1).as_f64()), (Some(3.0), Some(3.0)));
+}
+
+#[wasm_bindgen_test]
+async fn test_hash_batch_with_length_absurd_length_error() {
+    let mut hasher = Sha3WasmHasher::new("shake256").await.unwrap();
+    let inputs = to_js_array(&[b"test", b"more"]);
+
+    let result = hasher.hash_batch_with_length(&inputs, usize::MAX).await;
+    assert!(result.err().and_then(|err| err.as_string()).is_some());
+
+    // Past the per-input cap, the batch total is still checked before allocating
+    hasher.set_max_output_length(usize::MAX);
+    let result = hasher.hash_batch_with_length(&inputs, usize::MAX).await;
+    let message = result.err().and_then(|err| err.as_string()).expect("string error");
+    assert!(message.contains("output length"), "Unexpected error message: {message}");
+}
+
+#[wasm_bindgen_test]
+async fn test_error_shake_without_output_length() {
+    let mut hasher = Sha3WasmHasher::new("shake128").await.unwrap();
+    let inputs = to_js_array(&[b"test"]);
+
+    let result = hasher.hash_batch(&inputs).await;
+    let message = result.err().and_then(|err| err.as_string()).expect("string error");
+    assert!(message.contains("Missing output length"), "Unexpected error message: {message}");
+
+    let result = hasher.hash_batch_with_length(&inputs, 0).await;
+    let message = result.err().and_then(|err| err.as_string()).expect("string error");
+    assert!(message.contains("Invalid output length: 0"), "Unexpected error message: {message}");
+}
+
+#[wasm_bindgen_test]
+async fn test_hash_batch_var_len() {
+    let mut hasher = Sha3WasmHasher::new("shake128").await.unwrap();
+    let inputs: [&[u8]; 3] = [b"a", b"bc", b"def"];
+    let lengths = [16u32, 32, 200];
+    let hashes =
+        hasher.hash_batch_var_len(&to_js_array(&inputs), &Uint32Array::from(&lengths[..])).await;
+    let hashes = hashes.unwrap();
+    assert_eq!(hashes.length(), 3);
+
+    for (i, (input, length)) in inputs.iter().zip(lengths).enumerate() {
+        let hash = from_uint8_array(&Uint8Array::from(hashes.get(i as u32)));
+        let single = hasher.hash_batch_with_length(&to_js_array(&[input]), length as usize).await;
+        assert_eq!(hash, from_uint8_array(&Uint8Array::from(single.unwrap().get(0))));
+    }
+
+    let mismatched =
+        hasher.hash_batch_var_len(&to_js_array(&inputs), &Uint32Array::from(&lengths[..2])).await;
+    let message = mismatched.err().and_then(|err| err.as_string()).expect("string error");
+    assert!(message.contains("lengths.length"), "Unexpected error message: {message}");
+}
+
 #[wasm_bindgen_test]
 async fn test_hash_batch_with_length_custom_sizes() {
     let mut hasher = Sha3WasmHasher::new("shake128").await.unwrap();
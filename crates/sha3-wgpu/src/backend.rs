@@ -0,0 +1,170 @@
+//! Backend-agnostic hashing interface over the GPU and CPU implementations
+
+use sha3_core::{BatchHashParams, Sha3Variant};
+
+use crate::compute::{cpu_hash_batch, GpuSha3Hasher};
+use crate::context::GpuContext;
+use crate::error::GpuSha3Error;
+
+/// Boxed future of the flattened digests returned by [`Sha3Backend::hash_batch`]
+///
+/// `Send` on native targets; on WebAssembly GPU handles aren't `Send`, so neither is this.
+#[cfg(not(target_arch = "wasm32"))]
+pub type BackendFuture<'a> = futures::future::BoxFuture<'a, Result<Vec<u8>, GpuSha3Error>>;
+
+/// Boxed future of the flattened digests returned by [`Sha3Backend::hash_batch`]
+///
+/// `Send` on native targets; on WebAssembly GPU handles aren't `Send`, so neither is this.
+#[cfg(target_arch = "wasm32")]
+pub type BackendFuture<'a> = futures::future::LocalBoxFuture<'a, Result<Vec<u8>, GpuSha3Error>>;
+
+/// A SHA-3 batch hashing implementation
+///
+/// Lets downstream code be written once against GPU, CPU or mock backends. The trait is
+/// object safe, so backends can be chosen at runtime:
+///
+/// ```rust
+/// use sha3_core::{BatchHashParams, Sha3Variant};
+/// use sha3_wgpu::{CpuSha3Hasher, Sha3Backend};
+///
+/// // e.g. a `GpuSha3Hasher` when a GPU context could be created
+/// let backend: Box<dyn Sha3Backend> = Box::new(CpuSha3Hasher::new(Sha3Variant::Sha3_256));
+///
+/// let params = BatchHashParams::new(backend.variant(), 2, 3);
+/// let digests = pollster::block_on(backend.hash_batch(&[b"abc", b"xyz"], &params)).unwrap();
+/// assert_eq!(digests.len(), 2 * 32);
+/// ```
+pub trait Sha3Backend {
+    /// Hash a batch of inputs described by `params`, returning the flattened digests
+    fn hash_batch<'a>(
+        &'a self,
+        inputs: &'a [&'a [u8]],
+        params: &'a BatchHashParams,
+    ) -> BackendFuture<'a>;
+
+    /// The SHA-3 variant this backend hashes with by default
+    fn variant(&self) -> Sha3Variant;
+}
+
+impl Sha3Backend for GpuSha3Hasher {
+    fn hash_batch<'a>(
+        &'a self,
+        inputs: &'a [&'a [u8]],
+        params: &'a BatchHashParams,
+    ) -> BackendFuture<'a> {
+        Box::pin(self.hash_batch_with_params(inputs, params))
+    }
+
+    fn variant(&self) -> Sha3Variant {
+        GpuSha3Hasher::variant(self)
+    }
+}
+
+/// SHA-3 batch hasher running on the CPU, with no GPU context
+///
+/// Re-exported from [`sha3_core::cpu`] so it can stand in for `GpuSha3Hasher` behind
+/// [`Sha3Backend`] without a separate dependency on `sha3-core`. As a backend it reports
+/// malformed batches with this crate's errors, exactly like the GPU hasher.
+pub use sha3_core::cpu::CpuSha3Hasher;
+
+impl Sha3Backend for CpuSha3Hasher {
+    fn hash_batch<'a>(
+        &'a self,
+        inputs: &'a [&'a [u8]],
+        params: &'a BatchHashParams,
+    ) -> BackendFuture<'a> {
+        Box::pin(async move { cpu_hash_batch(&[], inputs, params) })
+    }
+
+    fn variant(&self) -> Sha3Variant {
+        CpuSha3Hasher::variant(self)
+    }
+}
+
+/// A hasher on the GPU when one is available, otherwise on the CPU
+///
+/// Built by [`GpuSha3Hasher::new_with_fallback`]. Both backends produce identical
+/// digests and errors through the same methods, so callers needn't branch on which one is
+/// active; [`is_gpu`](Self::is_gpu) reports it for logging.
+#[derive(Debug)]
+pub enum FallbackSha3Hasher {
+    /// GPU context creation succeeded
+    Gpu(Box<GpuSha3Hasher>),
+    /// No usable GPU; hashing runs on the reference implementation
+    Cpu(CpuSha3Hasher),
+}
+
+impl GpuSha3Hasher {
+    /// Create a hasher on the default GPU, or on the CPU if none can be initialized
+    ///
+    /// Headless and CI machines often have no adapter; rather than failing with
+    /// `AdapterNotFound`, this returns a CPU-backed hasher with the same API.
+    pub async fn new_with_fallback(variant: Sha3Variant) -> FallbackSha3Hasher {
+        FallbackSha3Hasher::from_context(GpuContext::new().await, variant)
+    }
+}
+
+impl FallbackSha3Hasher {
+    /// Hash on `context` if it was created and accepts `variant`, otherwise on the CPU
+    pub(crate) fn from_context(
+        context: Result<GpuContext, GpuSha3Error>,
+        variant: Sha3Variant,
+    ) -> Self {
+        match context.and_then(|context| GpuSha3Hasher::new(context, variant)) {
+            Ok(hasher) => Self::Gpu(Box::new(hasher)),
+            Err(_) => Self::Cpu(CpuSha3Hasher::new(variant)),
+        }
+    }
+
+    /// Whether hashing runs on the GPU
+    pub fn is_gpu(&self) -> bool {
+        matches!(self, Self::Gpu(_))
+    }
+
+    /// Hash a batch of inputs (all must be the same length)
+    /// Returns a flattened vector of all output hashes
+    pub async fn hash_batch(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        match self {
+            Self::Gpu(hasher) => hasher.hash_batch(inputs).await,
+            Self::Cpu(hasher) => {
+                let input_length = inputs.first().map_or(0, |input| input.len());
+                let params = BatchHashParams::new(hasher.variant(), inputs.len(), input_length);
+                cpu_hash_batch(&[], inputs, &params)
+            }
+        }
+    }
+
+    /// Hash a batch with custom parameters (for SHAKE variants with custom output length)
+    pub async fn hash_batch_with_params(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        match self {
+            Self::Gpu(hasher) => hasher.hash_batch_with_params(inputs, params).await,
+            Self::Cpu(_) => cpu_hash_batch(&[], inputs, params),
+        }
+    }
+
+    /// Get the SHA-3 variant this hasher uses
+    pub fn variant(&self) -> Sha3Variant {
+        match self {
+            Self::Gpu(hasher) => hasher.variant(),
+            Self::Cpu(hasher) => hasher.variant(),
+        }
+    }
+}
+
+impl Sha3Backend for FallbackSha3Hasher {
+    fn hash_batch<'a>(
+        &'a self,
+        inputs: &'a [&'a [u8]],
+        params: &'a BatchHashParams,
+    ) -> BackendFuture<'a> {
+        Box::pin(self.hash_batch_with_params(inputs, params))
+    }
+
+    fn variant(&self) -> Sha3Variant {
+        FallbackSha3Hasher::variant(self)
+    }
+}
@@ -0,0 +1,135 @@
+//! Coalescing individual hash requests into GPU batches
+
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use futures::channel::oneshot;
+use sha3_core::Sha3Variant;
+
+use crate::compute::{host_copy, GpuSha3Hasher, HostCopy};
+use crate::error::GpuSha3Error;
+
+/// Channel resolving one caller's future with its digest
+type Reply = oneshot::Sender<Result<Vec<u8>, GpuSha3Error>>;
+
+/// One caller's input and where its digest goes
+struct Request {
+    data: HostCopy,
+    reply: Reply,
+}
+
+/// Hasher turning concurrent single-input [`hash`](Self::hash) calls into batches
+///
+/// Each dispatch has a fixed cost, so a server hashing one input per request wastes most
+/// of the GPU. Requests are queued to a worker thread, which waits up to `window` after
+/// the first one for up to `max_batch_size` to arrive, hashes them as one
+/// [`hash_batch_grouped`](GpuSha3Hasher::hash_batch_grouped) call (so inputs may differ in
+/// length), and resolves each caller's future with its own digest. A lone request thus
+/// waits at most `window` longer than hashing it directly; under load, batches fill up
+/// before the window ends.
+///
+/// The futures don't depend on an async runtime. Dropping the hasher lets the worker
+/// finish the requests already queued, and waits for it to exit.
+pub struct BatchingHasher {
+    hasher: Arc<GpuSha3Hasher>,
+    /// Queue to the worker (`None` once dropping); locked only to send
+    requests: Option<Mutex<Sender<Request>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl BatchingHasher {
+    /// Batch requests to `hasher`, at most `max_batch_size` (at least one) per dispatch,
+    /// waiting up to `window` after a batch's first request for more
+    ///
+    /// Fails with `Io` if the worker thread can't be spawned.
+    pub fn new(
+        hasher: GpuSha3Hasher,
+        max_batch_size: usize,
+        window: Duration,
+    ) -> Result<Self, GpuSha3Error> {
+        let hasher = Arc::new(hasher);
+        let (sender, receiver) = mpsc::channel();
+        let worker_hasher = Arc::clone(&hasher);
+        let worker = std::thread::Builder::new()
+            .name("sha3-wgpu-batching".into())
+            .spawn(move || run_batches(&worker_hasher, &receiver, max_batch_size.max(1), window))?;
+        Ok(Self { hasher, requests: Some(Mutex::new(sender)), worker: Some(worker) })
+    }
+
+    /// Hash `data`, batched with the requests arriving around the same time
+    ///
+    /// Resolves to the same digest as hashing `data` alone on the wrapped hasher. If the
+    /// batch it joined fails, the request is retried on its own, so each caller sees the
+    /// error for its own input.
+    pub async fn hash(&self, data: &[u8]) -> Result<Vec<u8>, GpuSha3Error> {
+        let worker_exited =
+            || GpuSha3Error::GpuOperationFailed("Batching worker exited unexpectedly".into());
+        let (reply, receiver) = oneshot::channel();
+        let request = Request { data: host_copy(data.to_vec()), reply };
+        let requests = self.requests.as_ref().ok_or_else(worker_exited)?;
+        requests.lock().map_err(|_| worker_exited())?.send(request).map_err(|_| worker_exited())?;
+        receiver.await.map_err(|_| worker_exited())?
+    }
+
+    /// The hasher batches are dispatched to, e.g. for its `session_stats`
+    pub fn hasher(&self) -> &GpuSha3Hasher {
+        &self.hasher
+    }
+
+    /// Get the SHA-3 variant this hasher uses
+    pub fn variant(&self) -> Sha3Variant {
+        self.hasher.variant()
+    }
+}
+
+impl Drop for BatchingHasher {
+    fn drop(&mut self) {
+        // Closing the queue ends the worker once it has drained the requests already sent
+        self.requests.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Worker loop: collect a batch from `requests`, hash it, reply, until the queue closes
+fn run_batches(
+    hasher: &GpuSha3Hasher,
+    requests: &Receiver<Request>,
+    max_batch_size: usize,
+    window: Duration,
+) {
+    while let Ok(first) = requests.recv() {
+        let deadline = Instant::now() + window;
+        let mut batch = vec![first];
+        while batch.len() < max_batch_size {
+            match requests.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(request) => batch.push(request),
+                Err(RecvTimeoutError::Timeout | RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        futures::executor::block_on(hash_requests(hasher, batch));
+    }
+}
+
+/// Hash `batch` in one dispatch and send each request its digest
+async fn hash_requests(hasher: &GpuSha3Hasher, batch: Vec<Request>) {
+    let inputs: Vec<&[u8]> = batch.iter().map(|request| request.data.as_slice()).collect();
+    match hasher.hash_batch_grouped(&inputs).await {
+        Ok(digests) => {
+            let output_bytes = digests.len() / batch.len();
+            for (request, digest) in batch.into_iter().zip(digests.chunks(output_bytes)) {
+                // A caller that dropped its future no longer wants the digest
+                let _ = request.reply.send(Ok(digest.to_vec()));
+            }
+        }
+        Err(_) => {
+            for request in batch {
+                let result = hasher.hash_batch(&[request.data.as_slice()]).await;
+                let _ = request.reply.send(result);
+            }
+        }
+    }
+}
@@ -1,17 +1,68 @@
 //! GPU compute pipeline for SHA-3 batch hashing
 
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, TryLockError};
+use std::time::Duration;
+
 use futures::channel::oneshot;
-use sha3::digest::{Digest, ExtendableOutput, Update, XofReader};
-use sha3_core::{BatchHashParams, Sha3Variant};
+use futures::lock::Mutex;
+use sha3_core::encoding as sp800_185;
+use sha3_core::{ct_eq, BatchHashParams, HashResult, PreparedShape, Sha3Error, Sha3Variant};
 use wgpu::util::DeviceExt;
 use wgpu::*;
 
-use crate::{context::GpuContext, error::GpuSha3Error};
+use crate::{context::GpuContext, encoding, error::GpuSha3Error};
+use resident::ResidentBinding;
+use stats::{BatchPath, SessionCounters};
+
+mod argmin;
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+mod blocking;
+mod builder;
+mod chunked;
+mod derived;
+mod hmac;
+mod merkle;
+mod permute;
+#[cfg(not(target_arch = "wasm32"))]
+mod poller;
+mod prefixed;
+mod prepared;
+mod progress;
+mod record;
+mod resident;
+mod retained;
+mod select;
+mod stats;
+mod stream;
+#[cfg(test)]
+mod tests;
+mod texture;
+mod timing;
+#[cfg(not(target_arch = "wasm32"))]
+mod tune;
+
+pub use builder::GpuSha3HasherBuilder;
+pub use prepared::PreparedHasher;
+pub use retained::GpuBufferHandle;
+pub use stats::{BufferStats, SessionStats};
+pub use stream::{GpuSha3Stream, GpuSha3Writer, LogHasher};
+pub use texture::TextureRegion;
 
 /// Configuration for persistent buffer allocation
 /// (max_batch_size, max_input_length, max_output_bytes)
 type PersistentBufferConfig = (usize, usize, usize);
 
+/// One entry of a map hashed by [`GpuSha3Hasher::hash_kv_pairs`]: `(key, value)`
+pub type KeyValuePair<'a> = (&'a [u8], &'a [u8]);
+
+/// One sequence of byte strings hashed by [`GpuSha3Hasher::tuple_hash`]
+pub type Tuple<'a> = &'a [&'a [u8]];
+
+/// One input of [`GpuSha3Hasher::hash_with_ids`]: `(id, data)`
+pub type IdentifiedInput<'a, I> = (I, &'a [u8]);
+
 /// Parameters for persistent buffer hashing operation
 #[derive(Debug)]
 struct PersistentHashParams<'a> {
@@ -24,10 +75,162 @@ struct PersistentHashParams<'a> {
 // Include the WGSL shader at compile time
 const SHADER_SOURCE: &str = include_str!("wgsl/sha3.wgsl");
 
-/// Maximum input size per hash in bytes (must match MAX_INPUT_SIZE in WGSL shader)
-const MAX_INPUT_SIZE: usize = 8192;
+/// Debug entry point recording thread ids, appended to `SHADER_SOURCE` when in use
+#[cfg(feature = "debug-state")]
+const DEBUG_IDS_SHADER_SOURCE: &str = include_str!("wgsl/debug_ids.wgsl");
+
+/// Input capacity of the `hash_one_fast` buffer set; longer inputs take the batch path
+const FAST_PATH_INPUT_CAPACITY: usize = 8192;
+
+/// Smallest `hash_batch` batch checked for being one input repeated
+const IDENTICAL_BATCH_MIN: usize = 64;
+
+/// Times a batch is re-submitted after a failed readback mapping, unless configured
+const DEFAULT_MAPPING_RETRIES: u32 = 2;
+
+/// Staging buffers per persistent buffer set, unless configured (see `with_staging_buffers`)
+const DEFAULT_STAGING_BUFFERS: usize = 2;
+
+/// Size of the Keccak-f[1600] state in bytes
+const STATE_BYTES: usize = 200;
+
+/// Compute shader entry point (must match the `@compute` function in the WGSL shader)
+const ENTRY_POINT: &str = "main";
+
+/// Streaming absorb entry point (must match the `absorb` function in the WGSL shader)
+const ABSORB_ENTRY_POINT: &str = "absorb";
+
+/// Shader entry point appending each digest of a finished batch to its input
+const INTERLEAVE_ENTRY_POINT: &str = "interleave_digests";
+
+/// Shader entry point applying the bare Keccak-f[1600] permutation to a batch of states
+const PERMUTE_ENTRY_POINT: &str = "permute";
+
+/// Shader entry point running one level of the smallest-digest reduction
+const ARGMIN_ENTRY_POINT: &str = "argmin_pass";
+
+/// Threads per workgroup unless configured (must match the default of the WGSL shader's
+/// `override WORKGROUP_SIZE`)
+const DEFAULT_WORKGROUP_SIZE: u32 = 256;
+
+/// Name of the pipeline-overridable constant sizing the shader's workgroups
+const WORKGROUP_SIZE_CONSTANT: &str = "WORKGROUP_SIZE";
+
+/// Whether every input (all of one length) equals the first
+///
+/// Checks the middle and last inputs before scanning the rest, so typical batches of
+/// distinct inputs are rejected after two comparisons.
+fn all_identical(inputs: &[&[u8]]) -> bool {
+    let first = inputs[0];
+    let same = |input: &[u8]| std::ptr::eq(input.as_ptr(), first.as_ptr()) || input == first;
+    same(inputs[inputs.len() / 2])
+        && same(inputs[inputs.len() - 1])
+        && inputs.iter().all(|input| same(input))
+}
+
+/// Grid of `(x, y)` workgroups covering `num_workgroups`, at most `row_len` per row
+///
+/// Batches fitting in one row stay 1D; larger ones become rows of `row_len` workgroups
+/// (the last row partly idle, caught by the shader's bounds check).
+fn dispatch_grid(num_workgroups: u32, row_len: u32) -> (u32, u32) {
+    if num_workgroups <= row_len {
+        (num_workgroups, 1)
+    } else {
+        (row_len, (num_workgroups + row_len - 1) / row_len)
+    }
+}
+
+/// Snapshot of how a hasher's compute pipeline was built, for bug reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineDebugInfo {
+    /// Shader entry point the pipeline was compiled with
+    pub entry_point: &'static str,
+    /// SHA-3 variant the pipeline hashes
+    pub variant: Sha3Variant,
+    /// Threads per workgroup
+    pub workgroup_size: u32,
+    /// Features enabled on the device the pipeline runs on
+    pub features: Features,
+}
+
+/// A prefix absorbed once into the sponge state and shared by every hash
+#[derive(Debug, Clone)]
+struct FixedPrefix {
+    /// The full prefix (used by the CPU fallback path)
+    bytes: HostCopy,
+    /// Number of leading prefix bytes already absorbed into `state`
+    absorbed: usize,
+    /// Sponge state after absorbing every complete rate block of the prefix
+    state: [u64; 25],
+}
+
+impl FixedPrefix {
+    /// Absorb all complete rate blocks of `prefix` into a fresh sponge state
+    fn new(prefix: HostCopy, rate_bytes: usize) -> Self {
+        let absorbed = prefix.len() / rate_bytes * rate_bytes;
+        let mut state = [0u64; 25];
+        for block in prefix[..absorbed].chunks_exact(rate_bytes) {
+            for (lane, bytes) in state.iter_mut().zip(block.chunks_exact(8)) {
+                *lane ^= u64::from_le_bytes(bytes.try_into().expect("8-byte lane"));
+            }
+            keccak::f1600(&mut state);
+        }
+        Self { bytes: prefix, absorbed, state }
+    }
+
+    /// Prefix bytes that didn't fill a complete rate block and must precede each input
+    fn tail(&self) -> &[u8] {
+        &self.bytes[self.absorbed..]
+    }
+}
+
+#[cfg(feature = "secure")]
+impl Drop for FixedPrefix {
+    fn drop(&mut self) {
+        // `bytes` wipes itself; the state is a function of the prefix alone
+        zeroize::Zeroize::zeroize(&mut self.state);
+    }
+}
+
+/// Whether the WGSL shader computes `variant` correctly
+///
+/// Deliberately exhaustive: a newly added variant must be classified here before the crate
+/// compiles, so it can't silently reach the GPU with the wrong padding.
+fn shader_supports(variant: Sha3Variant) -> bool {
+    match variant {
+        // The domain byte is passed in the uniform params, so every sponge pads correctly
+        Sha3Variant::Sha3_224
+        | Sha3Variant::Sha3_256
+        | Sha3Variant::Sha3_384
+        | Sha3Variant::Sha3_512
+        | Sha3Variant::Shake128
+        | Sha3Variant::Shake256
+        | Sha3Variant::Keccak256
+        | Sha3Variant::Keccak512
+        | Sha3Variant::CShake128
+        | Sha3Variant::CShake256 => true,
+    }
+}
+
+/// Convert a Keccak state into the (high, low) u32 pair layout used by the shader
+fn state_to_gpu_words(state: &[u64; 25]) -> [u32; 50] {
+    let mut words = [0u32; 50];
+    for (pair, lane) in words.chunks_exact_mut(2).zip(state) {
+        pair[0] = (lane >> 32) as u32;
+        pair[1] = *lane as u32;
+    }
+    words
+}
 
-fn cpu_hash_batch(inputs: &[&[u8]], params: &BatchHashParams) -> Result<Vec<u8>, GpuSha3Error> {
+/// Hash each `prefix || input` on the CPU using the reference `sha3` crate
+///
+/// Delegates to [`sha3_core::cpu::hash_batch`], reporting malformed batches as this crate's
+/// errors.
+pub(crate) fn cpu_hash_batch(
+    prefix: &[u8],
+    inputs: &[&[u8]],
+    params: &BatchHashParams,
+) -> Result<Vec<u8>, GpuSha3Error> {
     if inputs.is_empty() {
         return Ok(Vec::new());
     }
@@ -40,75 +243,79 @@ fn cpu_hash_batch(inputs: &[&[u8]], params: &BatchHashParams) -> Result<Vec<u8>,
         return Err(GpuSha3Error::InvalidInputLength(params.input_length));
     }
 
-    let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
-    let mut output = Vec::with_capacity(inputs.len() * output_bytes);
-
-    match params.variant {
-        Sha3Variant::Sha3_224 => {
-            for input in inputs {
-                let mut hasher = sha3::Sha3_224::default();
-                Update::update(&mut hasher, input);
-                let digest = Digest::finalize(hasher);
-                output.extend_from_slice(digest.as_ref());
-            }
-        }
-        Sha3Variant::Sha3_256 => {
-            for input in inputs {
-                let mut hasher = sha3::Sha3_256::default();
-                Update::update(&mut hasher, input);
-                let digest = Digest::finalize(hasher);
-                output.extend_from_slice(digest.as_ref());
-            }
-        }
-        Sha3Variant::Sha3_384 => {
-            for input in inputs {
-                let mut hasher = sha3::Sha3_384::default();
-                Update::update(&mut hasher, input);
-                let digest = Digest::finalize(hasher);
-                output.extend_from_slice(digest.as_ref());
-            }
-        }
-        Sha3Variant::Sha3_512 => {
-            for input in inputs {
-                let mut hasher = sha3::Sha3_512::default();
-                Update::update(&mut hasher, input);
-                let digest = Digest::finalize(hasher);
-                output.extend_from_slice(digest.as_ref());
-            }
-        }
-        Sha3Variant::Shake128 => {
-            for input in inputs {
-                let mut hasher = sha3::Shake128::default();
-                Update::update(&mut hasher, input);
-                let mut reader = ExtendableOutput::finalize_xof(hasher);
-                let mut buf = vec![0u8; output_bytes];
-                reader.read(&mut buf);
-                output.extend_from_slice(&buf);
-            }
-        }
-        Sha3Variant::Shake256 => {
-            for input in inputs {
-                let mut hasher = sha3::Shake256::default();
-                Update::update(&mut hasher, input);
-                let mut reader = ExtendableOutput::finalize_xof(hasher);
-                let mut buf = vec![0u8; output_bytes];
-                reader.read(&mut buf);
-                output.extend_from_slice(&buf);
-            }
-        }
-    }
-
-    Ok(output)
+    sha3_core::cpu::hash_batch(prefix, inputs, params).map_err(GpuSha3Error::Core)
 }
 
-/// GPU parameters structure matching WGSL uniform
+/// Shader parameters for one batch, the contents of the uniform at binding 2
+///
+/// The WGSL `HashParams` struct is 32 bytes: eight little-endian `u32`s, in order
+/// `num_hashes`, `input_length`, `rate_bytes`, `output_bytes`, `input_layout` (0 for inputs
+/// stored back to back), `domain_separator`, `absorb_blocks` and `padded_length`. Build it
+/// with [`for_batch`](Self::for_batch) and upload [`as_bytes`](Self::as_bytes) to a buffer
+/// with `BufferUsages::UNIFORM` when binding your own buffers against
+/// [`GpuSha3Hasher::bind_group_layout`].
 #[repr(C)]
 #[derive(Debug, Clone, Copy)]
-struct GpuHashParams {
+pub struct GpuHashParams {
     num_hashes: u32,
     input_length: u32,
     rate_bytes: u32,
     output_bytes: u32,
+    /// `INPUT_LAYOUT_AOS` or `INPUT_LAYOUT_SOA`
+    input_layout: u32,
+    /// Padding domain byte (`BatchHashParams::domain_separator`)
+    domain_separator: u32,
+    /// Rate blocks per input, including padding (`PreparedShape::absorb_blocks`)
+    absorb_blocks: u32,
+    /// Padded message length, whose last byte takes the final padding bit
+    padded_length: u32,
+}
+
+/// Inputs stored one after another (`[hash0 bytes][hash1 bytes]...`)
+const INPUT_LAYOUT_AOS: u32 = 0;
+
+/// Inputs stored column-major (`[byte0 of all hashes][byte1 of all hashes]...`)
+const INPUT_LAYOUT_SOA: u32 = 1;
+
+impl GpuHashParams {
+    /// Shader parameters for `params.num_hashes` inputs of `params.input_length` bytes,
+    /// stored back to back
+    ///
+    /// Fails if `params` has no output length (a SHAKE variant without one).
+    pub fn for_batch(params: &BatchHashParams) -> Result<Self, GpuSha3Error> {
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        Ok(Self::new(params, output_bytes))
+    }
+
+    /// The uniform's bytes as the shader reads them
+    pub fn as_bytes(&self) -> &[u8] {
+        bytemuck::bytes_of(self)
+    }
+
+    /// Shader parameters for a row-major batch described by `params`
+    fn new(params: &BatchHashParams, output_bytes: usize) -> Self {
+        let shape = params.prepared_shape();
+        Self {
+            num_hashes: params.num_hashes as u32,
+            input_length: params.input_length as u32,
+            rate_bytes: params.variant.rate_bytes() as u32,
+            output_bytes: output_bytes as u32,
+            input_layout: INPUT_LAYOUT_AOS,
+            domain_separator: params.domain_separator() as u32,
+            absorb_blocks: shape.absorb_blocks() as u32,
+            padded_length: shape.padded_length() as u32,
+        }
+    }
+
+    /// The same parameters for inputs scheduled as `shape`, e.g. after prepending a tail
+    fn with_shape(self, shape: PreparedShape) -> Self {
+        Self {
+            input_length: shape.padding_offset() as u32,
+            absorb_blocks: shape.absorb_blocks() as u32,
+            padded_length: shape.padded_length() as u32,
+            ..self
+        }
+    }
 }
 
 // SAFETY: GpuHashParams is repr(C) with only u32 fields, which are Pod and Zeroable.
@@ -116,15 +323,218 @@ struct GpuHashParams {
 unsafe impl bytemuck::Pod for GpuHashParams {}
 unsafe impl bytemuck::Zeroable for GpuHashParams {}
 
+/// Size of the WGSL `HashParams` uniform struct in bytes
+const WGSL_HASH_PARAMS_SIZE: usize = 32;
+
+// A size mismatch with the WGSL uniform silently corrupts every dispatch, so refuse to build
+const _: () = assert!(std::mem::size_of::<GpuHashParams>() == WGSL_HASH_PARAMS_SIZE);
+
+/// Per-call GPU buffers for a single batch dispatch
+///
+/// The bind group keeps the uniform buffer alive until the dispatch completes.
+struct DynamicBatch {
+    input_buffer: Buffer,
+    output_buffer: Buffer,
+    staging_buffer: Buffer,
+    bind_group: BindGroup,
+    num_hashes: usize,
+    output_buffer_size: usize,
+    total_output_bytes: usize,
+}
+
+/// Concatenate inputs into one upload, zero-padded to `buffer_size` bytes
+fn flatten_inputs(inputs: &[&[u8]], buffer_size: usize) -> Vec<u8> {
+    let mut input_data = Vec::with_capacity(buffer_size);
+    for input in inputs.iter() {
+        input_data.extend_from_slice(input);
+    }
+    // Pad to required buffer size
+    input_data.resize(buffer_size, 0);
+    input_data
+}
+
+/// Host-side copy of caller data (inputs, keys, prefixes), wiped when dropped if the
+/// `secure` feature is enabled
+#[cfg(feature = "secure")]
+pub(crate) type HostCopy = zeroize::Zeroizing<Vec<u8>>;
+
+#[cfg(not(feature = "secure"))]
+pub(crate) type HostCopy = Vec<u8>;
+
+/// Take ownership of `data` as a [`HostCopy`]
+#[cfg(feature = "secure")]
+pub(crate) fn host_copy(data: Vec<u8>) -> HostCopy {
+    zeroize::Zeroizing::new(data)
+}
+
+#[cfg(not(feature = "secure"))]
+pub(crate) fn host_copy(data: Vec<u8>) -> HostCopy {
+    data
+}
+
+/// Append `data` to `copy`
+///
+/// With the `secure` feature, growing moves the bytes into a fresh allocation and wipes
+/// the old one, rather than letting the allocator release it with the bytes intact.
+#[cfg(feature = "secure")]
+pub(crate) fn extend_host_copy(copy: &mut HostCopy, data: &[u8]) {
+    if copy.capacity() - copy.len() < data.len() {
+        let mut grown = Vec::with_capacity((copy.len() + data.len()).max(copy.capacity() * 2));
+        grown.extend_from_slice(copy);
+        *copy = zeroize::Zeroizing::new(grown);
+    }
+    copy.extend_from_slice(data);
+}
+
+#[cfg(not(feature = "secure"))]
+pub(crate) fn extend_host_copy(copy: &mut HostCopy, data: &[u8]) {
+    copy.extend_from_slice(data);
+}
+
+/// Empty `copy`, wiping its bytes if the `secure` feature is enabled
+#[cfg(feature = "secure")]
+pub(crate) fn clear_host_copy(copy: &mut HostCopy) {
+    zeroize::Zeroize::zeroize(&mut **copy);
+}
+
+#[cfg(not(feature = "secure"))]
+pub(crate) fn clear_host_copy(copy: &mut HostCopy) {
+    copy.clear();
+}
+
+/// Wipe the host-side copy of an input upload once it has been written to the GPU
+///
+/// A no-op unless the `secure` feature is enabled. `write_buffer` copies the data out
+/// before returning, so the vector can be wiped straight after.
+#[cfg(feature = "secure")]
+fn wipe_upload(input_data: &mut Vec<u8>) {
+    zeroize::Zeroize::zeroize(input_data);
+}
+
+#[cfg(not(feature = "secure"))]
+fn wipe_upload(_input_data: &mut Vec<u8>) {}
+
+/// Record zeroing the first `size` bytes of an input buffer, after the passes already
+/// recorded into `encoder` have read it
+///
+/// A no-op unless the `secure` feature is enabled. Best effort: it overwrites the buffer
+/// contents, but not copies the driver may have made while uploading.
+#[cfg(feature = "secure")]
+fn encode_input_wipe(encoder: &mut CommandEncoder, input_buffer: &Buffer, size: u64) {
+    if size > 0 {
+        encoder.clear_buffer(input_buffer, 0, Some(size));
+    }
+}
+
+#[cfg(not(feature = "secure"))]
+fn encode_input_wipe(_encoder: &mut CommandEncoder, _input_buffer: &Buffer, _size: u64) {}
+
+/// Start mapping a staging buffer for reading
+fn begin_readback(staging_buffer: &Buffer) -> oneshot::Receiver<Result<(), BufferAsyncError>> {
+    let (sender, receiver) = oneshot::channel();
+    staging_buffer.slice(..).map_async(MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    receiver
+}
+
+/// Wait for a staging buffer mapping to complete and copy out the first `len` bytes
+async fn finish_readback(
+    staging_buffer: &Buffer,
+    receiver: oneshot::Receiver<Result<(), BufferAsyncError>>,
+    len: usize,
+) -> Result<Vec<u8>, GpuSha3Error> {
+    // Wait for the mapping callback to fire
+    receiver
+        .await
+        .map_err(|_| GpuSha3Error::BufferMapping("Failed to receive buffer mapping result".into()))?
+        .map_err(|e| GpuSha3Error::BufferMapping(format!("Buffer mapping failed: {e:?}")))?;
+
+    // Extract output data
+    let data = staging_buffer.slice(..).get_mapped_range();
+    let result = data[..len].to_vec();
+
+    drop(data);
+    staging_buffer.unmap();
+
+    Ok(result)
+}
+
+/// Map the outcome of a blocking device poll to a hasher error
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn check_poll_result(result: Result<PollStatus, PollError>) -> Result<(), GpuSha3Error> {
+    match result {
+        Ok(_) => Ok(()),
+        Err(PollError::Timeout) => {
+            Err(GpuSha3Error::GpuOperationFailed("readback timed out".into()))
+        }
+        Err(e) => Err(GpuSha3Error::GpuOperationFailed(format!("Device poll failed: {e}"))),
+    }
+}
+
+/// Copy per-group digests back to their original positions in the batch output
+fn scatter_digests(output: &mut [u8], indices: &[usize], digests: &[u8], output_bytes: usize) {
+    for (&index, digest) in indices.iter().zip(digests.chunks(output_bytes)) {
+        output[index * output_bytes..(index + 1) * output_bytes].copy_from_slice(digest);
+    }
+}
+
+/// One readback buffer in a `PersistentBuffers` staging ring
+struct StagingSlot {
+    buffer: Buffer,
+    /// Set from the submission of a batch copying into `buffer` until its digests are read
+    in_use: AtomicBool,
+}
+
+impl StagingSlot {
+    fn new(device: &Device, size: u64) -> Self {
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Persistent Staging Buffer"),
+            size,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self { buffer, in_use: AtomicBool::new(false) }
+    }
+}
+
+/// A staging slot claimed by one batch, released when dropped (also when its readback fails)
+struct StagingClaim(Arc<StagingSlot>);
+
+impl StagingClaim {
+    fn buffer(&self) -> &Buffer {
+        &self.0.buffer
+    }
+}
+
+impl Drop for StagingClaim {
+    fn drop(&mut self) {
+        self.0.in_use.store(false, Ordering::Release);
+    }
+}
+
+/// A persistent-buffer batch submitted to the GPU, awaiting readback from its staging slot
+struct PendingReadback {
+    staging: StagingClaim,
+    submission: SubmissionIndex,
+    len: usize,
+}
+
 /// Persistent GPU buffers for optimized performance
 /// Reuses buffers across multiple hash operations to eliminate allocation overhead
+///
+/// Input, output and uniform buffers are shared by every batch: queue writes for a batch
+/// land after the previously submitted batches ran, so only readback needs a buffer per
+/// batch in flight, taken from the staging ring.
 struct PersistentBuffers {
     /// Input buffer (storage, read-only) - packed u32 format for efficiency
     input_buffer: Buffer,
     /// Output buffer (storage, read-write)
     output_buffer: Buffer,
-    /// Staging buffer for efficient CPU readback
-    staging_buffer: Buffer,
+    /// Staging buffers for CPU readback, one per batch awaiting readback
+    staging_ring: Vec<Arc<StagingSlot>>,
+    /// Ring position the next staging claim starts searching from
+    next_staging: AtomicUsize,
     /// Uniform buffer for parameters
     uniform_buffer: Buffer,
     /// Bind group containing all buffers
@@ -138,26 +548,38 @@ struct PersistentBuffers {
 }
 
 impl PersistentBuffers {
-    /// Create persistent buffers for the given maximum batch parameters
+    /// Create persistent buffers for the given maximum batch parameters, with
+    /// `staging_buffers` staging buffers
     fn new(
         device: &Device,
         bind_group_layout: &BindGroupLayout,
-        max_batch_size: usize,
-        max_input_length: usize,
-        max_output_bytes: usize,
+        initial_state_buffer: &Buffer,
+        (max_batch_size, max_input_length, max_output_bytes): PersistentBufferConfig,
+        staging_buffers: usize,
     ) -> Result<Self, GpuSha3Error> {
-        let total_input_bytes = max_batch_size * max_input_length;
-        let total_output_bytes = max_batch_size * max_output_bytes;
-
-        // Align buffer sizes to 16 bytes (WGSL struct alignment requirement)
-        let input_buffer_size = ((total_input_bytes + 15) / 16) * 16;
-        let output_buffer_size = ((total_output_bytes + 15) / 16) * 16;
+        let total_input_bytes = max_batch_size.saturating_mul(max_input_length);
+        let total_output_bytes = max_batch_size.saturating_mul(max_output_bytes);
+
+        // Align buffer sizes to 16 bytes (WGSL struct alignment requirement). Bindings can't
+        // be empty, so zero-length inputs (or outputs) still get a minimal buffer.
+        let input_buffer_size = total_input_bytes.saturating_add(15).max(16) / 16 * 16;
+        let output_buffer_size = total_output_bytes.saturating_add(15).max(16) / 16 * 16;
+
+        // Oversized buffers can't be bound; refuse them here rather than in a validation panic
+        let limit = device.limits().max_storage_buffer_binding_size as usize;
+        if input_buffer_size > limit {
+            return Err(GpuSha3Error::InvalidInputLength(max_input_length));
+        }
+        if output_buffer_size > limit {
+            return Err(GpuSha3Error::Core(Sha3Error::InvalidOutputLength(max_output_bytes)));
+        }
 
         // Create input buffer (storage, read-only)
         let input_buffer = device.create_buffer(&BufferDescriptor {
             label: Some("SHA-3 Persistent Input Buffer"),
             size: input_buffer_size as u64,
-            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            // COPY_SRC lets the `secure` tests read back that the buffer was zeroed
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST | BufferUsages::COPY_SRC,
             mapped_at_creation: false,
         });
 
@@ -169,13 +591,10 @@ impl PersistentBuffers {
             mapped_at_creation: false,
         });
 
-        // Create staging buffer for CPU readback (persistent for performance)
-        let staging_buffer = device.create_buffer(&BufferDescriptor {
-            label: Some("SHA-3 Persistent Staging Buffer"),
-            size: output_buffer_size as u64,
-            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        // Create staging buffers for CPU readback (persistent for performance)
+        let staging_ring = (0..staging_buffers.max(1))
+            .map(|_| Arc::new(StagingSlot::new(device, output_buffer_size as u64)))
+            .collect();
 
         // Create uniform buffer
         let uniform_buffer = device.create_buffer(&BufferDescriptor {
@@ -193,13 +612,15 @@ impl PersistentBuffers {
                 BindGroupEntry { binding: 0, resource: input_buffer.as_entire_binding() },
                 BindGroupEntry { binding: 1, resource: output_buffer.as_entire_binding() },
                 BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 3, resource: initial_state_buffer.as_entire_binding() },
             ],
         });
 
         Ok(Self {
             input_buffer,
             output_buffer,
-            staging_buffer,
+            staging_ring,
+            next_staging: AtomicUsize::new(0),
             uniform_buffer,
             bind_group,
             max_batch_size,
@@ -219,37 +640,199 @@ impl PersistentBuffers {
             && input_length <= self.max_input_length
             && output_bytes <= self.max_output_bytes
     }
+
+    /// Get the (max_batch_size, max_input_length, max_output_bytes) this buffer set was sized for
+    fn config(&self) -> PersistentBufferConfig {
+        (self.max_batch_size, self.max_input_length, self.max_output_bytes)
+    }
+
+    /// Claim the next free staging buffer, or `None` if every one awaits a readback
+    fn claim_staging(&self) -> Option<StagingClaim> {
+        let len = self.staging_ring.len();
+        let start = self.next_staging.load(Ordering::Relaxed);
+        (start..start + len).map(|i| i % len).find_map(|i| {
+            let slot = &self.staging_ring[i];
+            slot.in_use.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).ok()?;
+            self.next_staging.store(i + 1, Ordering::Relaxed);
+            Some(StagingClaim(Arc::clone(slot)))
+        })
+    }
+
+    /// Sizes of the buffers in this set
+    fn stats(&self) -> BufferStats {
+        BufferStats {
+            input_bytes: self.input_buffer.size(),
+            output_bytes: self.output_buffer.size(),
+            staging_bytes: self.staging_ring.iter().map(|slot| slot.buffer.size()).sum(),
+            uniform_bytes: self.uniform_buffer.size(),
+            staging_buffers: self.staging_ring.len(),
+            max_batch_size: self.max_batch_size,
+            max_input_length: self.max_input_length,
+            max_output_bytes: self.max_output_bytes,
+        }
+    }
+
+    /// Resize the staging ring to `count` buffers (at least one)
+    fn set_staging_buffers(&mut self, device: &Device, count: usize) {
+        let size = self.output_buffer.size();
+        self.staging_ring.resize_with(count.max(1), || Arc::new(StagingSlot::new(device, size)));
+    }
+}
+
+/// Pipelines whose entry points run in `WORKGROUP_SIZE`-wide workgroups
+struct SizedPipelines {
+    pipeline: ComputePipeline,
+    permute_pipeline: ComputePipeline,
+    interleave_pipeline: ComputePipeline,
+    argmin_pipeline: ComputePipeline,
+}
+
+impl SizedPipelines {
+    /// Build the pipelines from `shader` with `workgroup_size` threads per workgroup
+    ///
+    /// `layouts` are the batch, interleave destination and argmin index bind group layouts.
+    fn new(
+        device: &Device,
+        shader: &ShaderModule,
+        layouts: [&BindGroupLayout; 3],
+        workgroup_size: u32,
+    ) -> Self {
+        let [bind_group_layout, interleave_bind_group_layout, argmin_bind_group_layout] = layouts;
+        let constants = [(WORKGROUP_SIZE_CONSTANT, f64::from(workgroup_size))];
+        let create = |label, bind_group_layouts: &[&BindGroupLayout], entry_point| {
+            let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+                label: Some(&format!("{label} Layout")),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            });
+            device.create_compute_pipeline(&ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&layout),
+                module: shader,
+                entry_point: Some(entry_point),
+                compilation_options: PipelineCompilationOptions {
+                    constants: &constants,
+                    ..Default::default()
+                },
+                cache: None,
+            })
+        };
+
+        Self {
+            pipeline: create("SHA-3 Compute Pipeline", &[bind_group_layout], ENTRY_POINT),
+            // Raw permutation uses the inputs, outputs and parameter bindings
+            permute_pipeline: create(
+                "SHA-3 Permute Pipeline",
+                &[bind_group_layout],
+                PERMUTE_ENTRY_POINT,
+            ),
+            interleave_pipeline: create(
+                "SHA-3 Interleave Pipeline",
+                &[bind_group_layout, interleave_bind_group_layout],
+                INTERLEAVE_ENTRY_POINT,
+            ),
+            argmin_pipeline: create(
+                "SHA-3 Argmin Pipeline",
+                &[bind_group_layout, argmin_bind_group_layout],
+                ARGMIN_ENTRY_POINT,
+            ),
+        }
+    }
 }
 
 /// GPU-accelerated SHA-3 batch hasher
 pub struct GpuSha3Hasher {
     context: GpuContext,
     variant: Sha3Variant,
+    /// Compiled SHA-3 shader, kept to rebuild the workgroup-sized pipelines
+    shader: ShaderModule,
+    /// Threads per workgroup the sized pipelines were built with (see `with_workgroup_size`)
+    workgroup_size: u32,
     pipeline: ComputePipeline,
     bind_group_layout: BindGroupLayout,
+    /// Pipeline absorbing whole rate blocks into a stream's GPU-resident state
+    absorb_pipeline: ComputePipeline,
+    absorb_bind_group_layout: BindGroupLayout,
+    /// Pipeline building `input || digest` rows on the GPU (group 1 holds the destination)
+    interleave_pipeline: ComputePipeline,
+    interleave_bind_group_layout: BindGroupLayout,
+    /// Pipeline permuting raw states, bound like `pipeline`
+    permute_pipeline: ComputePipeline,
+    /// Pipeline reducing a batch's digests to the smallest one's index (group 1 holds the
+    /// index slots and stride)
+    argmin_pipeline: ComputePipeline,
+    argmin_bind_group_layout: BindGroupLayout,
+    /// Sponge state every hash starts from (zeros unless a fixed prefix was absorbed)
+    initial_state_buffer: Buffer,
+    /// Fixed prefix pre-absorbed into `initial_state_buffer` (optional)
+    prefix: Option<FixedPrefix>,
     /// Persistent buffers for performance optimization (optional)
-    buffers: Option<PersistentBuffers>,
+    ///
+    /// Locked for the duration of a dispatch; concurrent calls that find it busy fall back
+    /// to dynamic buffers instead of waiting.
+    buffers: std::sync::Mutex<Option<PersistentBuffers>>,
+    /// Single-hash buffer set for `hash_one_fast`, allocated on first use
+    single_buffers: Mutex<Option<PersistentBuffers>>,
+    /// Whether persistent buffers are (re)allocated to fit batches that exceed them
+    grow_buffers: bool,
     /// Maximum batch size for persistent buffers
     max_batch_size: usize,
+    /// Maximum time to wait for GPU work before a readback fails (None waits forever)
+    readback_timeout: Option<Duration>,
+    /// Re-submissions of a batch whose readback mapping failed (see `with_mapping_retries`)
+    mapping_retries: u32,
+    /// Staging buffers in each persistent buffer set (see `with_staging_buffers`)
+    staging_buffers: usize,
+    /// Upcoming batches whose readback reports a mapping failure (test hook)
+    #[cfg(test)]
+    injected_mapping_failures: AtomicUsize,
+    /// Which variants may be dispatched to the shader (`shader_supports` outside tests)
+    shader_supports: fn(Sha3Variant) -> bool,
+    /// Dispatches needing more workgroups than this are laid out as a 2D grid
+    dispatch_2d_threshold: u32,
+    /// Longest input (in bytes) hashed on the GPU; longer ones go to the CPU
+    gpu_threshold: usize,
+    /// Activity counters reported by `session_stats`
+    stats: SessionCounters,
+    /// Bind groups for caller-owned input buffers (see `hash_batch_from_buffer`)
+    bind_group_cache: Mutex<HashMap<Buffer, ResidentBinding>>,
+    /// Bind groups built for `bind_group_cache` so far
+    bind_group_builds: AtomicUsize,
+    /// Thread running the blocking waits of `wait_for_readback`
+    #[cfg(not(target_arch = "wasm32"))]
+    poller: poller::DevicePoller,
 }
 
 impl GpuSha3Hasher {
     /// Create a new GPU SHA-3 hasher for a specific variant
     /// Uses persistent buffers by default for optimal performance
     pub fn new(context: GpuContext, variant: Sha3Variant) -> Result<Self, GpuSha3Error> {
-        // Enable persistent buffers by default for performance
         // Conservative defaults: 1000 hashes, 8KB input, 64 bytes output
-        let max_batch_size = 1000;
-        let max_input_length = 8192; // 8KB per input (matches shader limit)
-        let max_output_bytes = 64; // Maximum output size (covers SHA3-512 and reasonable SHAKE outputs)
-        Self::with_persistent_buffers(
-            context,
-            variant,
-            Some((max_batch_size, max_input_length, max_output_bytes)),
-        )
+        Self::builder().variant(variant).build(context)
+    }
+
+    /// Create a new GPU SHA-3 hasher whose persistent buffers are sized by the workload
+    ///
+    /// No buffers are allocated up front. The first batch allocates persistent buffers sized
+    /// exactly to its shape, and any later batch exceeding them in some dimension regrows the
+    /// buffers to cover both. Each growth is a one-time reallocation on the calling batch;
+    /// batches that fit the current buffers reuse them with no allocation at all.
+    pub fn new_adaptive(context: GpuContext, variant: Sha3Variant) -> Result<Self, GpuSha3Error> {
+        let mut hasher = Self::with_persistent_buffers(context, variant, None)?;
+        hasher.grow_buffers = true;
+        Ok(hasher)
     }
 
     /// Create a new GPU SHA-3 hasher with optional persistent buffers for performance
+    ///
+    /// `max_batch_config` is `(max_batch_size, max_input_length, max_output_bytes)`, the
+    /// initial shape of the persistent buffers; [`builder`](Self::builder) sets the same
+    /// shape by name. A batch exceeding them in some dimension
+    /// regrows the buffers to cover both shapes, and the grown buffers are kept for later
+    /// batches (see [`current_capacity`](Self::current_capacity)). With `None` no persistent
+    /// buffers are kept and every batch allocates dynamic buffers. Fails with
+    /// `InvalidInputLength` (or `InvalidOutputLength`) if the persistent input (or output)
+    /// buffer would exceed the device's storage buffer binding size.
     pub fn with_persistent_buffers(
         context: GpuContext,
         variant: Sha3Variant,
@@ -300,233 +883,1619 @@ impl GpuSha3Hasher {
                     },
                     count: None,
                 },
+                // Initial sponge state (storage, read-only)
+                BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: ShaderStages::COMPUTE,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         });
 
-        // Create pipeline layout
-        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
-            label: Some("SHA-3 Pipeline Layout"),
-            bind_group_layouts: &[&bind_group_layout],
+        // Create streaming absorb bind group layout (inputs, parameters, read-write state)
+        let absorb_bind_group_layout =
+            device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("SHA-3 Absorb Bind Group Layout"),
+                entries: &[
+                    // Rate blocks (storage, read-only)
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Parameters (uniform)
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Stream sponge state (storage, read-write)
+                    BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        // Create streaming absorb pipeline
+        let absorb_pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("SHA-3 Absorb Pipeline Layout"),
+            bind_group_layouts: &[&absorb_bind_group_layout],
             push_constant_ranges: &[],
         });
-
-        // Create compute pipeline
-        let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
-            label: Some("SHA-3 Compute Pipeline"),
-            layout: Some(&pipeline_layout),
+        let absorb_pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("SHA-3 Absorb Pipeline"),
+            layout: Some(&absorb_pipeline_layout),
             module: &shader,
-            entry_point: Some("main"),
+            entry_point: Some(ABSORB_ENTRY_POINT),
             compilation_options: Default::default(),
             cache: None,
         });
 
+        // Create digest interleave layout: the batch's own bind group plus a destination
+        let interleave_bind_group_layout =
+            device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("SHA-3 Interleave Bind Group Layout"),
+                entries: &[
+                    // Interleaved rows (storage, read-write)
+                    BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        // Create smallest-digest reduction layout: the batch's bind group plus index slots
+        let argmin_bind_group_layout =
+            device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+                label: Some("SHA-3 Argmin Bind Group Layout"),
+                entries: &[
+                    // Digest indices (storage, read-write)
+                    BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Pass stride (uniform)
+                    BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: ShaderStages::COMPUTE,
+                        ty: BindingType::Buffer {
+                            ty: BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        // Create the pipelines dispatched in workgroups of the default size
+        let SizedPipelines { pipeline, permute_pipeline, interleave_pipeline, argmin_pipeline } =
+            SizedPipelines::new(
+                device,
+                &shader,
+                [&bind_group_layout, &interleave_bind_group_layout, &argmin_bind_group_layout],
+                DEFAULT_WORKGROUP_SIZE,
+            );
+
+        // Create initial sponge state buffer (all zeros until a fixed prefix is absorbed)
+        let initial_state_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Initial State Buffer"),
+            contents: &[0u8; STATE_BYTES],
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+        });
+
         // Initialize persistent buffers if requested
-        let buffers =
-            if let Some((max_batch_size, max_input_length, max_output_bytes)) = max_batch_config {
-                Some(PersistentBuffers::new(
-                    device,
-                    &bind_group_layout,
-                    max_batch_size,
-                    max_input_length,
-                    max_output_bytes,
-                )?)
-            } else {
-                None
-            };
+        let buffers = if let Some(config) = max_batch_config {
+            Some(PersistentBuffers::new(
+                device,
+                &bind_group_layout,
+                &initial_state_buffer,
+                config,
+                DEFAULT_STAGING_BUFFERS,
+            )?)
+        } else {
+            None
+        };
 
         // Set default max_batch_size based on persistent buffers or fallback
         let max_batch_size = buffers.as_ref().map(|b| b.max_batch_size).unwrap_or(1000);
+        let grow_buffers = buffers.is_some();
+        let buffers = std::sync::Mutex::new(buffers);
+        let dispatch_2d_threshold = device.limits().max_compute_workgroups_per_dimension;
+        #[cfg(not(target_arch = "wasm32"))]
+        let poller = poller::DevicePoller::new(device)?;
 
-        Ok(Self { context, variant, pipeline, bind_group_layout, buffers, max_batch_size })
+        Ok(Self {
+            context,
+            variant,
+            shader,
+            workgroup_size: DEFAULT_WORKGROUP_SIZE,
+            pipeline,
+            bind_group_layout,
+            absorb_pipeline,
+            absorb_bind_group_layout,
+            interleave_pipeline,
+            interleave_bind_group_layout,
+            permute_pipeline,
+            argmin_pipeline,
+            argmin_bind_group_layout,
+            initial_state_buffer,
+            prefix: None,
+            buffers,
+            single_buffers: Mutex::new(None),
+            grow_buffers,
+            max_batch_size,
+            readback_timeout: None,
+            mapping_retries: DEFAULT_MAPPING_RETRIES,
+            staging_buffers: DEFAULT_STAGING_BUFFERS,
+            #[cfg(test)]
+            injected_mapping_failures: AtomicUsize::new(0),
+            shader_supports,
+            dispatch_2d_threshold,
+            gpu_threshold: usize::MAX,
+            stats: SessionCounters::default(),
+            bind_group_cache: Mutex::new(HashMap::new()),
+            bind_group_builds: AtomicUsize::new(0),
+            #[cfg(not(target_arch = "wasm32"))]
+            poller,
+        })
     }
 
-    /// Hash a batch of inputs (all must be the same length)
-    /// Returns a flattened vector of all output hashes
-    pub async fn hash_batch(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
-        if inputs.is_empty() {
-            return Ok(Vec::new());
-        }
+    /// Absorb a prefix shared by every input once, for the lifetime of the hasher
+    ///
+    /// Every subsequent hash equals hashing `prefix || input`. Complete rate blocks of the
+    /// prefix are absorbed into the initial sponge state up front, so only the remaining
+    /// tail (shorter than one rate block) is prepended to each input at hash time.
+    pub fn with_fixed_prefix(mut self, prefix: Vec<u8>) -> Self {
+        let prefix = FixedPrefix::new(host_copy(prefix), self.variant.rate_bytes());
+        let words = state_to_gpu_words(&prefix.state);
+        self.context.queue().write_buffer(
+            &self.initial_state_buffer,
+            0,
+            bytemuck::cast_slice(&words),
+        );
+        self.prefix = Some(prefix);
+        self
+    }
 
-        // Validate all inputs are the same length
-        let input_length = inputs[0].len();
-        if input_length > MAX_INPUT_SIZE {
-            return Err(GpuSha3Error::InvalidInputLength(input_length));
-        }
-        if !inputs.iter().all(|input| input.len() == input_length) {
-            return Err(GpuSha3Error::InvalidInputLength(input_length));
-        }
+    /// Hash every input within the namespace `tag`
+    ///
+    /// A fixed prefix of `left_encode(bit length of tag) || tag` (the SP 800-185
+    /// `encode_string`), so the boundary between tag and data is unambiguous: hashers with
+    /// different tags never produce the same digest for the same input, and no tag/input
+    /// split collides with another. Replaces any fixed prefix set before.
+    pub fn namespaced(self, tag: &[u8]) -> Self {
+        self.with_fixed_prefix(sp800_185::encode_string(tag))
+    }
 
-        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
-        self.hash_batch_with_params(inputs, &params).await
+    /// Fail readbacks with `GpuOperationFailed` if the GPU doesn't finish within `timeout`
+    ///
+    /// Guards against a wedged driver blocking the caller forever. Only affects native
+    /// targets; on WebGPU completion is driven by the browser event loop.
+    pub fn with_readback_timeout(mut self, timeout: Duration) -> Self {
+        self.readback_timeout = Some(timeout);
+        self
     }
 
-    /// Hash a batch with custom parameters (for SHAKE variants with custom output length)
-    pub async fn hash_batch_with_params(
-        &self,
-        inputs: &[&[u8]],
-        params: &BatchHashParams,
-    ) -> Result<Vec<u8>, GpuSha3Error> {
-        if inputs.is_empty() {
-            return Ok(Vec::new());
-        }
+    /// Dispatch batches needing more than `workgroups` workgroups as a 2D grid
+    ///
+    /// By default only batches exceeding the device's per-dimension workgroup limit use a
+    /// 2D grid (they couldn't be dispatched otherwise). A lower threshold spreads large
+    /// batches over rows of `workgroups` workgroups, which schedules better on some GPUs.
+    /// The value is clamped to `1..=` the device limit; output is identical either way.
+    pub fn with_dispatch_2d_threshold(mut self, workgroups: u32) -> Self {
+        let limit = self.context.limits().max_compute_workgroups_per_dimension;
+        self.dispatch_2d_threshold = workgroups.clamp(1, limit);
+        self
+    }
 
-        // Validate input size doesn't exceed GPU shader limits
-        if params.input_length > MAX_INPUT_SIZE {
-            return cpu_hash_batch(inputs, params);
-        }
+    /// Dispatch the hashing kernels in workgroups of `threads` invocations
+    ///
+    /// The shader's workgroup size is a pipeline-overridable constant, so this rebuilds the
+    /// batch pipelines with the new size. The best size depends on the GPU; defaults to
+    /// 256, and [`auto_tune`](Self::auto_tune) measures a few sizes and keeps the fastest.
+    /// The value is clamped to `1..=` the device's per-workgroup invocation and x-size
+    /// limits; output is identical either way.
+    pub fn with_workgroup_size(mut self, threads: u32) -> Self {
+        self.set_workgroup_size(threads);
+        self
+    }
 
-        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
-        let total_output_bytes = params.num_hashes * output_bytes;
+    /// Get the number of invocations per workgroup the hashing kernels are dispatched in
+    pub fn workgroup_size(&self) -> u32 {
+        self.workgroup_size
+    }
 
-        // Try persistent buffers first, fall back to dynamic allocation
-        if self.can_use_persistent_buffers(params.num_hashes, params.input_length, output_bytes) {
-            let buffers = self.buffers.as_ref().unwrap();
-            let hash_params =
-                PersistentHashParams { inputs, params, output_bytes, total_output_bytes };
-            self.hash_batch_with_persistent_buffers(buffers, hash_params).await
-        } else {
-            // Fallback to dynamic buffer allocation
-            self.hash_batch_with_dynamic_buffers(inputs, params, output_bytes, total_output_bytes)
-                .await
+    /// Rebuild the workgroup-sized pipelines for `threads` (clamped as in `with_workgroup_size`)
+    fn set_workgroup_size(&mut self, threads: u32) {
+        let threads = threads.clamp(1, self.context.capabilities().max_workgroup_size);
+        if threads == self.workgroup_size {
+            return;
         }
+
+        let SizedPipelines { pipeline, permute_pipeline, interleave_pipeline, argmin_pipeline } =
+            SizedPipelines::new(
+                self.context.device(),
+                &self.shader,
+                [
+                    &self.bind_group_layout,
+                    &self.interleave_bind_group_layout,
+                    &self.argmin_bind_group_layout,
+                ],
+                threads,
+            );
+        self.pipeline = pipeline;
+        self.permute_pipeline = permute_pipeline;
+        self.interleave_pipeline = interleave_pipeline;
+        self.argmin_pipeline = argmin_pipeline;
+        self.workgroup_size = threads;
     }
 
-    /// Check if persistent buffers can handle a batch
-    fn can_use_persistent_buffers(
-        &self,
-        num_hashes: usize,
-        input_length: usize,
-        output_bytes: usize,
-    ) -> bool {
-        self.buffers
-            .as_ref()
-            .map(|buffers| buffers.can_handle_batch(num_hashes, input_length, output_bytes))
-            .unwrap_or(false)
+    /// Set the longest input, in bytes, hashed on the GPU; longer inputs use the CPU
+    ///
+    /// The shader has no per-input limit, so by default (`usize::MAX`) every input goes to
+    /// the GPU, as long as the batch fits the device's storage buffer binding size. Lower
+    /// it where measurements show the CPU winning for some input sizes on a given GPU.
+    /// Applies to inputs as the shader sees them, i.e. including any unabsorbed
+    /// fixed-prefix tail.
+    pub fn set_gpu_threshold(&mut self, bytes: usize) {
+        self.gpu_threshold = bytes;
+    }
+
+    /// Get the longest input, in bytes, hashed on the GPU
+    pub fn gpu_threshold(&self) -> usize {
+        self.gpu_threshold
+    }
+
+    /// Get the configured readback timeout (None waits indefinitely)
+    pub fn readback_timeout(&self) -> Option<Duration> {
+        self.readback_timeout
+    }
+
+    /// Re-submit a batch up to `retries` times when mapping its results fails
+    ///
+    /// Mapping the readback buffer can fail transiently under heavy load; the inputs are
+    /// still at hand, so the batch methods (`hash_batch`, `hash_batch_with_params` and the
+    /// helpers built on them) dispatch it again before reporting `BufferMapping`. A lost
+    /// device is permanent and is reported as `DeviceLost` without retrying. Defaults to 2;
+    /// 0 disables retries.
+    pub fn with_mapping_retries(mut self, retries: u32) -> Self {
+        self.mapping_retries = retries;
+        self
+    }
+
+    /// Get the number of re-submissions after a failed readback mapping
+    pub fn mapping_retries(&self) -> u32 {
+        self.mapping_retries
+    }
+
+    /// Keep `count` staging buffers (at least one) for persistent-buffer batches
+    ///
+    /// Each batch hashed through the persistent buffers copies its digests into a staging
+    /// buffer of its own and waits for that readback without holding the other buffers, so
+    /// up to `count` concurrent batches (e.g. calls through an `Arc`) can be in flight at
+    /// once. Concurrent batches beyond that use dynamic buffers. Each staging buffer is the
+    /// size of the persistent output buffer. Defaults to 2.
+    pub fn with_staging_buffers(mut self, count: usize) -> Self {
+        self.staging_buffers = count.max(1);
+        if let Some(buffers) = self.buffers.get_mut().unwrap_or_else(PoisonError::into_inner) {
+            buffers.set_staging_buffers(self.context.device(), self.staging_buffers);
+        }
+        self
+    }
+
+    /// Get the number of staging buffers kept for persistent-buffer batches
+    pub fn staging_buffers(&self) -> usize {
+        self.staging_buffers
+    }
+
+    /// Make the readback of the next `count` GPU batches fail as if mapping had failed
+    #[cfg(test)]
+    pub(crate) fn with_injected_mapping_failures(self, count: usize) -> Self {
+        self.injected_mapping_failures.store(count, Ordering::Relaxed);
+        self
+    }
+
+    /// Hold back every readback until the returned guard is dropped (test hook for keeping
+    /// batches in flight together)
+    #[cfg(all(test, not(target_arch = "wasm32")))]
+    pub(crate) fn hold_readbacks(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.poller.hold()
+    }
+
+    /// Replace the shader support table (test hook for exercising unsupported variants)
+    #[cfg(test)]
+    pub(crate) fn with_shader_support(mut self, supports: fn(Sha3Variant) -> bool) -> Self {
+        self.shader_supports = supports;
+        self
     }
 
-    /// Optimized path using persistent buffers
-    async fn hash_batch_with_persistent_buffers(
+    /// Hash each `prefix || input` on the CPU, counting the batch in the session stats
+    fn hash_on_cpu(
         &self,
-        buffers: &PersistentBuffers,
-        hash_params: PersistentHashParams<'_>,
+        prefix: &[u8],
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
     ) -> Result<Vec<u8>, GpuSha3Error> {
-        let device = self.context.device();
-        let queue = self.context.queue();
+        let bytes = inputs.iter().map(|input| prefix.len() + input.len()).sum();
+        self.stats.record(BatchPath::Cpu, inputs.len(), bytes);
+        cpu_hash_batch(prefix, inputs, params)
+    }
 
-        // Prepare GPU parameters
-        let gpu_params = GpuHashParams {
-            num_hashes: hash_params.params.num_hashes as u32,
-            input_length: hash_params.params.input_length as u32,
-            rate_bytes: hash_params.params.variant.rate_bytes() as u32,
-            output_bytes: hash_params.output_bytes as u32,
-        };
+    /// Summarize the batches this hasher has run since creation or the last `reset_stats`
+    pub fn session_stats(&self) -> SessionStats {
+        self.stats.snapshot()
+    }
 
-        // Calculate actual buffer sizes needed for this batch
-        let total_input_bytes = hash_params.params.num_hashes * hash_params.params.input_length;
-        let input_buffer_size = ((total_input_bytes + 15) / 16) * 16; // Align to 16 bytes
-        let output_buffer_size = ((hash_params.total_output_bytes + 15) / 16) * 16; // Align to 16 bytes
+    /// Zero the counters reported by `session_stats`
+    pub fn reset_stats(&self) {
+        self.stats.reset();
+    }
 
-        // Flatten and copy input data (reuse persistent buffers)
-        let mut input_data = Vec::with_capacity(input_buffer_size);
-        for input in hash_params.inputs.iter() {
-            input_data.extend_from_slice(input);
+    /// Whether a batch should be dispatched to the GPU: inputs within the routing threshold
+    /// and buffers the device can bind
+    fn routes_to_gpu(&self, num_hashes: usize, input_length: usize, output_bytes: usize) -> bool {
+        input_length <= self.gpu_threshold
+            && self.fits_device(num_hashes, input_length, output_bytes)
+    }
+
+    /// Whether a batch's input and output buffers fit the device's storage binding size
+    fn fits_device(&self, num_hashes: usize, input_length: usize, output_bytes: usize) -> bool {
+        let limit = self.context.limits().max_storage_buffer_binding_size as usize;
+        let fits = |bytes: usize| bytes.saturating_add(15) / 16 * 16 <= limit;
+        fits(num_hashes.saturating_mul(input_length))
+            && fits(num_hashes.saturating_mul(output_bytes))
+    }
+
+    /// Fail with `UnsupportedVariant` unless the shader handles `variant`
+    fn check_shader_support(&self, variant: Sha3Variant) -> Result<(), GpuSha3Error> {
+        if (self.shader_supports)(variant) {
+            Ok(())
+        } else {
+            Err(GpuSha3Error::UnsupportedVariant(variant))
         }
-        // Pad to required buffer size
-        input_data.resize(input_buffer_size, 0);
-        queue.write_buffer(&buffers.input_buffer, 0, &input_data);
+    }
 
-        // Update uniform buffer with parameters
+    /// Hash a single input through a dedicated one-hash buffer set
+    ///
+    /// For many tiny independent hashes the batch path's per-call setup dominates; this
+    /// skips it, writing the input and uniform straight into buffers kept for this purpose
+    /// and dispatching a single workgroup. Falls back to `hash_batch` for inputs the shader
+    /// can't take, SHAKE variants, or while another `hash_one_fast` call holds the buffers.
+    pub async fn hash_one_fast(&self, input: &[u8]) -> Result<Vec<u8>, GpuSha3Error> {
+        let tail = self.prefix.as_ref().map_or(&[][..], |p| p.tail());
+        let input_length = tail.len() + input.len();
+        let output_bytes = self.variant.output_bytes();
+        if input_length > FAST_PATH_INPUT_CAPACITY
+            || input_length > self.gpu_threshold
+            || output_bytes == 0
+            || !(self.shader_supports)(self.variant)
+        {
+            return self.hash_batch(&[input]).await;
+        }
+        let Some(mut slot) = self.single_buffers.try_lock() else {
+            return self.hash_batch(&[input]).await;
+        };
+
+        let device = self.context.device();
+        let queue = self.context.queue();
+        if slot.is_none() {
+            *slot = Some(PersistentBuffers::new(
+                device,
+                &self.bind_group_layout,
+                &self.initial_state_buffer,
+                (1, FAST_PATH_INPUT_CAPACITY, output_bytes),
+                1,
+            )?);
+        }
+        let buffers = slot.as_ref().expect("single-hash buffers allocated above");
+        let staging = buffers
+            .claim_staging()
+            .expect("single-hash staging buffer is only used under its lock");
+
+        // Buffer writes must be a multiple of 4 bytes
+        let upload_len = (input_length + 3) / 4 * 4;
+        let mut input_data = Vec::with_capacity(upload_len);
+        input_data.extend_from_slice(tail);
+        input_data.extend_from_slice(input);
+        input_data.resize(upload_len, 0);
+        if !input_data.is_empty() {
+            queue.write_buffer(&buffers.input_buffer, 0, &input_data);
+            wipe_upload(&mut input_data);
+        }
+        let params = BatchHashParams::new(self.variant, 1, input_length);
+        let gpu_params = GpuHashParams::new(&params, output_bytes);
         queue.write_buffer(&buffers.uniform_buffer, 0, bytemuck::cast_slice(&[gpu_params]));
 
-        // Create command encoder and dispatch compute shader
         let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
-            label: Some("SHA-3 Command Encoder"),
+            label: Some("SHA-3 Single Hash Command Encoder"),
         });
+        self.encode_dispatch(&mut encoder, &buffers.bind_group, 1);
+        self.stats.record(BatchPath::Persistent, 1, input_length);
+        // Digest sizes are all multiples of 4 bytes
+        encoder.copy_buffer_to_buffer(
+            &buffers.output_buffer,
+            0,
+            staging.buffer(),
+            0,
+            output_bytes as u64,
+        );
+        encode_input_wipe(&mut encoder, &buffers.input_buffer, upload_len as u64);
+        queue.submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(staging.buffer());
+        self.wait_for_readback(&[staging.buffer()]).await?;
+        finish_readback(staging.buffer(), receiver, output_bytes).await
+    }
+
+    /// Hash a single input into an `N`-byte array, through [`hash_one_fast`](Self::hash_one_fast)
+    ///
+    /// Keeps the digest size in the type for variants with a fixed output. Fails with
+    /// `InvalidOutputLength(N)` unless the variant's output is exactly `N` bytes, which
+    /// includes every SHAKE variant.
+    pub async fn hash_one_fixed<const N: usize>(
+        &self,
+        input: &[u8],
+    ) -> Result<[u8; N], GpuSha3Error> {
+        if self.variant.output_bytes() != N {
+            return Err(GpuSha3Error::Core(Sha3Error::InvalidOutputLength(N)));
+        }
+        let digest = self.hash_one_fast(input).await?;
+        digest.try_into().map_err(|digest: Vec<u8>| {
+            GpuSha3Error::Core(Sha3Error::InvalidOutputLength(digest.len()))
+        })
+    }
+
+    /// Hash a single input with a SHA3-224 hasher into a 28-byte array
+    pub async fn hash224(&self, input: &[u8]) -> Result<[u8; 28], GpuSha3Error> {
+        self.hash_one_fixed(input).await
+    }
+
+    /// Hash a single input with a SHA3-256 or Keccak-256 hasher into a 32-byte array
+    pub async fn hash256(&self, input: &[u8]) -> Result<[u8; 32], GpuSha3Error> {
+        self.hash_one_fixed(input).await
+    }
+
+    /// Hash a single input with a SHA3-384 hasher into a 48-byte array
+    pub async fn hash384(&self, input: &[u8]) -> Result<[u8; 48], GpuSha3Error> {
+        self.hash_one_fixed(input).await
+    }
+
+    /// Hash a single input with a SHA3-512 or Keccak-512 hasher into a 64-byte array
+    pub async fn hash512(&self, input: &[u8]) -> Result<[u8; 64], GpuSha3Error> {
+        self.hash_one_fixed(input).await
+    }
+
+    /// Hash a batch of inputs (all must be the same length)
+    /// Returns a flattened vector of all output hashes
+    pub async fn hash_batch(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Validate all inputs are the same length
+        let input_length = inputs[0].len();
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+
+        // A large batch of one repeated input: hash it once and broadcast the digest
+        if inputs.len() >= IDENTICAL_BATCH_MIN && all_identical(inputs) {
+            let params = BatchHashParams::new(self.variant, 1, input_length);
+            let digest = self.hash_batch_with_params(&inputs[..1], &params).await?;
+            return Ok(digest.repeat(inputs.len()));
+        }
+
+        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
+        self.hash_batch_with_params(inputs, &params).await
+    }
 
+    /// Hash a batch of inputs (all must be the same length), one digest per input
+    ///
+    /// Same as [`hash_batch`](Self::hash_batch) with the flattened output already split
+    /// into `inputs.len()` digests, in input order. SHAKE variants have no default output
+    /// length; use [`hash_batch_vec_with_params`](Self::hash_batch_vec_with_params) to set
+    /// one.
+    pub async fn hash_batch_vec(&self, inputs: &[&[u8]]) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        let flat = self.hash_batch(inputs).await?;
+        let params = BatchHashParams::new(self.variant, inputs.len(), 0);
+        let digests = params.split_output(&flat).map_err(GpuSha3Error::Core)?;
+        Ok(digests.map(<[u8]>::to_vec).collect())
+    }
+
+    /// Hash a batch with custom parameters, one digest per input
+    ///
+    /// Each digest is `params`' output length, so SHAKE digests have the length configured
+    /// with `BatchHashParams::with_output_length`.
+    pub async fn hash_batch_vec_with_params(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        let flat = self.hash_batch_with_params(inputs, params).await?;
+        let digests = params.split_output(&flat).map_err(GpuSha3Error::Core)?;
+        Ok(digests.map(<[u8]>::to_vec).collect())
+    }
+
+    /// Hash a batch of inputs (all must be the same length), one lowercase hex digest per
+    /// input
+    ///
+    /// Same as [`hash_batch_vec`](Self::hash_batch_vec) with each digest hex-encoded. SHAKE
+    /// variants have no default output length; use
+    /// [`hash_batch_hex_with_params`](Self::hash_batch_hex_with_params) to set one.
+    pub async fn hash_batch_hex(&self, inputs: &[&[u8]]) -> Result<Vec<String>, GpuSha3Error> {
+        let digests = self.hash_batch_vec(inputs).await?;
+        Ok(digests.iter().map(|digest| encoding::to_hex(digest)).collect())
+    }
+
+    /// Hash a batch of inputs (all must be the same length), one [`HashResult`] per input
+    ///
+    /// Each result carries the hasher's variant alongside the digest, so it can be stored
+    /// or compared without losing which algorithm produced it.
+    pub async fn hash_batch_results(
+        &self,
+        inputs: &[&[u8]],
+    ) -> Result<Vec<HashResult>, GpuSha3Error> {
+        let digests = self.hash_batch_vec(inputs).await?;
+        Ok(digests.into_iter().map(|digest| HashResult::new(self.variant, digest)).collect())
+    }
+
+    /// Hash a batch of inputs (all must be the same length) and check each digest against
+    /// its `expected` value, returning whether each matched
+    ///
+    /// Digests are compared with [`sha3_core::ct_eq`], so a mismatch takes as long to
+    /// find as a match. Fails before hashing with `InvalidInputLength` if `expected` doesn't
+    /// hold one digest per input, and with `InvalidOutputLength` if an expected digest
+    /// isn't the variant's output size (SHAKE variants have none, so they fail with
+    /// `MissingOutputLength`).
+    pub async fn verify_batch(
+        &self,
+        inputs: &[&[u8]],
+        expected: &[&[u8]],
+    ) -> Result<Vec<bool>, GpuSha3Error> {
+        if expected.len() != inputs.len() {
+            return Err(GpuSha3Error::InvalidInputLength(expected.len()));
+        }
+        let output_bytes = self.variant.output_bytes();
+        if output_bytes == 0 {
+            return Err(GpuSha3Error::Core(Sha3Error::MissingOutputLength));
+        }
+        if let Some(digest) = expected.iter().find(|digest| digest.len() != output_bytes) {
+            return Err(GpuSha3Error::Core(Sha3Error::InvalidOutputLength(digest.len())));
+        }
+
+        let digests = self.hash_batch_vec(inputs).await?;
+        Ok(digests.iter().zip(expected).map(|(digest, expected)| ct_eq(digest, expected)).collect())
+    }
+
+    /// Hash a batch with custom parameters, one lowercase hex digest per input
+    ///
+    /// Each digest is `params`' output length, so SHAKE digests have the length configured
+    /// with `BatchHashParams::with_output_length`.
+    pub async fn hash_batch_hex_with_params(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<String>, GpuSha3Error> {
+        let flat = self.hash_batch_with_params(inputs, params).await?;
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        Ok(flat.chunks(output_bytes.max(1)).map(encoding::to_hex).collect())
+    }
+
+    /// Hash `num_hashes` inputs of `input_length` bytes stored column-major
+    ///
+    /// `data` holds byte 0 of every input, then byte 1 of every input, and so on, so
+    /// `data[b * num_hashes + i]` is byte `b` of input `i`. The shader reads this layout
+    /// directly, saving structure-of-arrays producers a host-side transpose. Digests are
+    /// returned in input order, as from `hash_batch`.
+    pub async fn hash_batch_soa(
+        &self,
+        data: &[u8],
+        num_hashes: usize,
+        input_length: usize,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if num_hashes.checked_mul(input_length) != Some(data.len()) {
+            return Err(GpuSha3Error::InvalidInputLength(data.len()));
+        }
+        if num_hashes == 0 {
+            return Ok(Vec::new());
+        }
+
+        let params = BatchHashParams::new(self.variant, num_hashes, input_length);
+        let has_prefix_tail = self.prefix.as_ref().is_some_and(|p| !p.tail().is_empty());
+        if !self.routes_to_gpu(num_hashes, input_length, params.output_bytes_or(0))
+            || has_prefix_tail
+            || !(self.shader_supports)(self.variant)
         {
-            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
-                label: Some("SHA-3 Compute Pass"),
-                timestamp_writes: None,
-            });
+            // The shader can't read this batch in place: transpose and take the regular path
+            let rows: Vec<HostCopy> = (0..num_hashes)
+                .map(|i| host_copy((0..input_length).map(|b| data[b * num_hashes + i]).collect()))
+                .collect();
+            let row_refs: Vec<&[u8]> = rows.iter().map(|v| v.as_slice()).collect();
+            return self.hash_batch_with_params(&row_refs, &params).await;
+        }
+
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let gpu_params = GpuHashParams {
+            input_layout: INPUT_LAYOUT_SOA,
+            ..GpuHashParams::new(&params, output_bytes)
+        };
+        self.hash_batch_with_dynamic_buffers(&[data], gpu_params, &self.initial_state_buffer).await
+    }
+
+    /// Hash a batch with custom parameters (for SHAKE variants with custom output length)
+    ///
+    /// Fails with `InvalidOutputLength` if the batch's combined output couldn't be
+    /// allocated, rather than routing an absurd `output_length` to the CPU.
+    pub async fn hash_batch_with_params(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        params.total_output_bytes().map_err(GpuSha3Error::Core)?;
+
+        if !(self.shader_supports)(params.variant) {
+            // Route to the reference implementation rather than produce wrong digests
+            let prefix = self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..]);
+            return self.hash_on_cpu(prefix, inputs, params);
+        }
+
+        if let Some(prefix) = &self.prefix {
+            if params.variant.rate_bytes() != self.variant.rate_bytes() {
+                // The pre-absorbed state is only valid for the rate it was computed with
+                return self.hash_on_cpu(&prefix.bytes, inputs, params);
+            }
+        }
 
-            compute_pass.set_pipeline(&self.pipeline);
-            compute_pass.set_bind_group(0, &buffers.bind_group, &[]);
+        if params.has_cshake_header() {
+            return self.hash_batch_cshake(inputs, params).await;
+        }
 
-            // Dispatch workgroups (one thread per hash, 256 threads per workgroup)
-            // Optimized: Increased from 128 to 256 for maximum GPU occupancy
-            let workgroup_size = 256;
-            let num_workgroups =
-                (hash_params.params.num_hashes + workgroup_size - 1) / workgroup_size;
-            compute_pass.dispatch_workgroups(num_workgroups as u32, 1, 1);
+        match self.frame_inputs(inputs) {
+            Some(framed) => {
+                // Hash `tail || input` for each input, starting from the pre-absorbed state
+                let framed_refs: Vec<&[u8]> = framed.iter().map(|v| v.as_slice()).collect();
+                let mut framed_params = params.clone();
+                framed_params.input_length = framed_refs[0].len();
+                self.hash_from_initial_state(&framed_refs, &framed_params).await
+            }
+            None => self.hash_from_initial_state(inputs, params).await,
+        }
+    }
+
+    /// Hash a batch, also returning the id of the GPU invocation that produced each digest
+    ///
+    /// Runs the shader's hashing code through a debug entry point that records, for each
+    /// input `i`, the id (workgroup index * workgroup size + local index) of the invocation
+    /// that wrote output `i`; with correct indexing the ids are exactly `0..inputs.len()`.
+    /// Always dispatches to the GPU, so inputs must be the same length and fit the shader.
+    #[cfg(feature = "debug-state")]
+    pub async fn hash_batch_debug_ids(
+        &self,
+        inputs: &[&[u8]],
+    ) -> Result<(Vec<u8>, Vec<u32>), GpuSha3Error> {
+        self.check_shader_support(self.variant)?;
+        if inputs.is_empty() {
+            return Ok((Vec::new(), Vec::new()));
+        }
+        if !inputs.iter().all(|input| input.len() == inputs[0].len()) {
+            return Err(GpuSha3Error::InvalidInputLength(inputs[0].len()));
         }
 
-        // Copy output to staging buffer
-        let current_staging = &buffers.staging_buffer;
+        let framed = self.frame_inputs(inputs);
+        let framed_refs: Vec<&[u8]>;
+        let inputs = match &framed {
+            Some(framed) => {
+                framed_refs = framed.iter().map(|v| v.as_slice()).collect();
+                &framed_refs[..]
+            }
+            None => inputs,
+        };
+        let num_hashes = inputs.len();
+        let input_length = inputs[0].len();
+        let params = BatchHashParams::new(self.variant, num_hashes, input_length);
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        if !self.fits_device(num_hashes, input_length, output_bytes) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+        let gpu_params = GpuHashParams::new(&params, output_bytes);
+        let batch = self.create_dynamic_batch(inputs, gpu_params, &self.initial_state_buffer);
+
+        // Instrumented pipeline: the batch's bind group plus the thread id buffer
+        let device = self.context.device();
+        let module = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("SHA-3 Debug Ids Shader"),
+            source: ShaderSource::Wgsl(
+                format!("{SHADER_SOURCE}\n{DEBUG_IDS_SHADER_SOURCE}").into(),
+            ),
+        });
+        let ids_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("SHA-3 Debug Ids Bind Group Layout"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 1,
+                visibility: ShaderStages::COMPUTE,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("SHA-3 Debug Ids Pipeline Layout"),
+            bind_group_layouts: &[&self.bind_group_layout, &ids_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+            label: Some("SHA-3 Debug Ids Pipeline"),
+            layout: Some(&pipeline_layout),
+            module: &module,
+            entry_point: Some("main_debug_ids"),
+            compilation_options: PipelineCompilationOptions {
+                constants: &[(WORKGROUP_SIZE_CONSTANT, f64::from(self.workgroup_size))],
+                ..Default::default()
+            },
+            cache: None,
+        });
+
+        let ids_size = ((num_hashes * 4 + 15) / 16 * 16) as u64;
+        let ids_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Debug Ids Buffer"),
+            size: ids_size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let ids_staging = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Debug Ids Staging Buffer"),
+            size: ids_size,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let ids_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("SHA-3 Debug Ids Bind Group"),
+            layout: &ids_layout,
+            entries: &[BindGroupEntry { binding: 1, resource: ids_buffer.as_entire_binding() }],
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Debug Ids Command Encoder"),
+        });
+        self.encode_pass(
+            &mut encoder,
+            &pipeline,
+            &[&batch.bind_group, &ids_bind_group],
+            num_hashes,
+        );
         encoder.copy_buffer_to_buffer(
-            &buffers.output_buffer,
+            &batch.output_buffer,
             0,
-            current_staging,
+            &batch.staging_buffer,
             0,
-            output_buffer_size as u64,
+            batch.output_buffer_size as u64,
         );
+        encoder.copy_buffer_to_buffer(&ids_buffer, 0, &ids_staging, 0, ids_size);
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let digests_receiver = begin_readback(&batch.staging_buffer);
+        let ids_receiver = begin_readback(&ids_staging);
+        self.wait_for_readback(&[&batch.staging_buffer, &ids_staging]).await?;
+        let digests =
+            finish_readback(&batch.staging_buffer, digests_receiver, batch.total_output_bytes)
+                .await?;
+        let ids = finish_readback(&ids_staging, ids_receiver, num_hashes * 4).await?;
+        Ok((digests, bytemuck::pod_collect_to_vec(&ids)))
+    }
 
-        // Submit commands
-        queue.submit(Some(encoder.finish()));
+    /// Hash inputs of varying lengths by grouping equal-length inputs into sub-batches
+    ///
+    /// Every length group gets its own buffer set and all groups are recorded into a single
+    /// submission, so small groups run concurrently instead of one after another. Results
+    /// are collected per group and reassembled in the original input order.
+    pub async fn hash_batch_grouped(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        // Read results from current staging buffer
-        let buffer_slice = current_staging.slice(..);
-        let (sender, receiver) = oneshot::channel();
+        let output_bytes = BatchHashParams::new(self.variant, inputs.len(), 0)
+            .get_output_bytes()
+            .map_err(GpuSha3Error::Core)?;
+
+        let framed = self.frame_inputs(inputs);
+        let framed_refs: Vec<&[u8]>;
+        let inputs = match &framed {
+            Some(framed) => {
+                framed_refs = framed.iter().map(|v| v.as_slice()).collect();
+                &framed_refs[..]
+            }
+            None => inputs,
+        };
+
+        // Group input indices by length (BTreeMap keeps the dispatch order deterministic)
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, input) in inputs.iter().enumerate() {
+            groups.entry(input.len()).or_default().push(index);
+        }
 
-        buffer_slice.map_async(MapMode::Read, move |result| {
-            let _ = sender.send(result);
+        let mut output = vec![0u8; inputs.len() * output_bytes];
+        let mut encoder = self.context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Grouped Command Encoder"),
         });
+        let mut pending = Vec::with_capacity(groups.len());
+
+        for (&input_length, indices) in &groups {
+            let group_inputs: Vec<&[u8]> = indices.iter().map(|&i| inputs[i]).collect();
+            let params = BatchHashParams::new(self.variant, indices.len(), input_length);
+
+            if !self.routes_to_gpu(indices.len(), input_length, output_bytes)
+                || !(self.shader_supports)(self.variant)
+            {
+                let digests = self.hash_on_cpu(self.absorbed_prefix(), &group_inputs, &params)?;
+                scatter_digests(&mut output, indices, &digests, output_bytes);
+                continue;
+            }
+
+            let batch = self.create_dynamic_batch(
+                &group_inputs,
+                GpuHashParams::new(&params, output_bytes),
+                &self.initial_state_buffer,
+            );
+            self.encode_dynamic_batch(&mut encoder, &batch);
+            self.stats.record(BatchPath::Dynamic, indices.len(), indices.len() * input_length);
+            pending.push((indices, batch));
+        }
+
+        self.context.queue().submit(Some(encoder.finish()));
 
-        // Ensure the mapping callback is processed on native targets
-        #[allow(unused_must_use)]
+        // Map every group's staging buffer before polling so the readbacks overlap
+        let receivers: Vec<_> =
+            pending.iter().map(|(_, batch)| begin_readback(&batch.staging_buffer)).collect();
+        let staging_buffers: Vec<&Buffer> =
+            pending.iter().map(|(_, batch)| &batch.staging_buffer).collect();
+        self.wait_for_readback(&staging_buffers).await?;
+
+        for ((indices, batch), receiver) in pending.iter().zip(receivers) {
+            let digests =
+                finish_readback(&batch.staging_buffer, receiver, batch.total_output_bytes).await?;
+            scatter_digests(&mut output, indices, &digests, output_bytes);
+        }
+
+        Ok(output)
+    }
+
+    /// Hash inputs of any lengths, each squeezed to its own output length
+    ///
+    /// Meant for SHAKE variants: inputs are grouped by length, each group is squeezed once
+    /// to its longest requested output, and every digest is cut to its own length (a
+    /// shorter SHAKE output is a prefix of a longer one). For fixed-output variants each
+    /// length must be at most the digest size, and digests are truncated.
+    pub async fn hash_batch_with_output_lengths(
+        &self,
+        inputs: &[&[u8]],
+        output_lengths: &[usize],
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        if output_lengths.len() != inputs.len() {
+            return Err(GpuSha3Error::InvalidInputLength(output_lengths.len()));
+        }
+        let digest_bytes = self.variant.output_bytes();
+        if let Some(&length) =
+            output_lengths.iter().find(|&&length| digest_bytes != 0 && length > digest_bytes)
         {
-            device.poll(wgpu::PollType::Wait { submission_index: None, timeout: None });
+            return Err(GpuSha3Error::Core(Sha3Error::InvalidOutputLength(length)));
         }
 
-        // Wait for the mapping callback to fire
-        receiver
-            .await
-            .map_err(|_| {
-                GpuSha3Error::BufferMapping("Failed to receive buffer mapping result".into())
-            })?
-            .map_err(|e| GpuSha3Error::BufferMapping(format!("Buffer mapping failed: {e:?}")))?;
+        // Group input indices by length (BTreeMap keeps the dispatch order deterministic)
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (index, input) in inputs.iter().enumerate() {
+            groups.entry(input.len()).or_default().push(index);
+        }
 
-        // Extract output data
-        let data = buffer_slice.get_mapped_range();
-        let mut result = vec![0u8; hash_params.total_output_bytes];
-        result.copy_from_slice(&data[..hash_params.total_output_bytes]);
+        let mut output = vec![Vec::new(); inputs.len()];
+        for (&input_length, indices) in &groups {
+            let longest = indices.iter().map(|&i| output_lengths[i]).max().unwrap_or(0);
+            if longest == 0 {
+                continue;
+            }
+            let group_inputs: Vec<&[u8]> = indices.iter().map(|&i| inputs[i]).collect();
+            let params = BatchHashParams::new(self.variant, indices.len(), input_length)
+                .with_output_length(longest);
+            let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+
+            let digests = self.hash_batch_with_params(&group_inputs, &params).await?;
+            for (&index, digest) in indices.iter().zip(digests.chunks(output_bytes)) {
+                output[index] = digest[..output_lengths[index]].to_vec();
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Hash a batch and write the digests straight into a memory-mapped file at `path`
+    ///
+    /// Inputs are hashed in sub-batches of the persistent buffer capacity and each
+    /// sub-batch's digests are copied into the mapping as they complete, so the full
+    /// output never has to fit in memory. The file is created (or truncated) to exactly
+    /// `inputs.len() * output_bytes` bytes, laid out in input order.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn hash_batch_to_file(
+        &self,
+        inputs: &[&[u8]],
+        path: &std::path::Path,
+    ) -> Result<(), GpuSha3Error> {
+        let output_bytes = BatchHashParams::new(self.variant, inputs.len(), 0)
+            .get_output_bytes()
+            .map_err(GpuSha3Error::Core)?;
+
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        let total_output_bytes = inputs.len() * output_bytes;
+        file.set_len(total_output_bytes as u64)?;
+        if total_output_bytes == 0 {
+            return Ok(());
+        }
 
-        drop(data);
-        current_staging.unmap();
+        // SAFETY: the file was just created by us and sized to the mapping; it must not be
+        // truncated or modified by other processes while the mapping is alive.
+        let mut mmap = unsafe { memmap2::MmapMut::map_mut(&file)? };
 
-        Ok(result)
+        let mut offset = 0;
+        for chunk in inputs.chunks(self.max_batch_size) {
+            let digests = self.hash_batch(chunk).await?;
+            mmap[offset..offset + digests.len()].copy_from_slice(&digests);
+            offset += digests.len();
+        }
+
+        mmap.flush()?;
+        Ok(())
     }
 
-    /// Fallback path for very large batches that exceed persistent buffer capacity
-    async fn hash_batch_with_dynamic_buffers(
+    /// Hash a batch, sending each sub-batch's digests through `tx` as it completes
+    ///
+    /// Inputs are hashed in sub-batches of the persistent buffer capacity; every message is
+    /// one sub-batch's concatenated digests, sent in input order. The send waits while the
+    /// bounded channel is full, so a slow consumer holds back further GPU work. Fails with
+    /// `GpuOperationFailed` if the receiver is dropped before every digest is delivered.
+    pub async fn hash_batch_to_channel(
+        &self,
+        inputs: &[&[u8]],
+        mut tx: futures::channel::mpsc::Sender<Vec<u8>>,
+    ) -> Result<(), GpuSha3Error> {
+        use futures::SinkExt;
+
+        for chunk in inputs.chunks(self.max_batch_size) {
+            let digests = self.hash_batch(chunk).await?;
+            tx.send(digests)
+                .await
+                .map_err(|_| GpuSha3Error::GpuOperationFailed("result channel closed".into()))?;
+        }
+        Ok(())
+    }
+
+    /// Find every pair of inputs whose digests share the same first `prefix_bytes` bytes
+    ///
+    /// Inputs may have different lengths. Returns `(i, j)` index pairs with `i < j`, sorted;
+    /// identical inputs always collide. Fails with `InvalidInputLength` if `prefix_bytes`
+    /// exceeds the digest size.
+    pub async fn find_truncation_collisions(
+        &self,
+        inputs: &[&[u8]],
+        prefix_bytes: usize,
+    ) -> Result<Vec<(usize, usize)>, GpuSha3Error> {
+        let output_bytes = BatchHashParams::new(self.variant, inputs.len(), 0)
+            .get_output_bytes()
+            .map_err(GpuSha3Error::Core)?;
+        if prefix_bytes > output_bytes {
+            return Err(GpuSha3Error::InvalidInputLength(prefix_bytes));
+        }
+
+        let digests = self.hash_batch_grouped(inputs).await?;
+
+        // Bucket input indices by truncated digest, then pair up each bucket
+        let mut buckets = HashMap::new();
+        for (index, digest) in digests.chunks_exact(output_bytes).enumerate() {
+            buckets.entry(&digest[..prefix_bytes]).or_insert_with(Vec::new).push(index);
+        }
+        let mut collisions: Vec<(usize, usize)> = buckets
+            .values()
+            .flat_map(|indices| {
+                indices
+                    .iter()
+                    .enumerate()
+                    .flat_map(move |(n, &i)| indices[n + 1..].iter().map(move |&j| (i, j)))
+            })
+            .collect();
+        collisions.sort_unstable();
+        Ok(collisions)
+    }
+
+    /// Create a [`std::io::Write`] sink hashing one message (see [`GpuSha3Writer`])
+    pub fn writer(&self) -> GpuSha3Writer<'_> {
+        GpuSha3Writer::new(self.stream())
+    }
+
+    /// Start a running digest over an append-only log (see [`LogHasher`])
+    pub fn log_hasher(&self) -> LogHasher<'_> {
+        LogHasher::new(self.stream())
+    }
+
+    /// Hash a batch and frame each digest for the wire
+    ///
+    /// The result is one contiguous buffer of `inputs.len()` records, in input order, each
+    /// laid out as:
+    ///
+    /// ```text
+    /// [ length: u32, big-endian ][ digest: `length` bytes ]
+    /// ```
+    ///
+    /// `length` is the variant's output size (e.g. `00 00 00 20` for SHA3-256), so each
+    /// record is `4 + output_bytes` long. An empty batch yields an empty buffer.
+    pub async fn hash_batch_framed_output(
+        &self,
+        inputs: &[&[u8]],
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        let digests = self.hash_batch(inputs).await?;
+        Ok(encoding::length_prefixed(&digests, self.variant.output_bytes()))
+    }
+
+    /// Hash several fields as one unambiguous message
+    ///
+    /// Each field is absorbed as `left_encode(bit length) || field` (the SP 800-185
+    /// `encode_string`), so moving bytes between adjacent fields always changes the digest.
+    /// Returns a single digest of the hasher's variant.
+    pub async fn hash_framed(&self, fields: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        let mut message = host_copy(Vec::new());
+        for field in fields {
+            extend_host_copy(&mut message, &host_copy(sp800_185::encode_string(field)));
+        }
+        self.hash_batch(&[message.as_slice()]).await
+    }
+
+    /// Hash a map or record as one digest, independent of the order of its entries
+    ///
+    /// Pairs are sorted by key (then value, so repeated keys are order-independent too) and
+    /// each is absorbed as `left_encode(len(k)) || k || left_encode(len(v)) || v`, lengths
+    /// in bytes. The length prefixes make every key/value boundary unambiguous. Returns a
+    /// single digest of the hasher's variant.
+    pub async fn hash_kv_pairs(&self, pairs: &[KeyValuePair<'_>]) -> Result<Vec<u8>, GpuSha3Error> {
+        let mut sorted = pairs.to_vec();
+        sorted.sort_unstable();
+        let mut message = host_copy(Vec::new());
+        for (key, value) in sorted {
+            extend_host_copy(&mut message, &sp800_185::left_encode(key.len() as u64));
+            extend_host_copy(&mut message, key);
+            extend_host_copy(&mut message, &sp800_185::left_encode(value.len() as u64));
+            extend_host_copy(&mut message, value);
+        }
+        self.hash_batch(&[message.as_slice()]).await
+    }
+
+    /// Hash each item's data and return its digest alongside the item's id
+    ///
+    /// Results follow the order of `items`, but carrying the id means callers needn't rely
+    /// on position to correlate them. Ids and data may repeat; every item gets its own
+    /// entry. Data may differ in length (it's hashed via
+    /// [`hash_batch_grouped`](Self::hash_batch_grouped)).
+    pub async fn hash_with_ids<I: Clone>(
+        &self,
+        items: &[IdentifiedInput<'_, I>],
+    ) -> Result<Vec<(I, Vec<u8>)>, GpuSha3Error> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        let inputs: Vec<&[u8]> = items.iter().map(|(_, data)| *data).collect();
+        let digests = self.hash_batch_grouped(&inputs).await?;
+        let output_bytes = digests.len() / items.len();
+        Ok(items
+            .iter()
+            .zip(digests.chunks(output_bytes))
+            .map(|((id, _), digest)| (id.clone(), digest.to_vec()))
+            .collect())
+    }
+
+    /// Hash each distinct string and map it to its lowercase hex digest
+    ///
+    /// Repeated strings are hashed once. Strings may differ in length (they're hashed via
+    /// [`hash_batch_grouped`](Self::hash_batch_grouped)); fixed-output variants only.
+    pub async fn hash_strings_to_map(
+        &self,
+        strings: &[&str],
+    ) -> Result<HashMap<String, String>, GpuSha3Error> {
+        let mut unique = strings.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        if unique.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let inputs: Vec<&[u8]> = unique.iter().map(|s| s.as_bytes()).collect();
+        let digests = self.hash_batch_grouped(&inputs).await?;
+        let output_bytes = digests.len() / unique.len();
+        Ok(unique
+            .into_iter()
+            .zip(digests.chunks(output_bytes))
+            .map(|(s, digest)| (s.to_string(), encoding::to_hex(digest)))
+            .collect())
+    }
+
+    /// Hash each input together with its own digest: `H(input || H(input))`
+    ///
+    /// Commitments for commit-reveal schemes. Both passes run in one submission: the first
+    /// pass's digests stay on the GPU, where they're appended to their inputs and hashed
+    /// again, so only the final digests are read back. Inputs must all be the same length,
+    /// as for [`hash_batch`](Self::hash_batch); a fixed prefix precedes both passes.
+    pub async fn hash_commit(&self, inputs: &[&[u8]]) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input_length = inputs[0].len();
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+
+        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let framed = self.frame_inputs(inputs);
+        let framed_refs: Vec<&[u8]>;
+        let framed_inputs = match &framed {
+            Some(framed) => {
+                framed_refs = framed.iter().map(|v| v.as_slice()).collect();
+                &framed_refs[..]
+            }
+            None => inputs,
+        };
+        let framed_length = framed_inputs[0].len();
+
+        let digests =
+            if !self.routes_to_gpu(inputs.len(), framed_length + output_bytes, output_bytes)
+                || !(self.shader_supports)(self.variant)
+            {
+                // Second pass won't fit the shader: run both passes on the reference implementation
+                let prefix = self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..]);
+                let first = self.hash_on_cpu(prefix, inputs, &params)?;
+                let rows: Vec<HostCopy> = inputs
+                    .iter()
+                    .zip(first.chunks(output_bytes))
+                    .map(|(input, digest)| host_copy([input, digest].concat()))
+                    .collect();
+                let row_refs: Vec<&[u8]> = rows.iter().map(|v| v.as_slice()).collect();
+                let second =
+                    BatchHashParams::new(self.variant, inputs.len(), input_length + output_bytes);
+                self.hash_on_cpu(prefix, &row_refs, &second)?
+            } else {
+                self.hash_commit_on_gpu(framed_inputs, &params, output_bytes).await?
+            };
+        Ok(digests.chunks(output_bytes).map(<[u8]>::to_vec).collect())
+    }
+
+    /// Set up a reusable dispatch for batches of exactly `shape`
+    ///
+    /// Validates the shape and allocates its buffers, uniform and bind group once, so
+    /// [`PreparedHasher::hash`] only uploads inputs, dispatches and reads back. Fails if the
+    /// shader can't run the shape: buffers over the device limits, unsupported variants, or a
+    /// variant whose rate differs from the one a fixed prefix was absorbed with.
+    pub fn prepared(&self, shape: &BatchHashParams) -> Result<PreparedHasher<'_>, GpuSha3Error> {
+        PreparedHasher::new(self, shape)
+    }
+
+    /// Start an incremental hash of a single message fed in pieces
+    ///
+    /// The stream starts from this hasher's initial state, so a fixed prefix (if any)
+    /// precedes the streamed data. See [`GpuSha3Stream`].
+    pub fn stream(&self) -> GpuSha3Stream<'_> {
+        GpuSha3Stream::new(self)
+    }
+
+    /// Prepend the unabsorbed tail of the fixed prefix (if any) to each input
+    fn frame_inputs(&self, inputs: &[&[u8]]) -> Option<Vec<HostCopy>> {
+        let tail = self.prefix.as_ref()?.tail();
+        if tail.is_empty() {
+            return None;
+        }
+        Some(inputs.iter().map(|input| host_copy([tail, input].concat())).collect())
+    }
+
+    /// Prefix bytes already absorbed into the initial sponge state
+    fn absorbed_prefix(&self) -> &[u8] {
+        self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..p.absorbed])
+    }
+
+    /// Hash a batch starting from the hasher's initial sponge state
+    ///
+    /// Failed readback mappings are retried up to `mapping_retries` times unless the device
+    /// was lost.
+    async fn hash_from_initial_state(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        let mut retries_left = self.mapping_retries;
+        loop {
+            let result = self.hash_from_initial_state_once(inputs, params).await;
+            #[cfg(test)]
+            let result = result.and_then(|digests| {
+                let injected = self.injected_mapping_failures.fetch_update(
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                    |count| count.checked_sub(1),
+                );
+                match injected {
+                    Ok(_) => Err(GpuSha3Error::BufferMapping("injected failure".into())),
+                    Err(_) => Ok(digests),
+                }
+            });
+
+            match result {
+                Err(GpuSha3Error::BufferMapping(message)) if self.context.is_device_lost() => {
+                    return Err(GpuSha3Error::DeviceLost(message));
+                }
+                Err(GpuSha3Error::BufferMapping(_)) if retries_left > 0 => retries_left -= 1,
+                result => return result,
+            }
+        }
+    }
+
+    /// One attempt of `hash_from_initial_state`
+    async fn hash_from_initial_state_once(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        self.check_shader_support(params.variant)?;
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+
+        // Inputs over the routing threshold, or batches too big to bind, are hashed on the CPU
+        if !self.routes_to_gpu(params.num_hashes, params.input_length, output_bytes) {
+            return self.hash_on_cpu(self.absorbed_prefix(), inputs, params);
+        }
+
+        let total_output_bytes = params.num_hashes * output_bytes;
+
+        // Try persistent buffers first, fall back to dynamic allocation. The lock is only held
+        // while submitting; the readback waits on a staging buffer claimed for this batch.
+        let pending = match self.try_lock_buffers() {
+            Some(mut slot) => {
+                let fits = |buffers: &PersistentBuffers| {
+                    buffers.can_handle_batch(params.num_hashes, params.input_length, output_bytes)
+                };
+                if self.grow_buffers && !slot.as_ref().is_some_and(fits) {
+                    // Grow (or first allocate, for adaptive hashers) to cover both shapes, unless
+                    // the combined shape is too big to bind; this batch then uses dynamic buffers
+                    let (batch, input, output) = slot.as_ref().map_or((0, 0, 0), |b| b.config());
+                    let config = (
+                        batch.max(params.num_hashes),
+                        input.max(params.input_length),
+                        output.max(output_bytes),
+                    );
+                    if self.fits_device(config.0, config.1, config.2) {
+                        *slot = Some(PersistentBuffers::new(
+                            self.context.device(),
+                            &self.bind_group_layout,
+                            &self.initial_state_buffer,
+                            config,
+                            self.staging_buffers,
+                        )?);
+                    }
+                }
+                let hash_params =
+                    PersistentHashParams { inputs, params, output_bytes, total_output_bytes };
+                slot.as_ref()
+                    .filter(|buffers| fits(buffers))
+                    .and_then(|buffers| self.submit_persistent_batch(buffers, hash_params))
+            }
+            None => None,
+        };
+        if let Some(pending) = pending {
+            return self.finish_persistent_batch(pending).await;
+        }
+
+        // Fallback to dynamic buffer allocation (too large, absent, in use, or every staging
+        // buffer awaiting a readback)
+        let gpu_params = GpuHashParams::new(params, output_bytes);
+        self.hash_batch_with_dynamic_buffers(inputs, gpu_params, &self.initial_state_buffer).await
+    }
+
+    /// Run both `hash_commit` passes in one submission, returning the flattened digests
+    async fn hash_commit_on_gpu(
         &self,
         inputs: &[&[u8]],
         params: &BatchHashParams,
         output_bytes: usize,
-        total_output_bytes: usize,
     ) -> Result<Vec<u8>, GpuSha3Error> {
+        let device = self.context.device();
+        let num_hashes = inputs.len();
+        let first_params = GpuHashParams::new(params, output_bytes)
+            .with_shape(PreparedShape::new(params.variant, inputs[0].len()));
+        let first = self.create_dynamic_batch(inputs, first_params, &self.initial_state_buffer);
+
+        // `input || digest` rows, written by the interleave pass and read by the second pass
+        let second_params = first_params
+            .with_shape(PreparedShape::new(params.variant, inputs[0].len() + output_bytes));
+        let interleaved_bytes = num_hashes * second_params.input_length as usize;
+        let interleaved_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Interleaved Input Buffer"),
+            size: (((interleaved_bytes + 15) / 16) * 16) as u64,
+            usage: BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        let interleave_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("SHA-3 Interleave Bind Group"),
+            layout: &self.interleave_bind_group_layout,
+            entries: &[BindGroupEntry {
+                binding: 0,
+                resource: interleaved_buffer.as_entire_binding(),
+            }],
+        });
+
+        // The second pass shares the first's output size, so it reuses its staging buffer
+        let output_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Commit Output Buffer"),
+            size: first.output_buffer_size as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let uniform_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Commit Uniform Buffer"),
+            contents: bytemuck::cast_slice(&[second_params]),
+            usage: BufferUsages::UNIFORM,
+        });
+        let second_bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("SHA-3 Commit Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                BindGroupEntry { binding: 0, resource: interleaved_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 1, resource: output_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: self.initial_state_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Commit Command Encoder"),
+        });
+        self.encode_dispatch(&mut encoder, &first.bind_group, num_hashes);
+        self.encode_pass(
+            &mut encoder,
+            &self.interleave_pipeline,
+            &[&first.bind_group, &interleave_bind_group],
+            (interleaved_bytes + 3) / 4,
+        );
+        self.encode_dispatch(&mut encoder, &second_bind_group, num_hashes);
+        let input_length = first_params.input_length as usize;
+        self.stats.record(BatchPath::Dynamic, num_hashes, num_hashes * input_length);
+        let second_length = second_params.input_length as usize;
+        self.stats.record(BatchPath::Dynamic, num_hashes, num_hashes * second_length);
+        encoder.copy_buffer_to_buffer(
+            &output_buffer,
+            0,
+            &first.staging_buffer,
+            0,
+            first.output_buffer_size as u64,
+        );
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&first.staging_buffer);
+        self.wait_for_readback(&[&first.staging_buffer]).await?;
+        finish_readback(&first.staging_buffer, receiver, first.total_output_bytes).await
+    }
+
+    /// Shape of the currently allocated persistent buffers
+    ///
+    /// Returns `(max_batch_size, max_input_length, max_output_bytes)`, which grows as
+    /// larger batches arrive. `None` if no buffers are allocated yet.
+    pub fn current_capacity(&self) -> Option<PersistentBufferConfig> {
+        self.lock_buffers().as_ref().map(|buffers| buffers.config())
+    }
+
+    /// Byte sizes of the persistent buffers and the batch shape they hold
+    ///
+    /// All zero if no persistent buffers are allocated. Reflects growth: call again after
+    /// larger batches to see the current footprint.
+    pub fn buffer_stats(&self) -> BufferStats {
+        self.lock_buffers().as_ref().map(PersistentBuffers::stats).unwrap_or_default()
+    }
+
+    /// Reallocate the persistent buffers down to at most `max_batch_size` hashes of
+    /// `max_input_length` input and `max_output_bytes` output bytes
+    ///
+    /// Persistent buffers only grow, so one huge batch keeps its memory for the life of
+    /// the hasher; this gives it back. Each dimension becomes the smaller of its current and
+    /// requested size (nothing is grown), and the buffers, staging buffers and bind group
+    /// are recreated at that shape. Later batches larger than it regrow the buffers as
+    /// before. Does nothing without persistent buffers or when no dimension shrinks.
+    /// Batches already in flight finish in the buffers they started with.
+    pub fn shrink_to(
+        &self,
+        max_batch_size: usize,
+        max_input_length: usize,
+        max_output_bytes: usize,
+    ) -> Result<(), GpuSha3Error> {
+        let mut slot = self.lock_buffers();
+        let Some((batch, input, output)) = slot.as_ref().map(PersistentBuffers::config) else {
+            return Ok(());
+        };
+        let config =
+            (batch.min(max_batch_size), input.min(max_input_length), output.min(max_output_bytes));
+        if config != (batch, input, output) {
+            *slot = Some(PersistentBuffers::new(
+                self.context.device(),
+                &self.bind_group_layout,
+                &self.initial_state_buffer,
+                config,
+                self.staging_buffers,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Lock the persistent buffer slot, waiting out a batch being submitted
+    ///
+    /// The lock is held only while a batch is submitted, never across an await, so this
+    /// blocks briefly at most. A panic during a submission leaves the slot usable.
+    fn lock_buffers(&self) -> std::sync::MutexGuard<'_, Option<PersistentBuffers>> {
+        self.buffers.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Lock the persistent buffer slot, or `None` if a batch is being submitted with it
+    fn try_lock_buffers(&self) -> Option<std::sync::MutexGuard<'_, Option<PersistentBuffers>>> {
+        match self.buffers.try_lock() {
+            Ok(slot) => Some(slot),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        }
+    }
+
+    /// Optimized path using persistent buffers: upload, dispatch and copy out a batch
+    ///
+    /// Returns `None` without submitting anything if no staging buffer is free.
+    fn submit_persistent_batch(
+        &self,
+        buffers: &PersistentBuffers,
+        hash_params: PersistentHashParams<'_>,
+    ) -> Option<PendingReadback> {
+        let staging = buffers.claim_staging()?;
         let device = self.context.device();
         let queue = self.context.queue();
 
         // Prepare GPU parameters
-        let gpu_params = GpuHashParams {
-            num_hashes: params.num_hashes as u32,
-            input_length: params.input_length as u32,
-            rate_bytes: params.variant.rate_bytes() as u32,
-            output_bytes: output_bytes as u32,
-        };
+        let gpu_params = GpuHashParams::new(hash_params.params, hash_params.output_bytes);
+
+        // Calculate actual buffer sizes needed for this batch
+        let total_input_bytes = hash_params.params.num_hashes * hash_params.params.input_length;
+        let input_buffer_size = ((total_input_bytes + 15) / 16) * 16; // Align to 16 bytes
+        let output_buffer_size = ((hash_params.total_output_bytes + 15) / 16) * 16; // Align to 16 bytes
+
+        // Flatten and copy input data (reuse persistent buffers)
+        let mut input_data = flatten_inputs(hash_params.inputs, input_buffer_size);
+        queue.write_buffer(&buffers.input_buffer, 0, &input_data);
+        wipe_upload(&mut input_data);
+
+        // Update uniform buffer with parameters
+        queue.write_buffer(&buffers.uniform_buffer, 0, bytemuck::cast_slice(&[gpu_params]));
+
+        // Create command encoder and dispatch compute shader
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Command Encoder"),
+        });
+        self.encode_dispatch(&mut encoder, &buffers.bind_group, hash_params.params.num_hashes);
+        self.stats.record(BatchPath::Persistent, hash_params.params.num_hashes, total_input_bytes);
+
+        // Copy output to this batch's staging buffer
+        encoder.copy_buffer_to_buffer(
+            &buffers.output_buffer,
+            0,
+            staging.buffer(),
+            0,
+            output_buffer_size as u64,
+        );
+        encode_input_wipe(&mut encoder, &buffers.input_buffer, input_buffer_size as u64);
+
+        // Submit commands
+        let submission = queue.submit(Some(encoder.finish()));
+        Some(PendingReadback { staging, submission, len: hash_params.total_output_bytes })
+    }
+
+    /// Read back a batch submitted by `submit_persistent_batch`, releasing its staging buffer
+    async fn finish_persistent_batch(
+        &self,
+        pending: PendingReadback,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        let staging_buffer = pending.staging.buffer();
+        let receiver = begin_readback(staging_buffer);
+        self.wait_for_submission(Some(pending.submission), &[staging_buffer]).await?;
+        finish_readback(staging_buffer, receiver, pending.len).await
+    }
 
-        // Calculate buffer sizes (pad to 16-byte alignment to match WGSL struct alignment)
-        let total_input_bytes = params.num_hashes * params.input_length;
+    /// Fallback path for very large batches that exceed persistent buffer capacity
+    ///
+    /// Every hash starts from the sponge state held in `initial_state`.
+    async fn hash_batch_with_dynamic_buffers(
+        &self,
+        inputs: &[&[u8]],
+        gpu_params: GpuHashParams,
+        initial_state: &Buffer,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        let batch = self.create_dynamic_batch(inputs, gpu_params, initial_state);
+
+        // Create command encoder, dispatch compute shader and copy output to staging buffer
+        let mut encoder = self.context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Command Encoder"),
+        });
+        self.encode_dynamic_batch(&mut encoder, &batch);
+        let num_hashes = gpu_params.num_hashes as usize;
+        self.stats.record(BatchPath::Dynamic, num_hashes, inputs.iter().map(|i| i.len()).sum());
+
+        // Submit commands
+        self.context.queue().submit(Some(encoder.finish()));
+
+        // Read results from staging buffer
+        let receiver = begin_readback(&batch.staging_buffer);
+        self.wait_for_readback(&[&batch.staging_buffer]).await?;
+        finish_readback(&batch.staging_buffer, receiver, batch.total_output_bytes).await
+    }
+
+    /// Allocate and fill a fresh buffer set for a single batch starting from `initial_state`
+    ///
+    /// `inputs` are concatenated into the input buffer as-is, so a single pre-laid-out slice
+    /// (e.g. column-major data for `INPUT_LAYOUT_SOA`) is uploaded unchanged.
+    fn create_dynamic_batch(
+        &self,
+        inputs: &[&[u8]],
+        gpu_params: GpuHashParams,
+        initial_state: &Buffer,
+    ) -> DynamicBatch {
+        let device = self.context.device();
+        let queue = self.context.queue();
+        let num_hashes = gpu_params.num_hashes as usize;
+        let total_output_bytes = num_hashes * gpu_params.output_bytes as usize;
+
+        // Calculate buffer sizes (pad to 16-byte alignment to match WGSL struct alignment).
+        // Inputs only: the shader synthesizes padding, including the extra block a
+        // rate-multiple input needs, and never loads past the last input's bytes.
+        let total_input_bytes = num_hashes * gpu_params.input_length as usize;
         let input_buffer_size = if total_input_bytes == 0 {
             16 // Minimum size for empty input (16-byte alignment)
         } else {
@@ -548,13 +2517,9 @@ impl GpuSha3Hasher {
         });
 
         // Flatten and copy input data (optimized allocation)
-        let mut input_data = Vec::with_capacity(input_buffer_size);
-        for input in inputs.iter() {
-            input_data.extend_from_slice(input);
-        }
-        // Pad to required buffer size
-        input_data.resize(input_buffer_size, 0);
+        let mut input_data = flatten_inputs(inputs, input_buffer_size);
         queue.write_buffer(&input_buffer, 0, &input_data);
+        wipe_upload(&mut input_data);
 
         // Create output buffer
         let output_buffer = device.create_buffer(&BufferDescriptor {
@@ -587,72 +2552,117 @@ impl GpuSha3Hasher {
                 BindGroupEntry { binding: 0, resource: input_buffer.as_entire_binding() },
                 BindGroupEntry { binding: 1, resource: output_buffer.as_entire_binding() },
                 BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 3, resource: initial_state.as_entire_binding() },
             ],
         });
 
-        // Create command encoder and dispatch compute shader
-        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
-            label: Some("SHA-3 Command Encoder"),
-        });
-
-        {
-            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
-                label: Some("SHA-3 Compute Pass"),
-                timestamp_writes: None,
-            });
-
-            compute_pass.set_pipeline(&self.pipeline);
-            compute_pass.set_bind_group(0, &bind_group, &[]);
-
-            // Dispatch workgroups (one thread per hash, 256 threads per workgroup)
-            let workgroup_size = 256;
-            let num_workgroups = (params.num_hashes + workgroup_size - 1) / workgroup_size;
-            compute_pass.dispatch_workgroups(num_workgroups as u32, 1, 1);
+        DynamicBatch {
+            input_buffer,
+            output_buffer,
+            staging_buffer,
+            bind_group,
+            num_hashes,
+            output_buffer_size,
+            total_output_bytes,
         }
+    }
 
-        // Copy output to staging buffer
+    /// Record the compute dispatch and staging copy for a dynamic batch
+    fn encode_dynamic_batch(&self, encoder: &mut CommandEncoder, batch: &DynamicBatch) {
+        self.encode_dispatch(encoder, &batch.bind_group, batch.num_hashes);
         encoder.copy_buffer_to_buffer(
-            &output_buffer,
+            &batch.output_buffer,
             0,
-            &staging_buffer,
+            &batch.staging_buffer,
             0,
-            output_buffer_size as u64,
+            batch.output_buffer_size as u64,
         );
+        encode_input_wipe(encoder, &batch.input_buffer, batch.input_buffer.size());
+    }
 
-        // Submit commands
-        queue.submit(Some(encoder.finish()));
-
-        // Read results from staging buffer
-        let buffer_slice = staging_buffer.slice(..);
-        let (sender, receiver) = oneshot::channel();
+    /// Record a compute pass hashing `num_hashes` inputs described by `bind_group`
+    fn encode_dispatch(
+        &self,
+        encoder: &mut CommandEncoder,
+        bind_group: &BindGroup,
+        num_hashes: usize,
+    ) {
+        // One thread per hash
+        self.encode_pass(encoder, &self.pipeline, &[bind_group], num_hashes);
+    }
 
-        buffer_slice.map_async(MapMode::Read, move |result| {
-            let _ = sender.send(result);
+    /// Record a compute pass running `invocations` threads of a workgroup-sized `pipeline`
+    ///
+    /// `bind_groups` are bound to consecutive groups starting at 0.
+    fn encode_pass(
+        &self,
+        encoder: &mut CommandEncoder,
+        pipeline: &ComputePipeline,
+        bind_groups: &[&BindGroup],
+        invocations: usize,
+    ) {
+        let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("SHA-3 Compute Pass"),
+            timestamp_writes: None,
         });
+        self.dispatch_in_pass(&mut compute_pass, pipeline, bind_groups, invocations);
+    }
 
-        // Ensure the mapping callback is processed on native targets
-        #[allow(unused_must_use)]
-        {
-            device.poll(wgpu::PollType::Wait { submission_index: None, timeout: None });
+    /// Bind `bind_groups` and dispatch `invocations` threads of `pipeline` in an open pass
+    fn dispatch_in_pass(
+        &self,
+        compute_pass: &mut ComputePass<'_>,
+        pipeline: &ComputePipeline,
+        bind_groups: &[&BindGroup],
+        invocations: usize,
+    ) {
+        compute_pass.set_pipeline(pipeline);
+        for (index, bind_group) in bind_groups.iter().enumerate() {
+            compute_pass.set_bind_group(index as u32, *bind_group, &[]);
         }
 
-        // Wait for the mapping callback to fire
-        receiver
-            .await
-            .map_err(|_| {
-                GpuSha3Error::BufferMapping("Failed to receive buffer mapping result".into())
-            })?
-            .map_err(|e| GpuSha3Error::BufferMapping(format!("Buffer mapping failed: {e:?}")))?;
+        let workgroup_size = self.workgroup_size as usize;
+        let num_workgroups = (invocations + workgroup_size - 1) / workgroup_size;
+        let (x, y) = dispatch_grid(num_workgroups as u32, self.dispatch_2d_threshold);
+        compute_pass.dispatch_workgroups(x, y, 1);
+    }
 
-        // Extract output data
-        let data = buffer_slice.get_mapped_range();
-        let mut result = vec![0u8; total_output_bytes];
-        result.copy_from_slice(&data[..total_output_bytes]);
+    /// Wait for submitted work so pending mapping callbacks are processed
+    ///
+    /// On native targets the blocking `device.poll` runs on the hasher's polling thread
+    /// while this future awaits its outcome, so the calling executor thread is never
+    /// blocked. On WebGPU mappings are completed by the browser event loop and no polling
+    /// is needed.
+    ///
+    /// If the wait fails (e.g. times out), the pending mappings of `staging_buffers` are
+    /// cancelled so the buffers can be mapped again by later calls.
+    async fn wait_for_readback(&self, staging_buffers: &[&Buffer]) -> Result<(), GpuSha3Error> {
+        self.wait_for_submission(None, staging_buffers).await
+    }
 
-        drop(data);
-        staging_buffer.unmap();
+    /// Wait for `submission` (or all submitted work, if `None`) as in `wait_for_readback`
+    ///
+    /// Waiting on its own submission lets a batch finish while later ones still run.
+    async fn wait_for_submission(
+        &self,
+        submission: Option<SubmissionIndex>,
+        staging_buffers: &[&Buffer],
+    ) -> Result<(), GpuSha3Error> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.poller.wait(submission, self.readback_timeout).await.map_err(|e| {
+                for staging_buffer in staging_buffers {
+                    staging_buffer.unmap();
+                }
+                e
+            })
+        }
 
-        Ok(result)
+        #[cfg(target_arch = "wasm32")]
+        {
+            let _ = (submission, staging_buffers);
+            Ok(())
+        }
     }
 
     /// Get the SHA-3 variant this hasher uses
@@ -664,6 +2674,18 @@ impl GpuSha3Hasher {
     pub fn context(&self) -> &GpuContext {
         &self.context
     }
+
+    /// Describe the compiled pipeline (entry point, variant, workgroup size, device features)
+    ///
+    /// Useful to attach to bug reports when digests mismatch on a particular GPU.
+    pub fn debug_info(&self) -> PipelineDebugInfo {
+        PipelineDebugInfo {
+            entry_point: ENTRY_POINT,
+            variant: self.variant,
+            workgroup_size: self.workgroup_size,
+            features: self.context.device().features(),
+        }
+    }
 }
 
 impl std::fmt::Debug for GpuSha3Hasher {
@@ -672,7 +2694,10 @@ impl std::fmt::Debug for GpuSha3Hasher {
             .field("variant", &self.variant)
             .field("context", &self.context)
             .field("max_batch_size", &self.max_batch_size)
-            .field("has_persistent_buffers", &self.buffers.is_some())
+            .field("current_capacity", &self.current_capacity())
+            .field("grow_buffers", &self.grow_buffers)
+            .field("staging_buffers", &self.staging_buffers)
+            .field("has_fixed_prefix", &self.prefix.is_some())
             .finish()
     }
 }
@@ -0,0 +1,146 @@
+//! Smallest digest of a batch, found by a reduction on the GPU
+
+use sha3_core::{BatchHashParams, PreparedShape};
+use wgpu::util::DeviceExt;
+use wgpu::*;
+
+use super::stats::BatchPath;
+use super::{begin_readback, finish_readback, GpuHashParams, GpuSha3Hasher};
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Hash a batch and return the index and value of the lexicographically smallest digest
+    ///
+    /// For sortition and leader election. The digests stay on the GPU, where a pairwise
+    /// reduction finds the smallest, so only its index and the one digest are read back.
+    /// Ties go to the lowest index. Inputs must all be the same length, as for
+    /// [`hash_batch`](Self::hash_batch); an empty batch fails with `InvalidInputLength`.
+    pub async fn argmin_digest(&self, inputs: &[&[u8]]) -> Result<(usize, Vec<u8>), GpuSha3Error> {
+        if inputs.is_empty() {
+            return Err(GpuSha3Error::InvalidInputLength(0));
+        }
+        let input_length = inputs[0].len();
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+
+        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let framed = self.frame_inputs(inputs);
+        let framed_refs: Vec<&[u8]>;
+        let framed_inputs = match &framed {
+            Some(framed) => {
+                framed_refs = framed.iter().map(|v| v.as_slice()).collect();
+                &framed_refs[..]
+            }
+            None => inputs,
+        };
+
+        if !self.routes_to_gpu(inputs.len(), framed_inputs[0].len(), output_bytes)
+            || !(self.shader_supports)(self.variant)
+        {
+            let prefix = self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..]);
+            let digests = self.hash_on_cpu(prefix, inputs, &params)?;
+            let (index, digest) = digests
+                .chunks(output_bytes)
+                .enumerate()
+                .min_by_key(|&(_, digest)| digest)
+                .expect("batch is not empty");
+            return Ok((index, digest.to_vec()));
+        }
+        self.argmin_on_gpu(framed_inputs, &params, output_bytes).await
+    }
+
+    /// Hash `inputs`, reduce their digests on the GPU, then read back the winner
+    async fn argmin_on_gpu(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+        output_bytes: usize,
+    ) -> Result<(usize, Vec<u8>), GpuSha3Error> {
+        let device = self.context.device();
+        let num_hashes = inputs.len();
+        let gpu_params = GpuHashParams::new(params, output_bytes)
+            .with_shape(PreparedShape::new(params.variant, inputs[0].len()));
+        let batch = self.create_dynamic_batch(inputs, gpu_params, &self.initial_state_buffer);
+
+        let slots: Vec<u32> = (0..num_hashes as u32).collect();
+        let mut slot_bytes = bytemuck::cast_slice::<u32, u8>(&slots).to_vec();
+        slot_bytes.resize((slot_bytes.len() + 15) / 16 * 16, 0);
+        let index_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Argmin Index Buffer"),
+            contents: &slot_bytes,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Argmin Command Encoder"),
+        });
+        self.encode_dispatch(&mut encoder, &batch.bind_group, num_hashes);
+        self.stats.record(BatchPath::Dynamic, num_hashes, num_hashes * inputs[0].len());
+
+        // One pass per tree level; each needs its own stride, so its own uniform
+        let mut stride = 1;
+        while stride < num_hashes {
+            let stride_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+                label: Some("SHA-3 Argmin Stride Buffer"),
+                contents: bytemuck::cast_slice(&[stride as u32, 0, 0, 0]),
+                usage: BufferUsages::UNIFORM,
+            });
+            let pass_bind_group = device.create_bind_group(&BindGroupDescriptor {
+                label: Some("SHA-3 Argmin Bind Group"),
+                layout: &self.argmin_bind_group_layout,
+                entries: &[
+                    BindGroupEntry { binding: 2, resource: index_buffer.as_entire_binding() },
+                    BindGroupEntry { binding: 3, resource: stride_buffer.as_entire_binding() },
+                ],
+            });
+            let pairs = (num_hashes + 2 * stride - 1) / (2 * stride);
+            self.encode_pass(
+                &mut encoder,
+                &self.argmin_pipeline,
+                &[&batch.bind_group, &pass_bind_group],
+                pairs,
+            );
+            stride *= 2;
+        }
+
+        // Slot 0 now indexes the smallest digest
+        let index_staging = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Argmin Index Staging Buffer"),
+            size: 16,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        encoder.copy_buffer_to_buffer(&index_buffer, 0, &index_staging, 0, 16);
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&index_staging);
+        self.wait_for_readback(&[&index_staging]).await?;
+        let index_bytes = finish_readback(&index_staging, receiver, 4).await?;
+        let index = u32::from_le_bytes(index_bytes[..].try_into().unwrap()) as usize;
+
+        // Copy out just the word-aligned span holding the winning digest
+        let start = index * output_bytes;
+        let aligned_start = start / 4 * 4;
+        let aligned_end = (start + output_bytes + 3) / 4 * 4;
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Argmin Digest Command Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(
+            &batch.output_buffer,
+            aligned_start as u64,
+            &batch.staging_buffer,
+            0,
+            (aligned_end - aligned_start) as u64,
+        );
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&batch.staging_buffer);
+        self.wait_for_readback(&[&batch.staging_buffer]).await?;
+        let span =
+            finish_readback(&batch.staging_buffer, receiver, aligned_end - aligned_start).await?;
+        let offset = start - aligned_start;
+        Ok((index, span[offset..offset + output_bytes].to_vec()))
+    }
+}
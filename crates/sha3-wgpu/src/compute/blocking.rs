@@ -0,0 +1,15 @@
+//! Synchronous wrappers over the async hashing API, for callers without an executor
+
+use super::GpuSha3Hasher;
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// [`hash_batch`](Self::hash_batch), blocking the calling thread until the digests are
+    /// read back
+    ///
+    /// Needs no async runtime; don't call it from inside one, where it would stall the
+    /// executor thread for the whole GPU round trip.
+    pub fn hash_batch_blocking(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        pollster::block_on(self.hash_batch(inputs))
+    }
+}
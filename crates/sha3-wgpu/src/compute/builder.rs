@@ -0,0 +1,117 @@
+//! Named configuration for constructing a [`GpuSha3Hasher`]
+
+use sha3_core::Sha3Variant;
+
+use super::GpuSha3Hasher;
+use crate::context::GpuContext;
+use crate::error::GpuSha3Error;
+
+/// Hashes the default persistent buffers hold per batch
+const DEFAULT_MAX_BATCH_SIZE: usize = 1000;
+/// Input bytes per hash the default persistent buffers hold (larger batches grow them)
+const DEFAULT_MAX_INPUT_LENGTH: usize = 8192;
+/// Output bytes per hash the default persistent buffers hold (covers SHA3-512 and
+/// reasonable SHAKE outputs)
+const DEFAULT_MAX_OUTPUT_BYTES: usize = 64;
+
+/// Builder for a [`GpuSha3Hasher`], created by [`GpuSha3Hasher::builder`]
+///
+/// Names the persistent-buffer shape that
+/// [`with_persistent_buffers`](GpuSha3Hasher::with_persistent_buffers) takes as a tuple.
+/// Defaults match [`GpuSha3Hasher::new`]: SHA3-256, with persistent buffers for 1000
+/// hashes of up to 8 KiB input and 64 bytes output.
+///
+/// ```no_run
+/// # async fn example() -> Result<(), sha3_wgpu::GpuSha3Error> {
+/// use sha3_core::Sha3Variant;
+/// use sha3_wgpu::{GpuContext, GpuSha3Hasher};
+///
+/// let hasher = GpuSha3Hasher::builder()
+///     .variant(Sha3Variant::Keccak256)
+///     .max_batch_size(4096)
+///     .max_input_length(64)
+///     .build(GpuContext::new().await?)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GpuSha3HasherBuilder {
+    variant: Sha3Variant,
+    max_batch_size: usize,
+    max_input_length: usize,
+    max_output_bytes: usize,
+    persistent: bool,
+}
+
+impl Default for GpuSha3HasherBuilder {
+    fn default() -> Self {
+        Self {
+            variant: Sha3Variant::Sha3_256,
+            max_batch_size: DEFAULT_MAX_BATCH_SIZE,
+            max_input_length: DEFAULT_MAX_INPUT_LENGTH,
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            persistent: true,
+        }
+    }
+}
+
+impl GpuSha3HasherBuilder {
+    /// Start from the defaults
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hash function the hasher computes
+    pub fn variant(mut self, variant: Sha3Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Hashes per batch the persistent buffers hold initially
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size;
+        self
+    }
+
+    /// Input bytes per hash the persistent buffers hold initially
+    pub fn max_input_length(mut self, max_input_length: usize) -> Self {
+        self.max_input_length = max_input_length;
+        self
+    }
+
+    /// Output bytes per hash the persistent buffers hold initially
+    pub fn max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Whether to keep persistent buffers (default `true`)
+    ///
+    /// Without them every batch allocates dynamic buffers, and the `max_*` sizes are
+    /// ignored.
+    pub fn persistent(mut self, persistent: bool) -> Self {
+        self.persistent = persistent;
+        self
+    }
+
+    /// Create the hasher on `context`
+    ///
+    /// Fails as [`with_persistent_buffers`](GpuSha3Hasher::with_persistent_buffers) does,
+    /// e.g. with `InvalidInputLength` or `InvalidOutputLength` if the persistent buffers
+    /// would exceed the device's storage buffer binding size.
+    pub fn build(self, context: GpuContext) -> Result<GpuSha3Hasher, GpuSha3Error> {
+        let config = (self.max_batch_size, self.max_input_length, self.max_output_bytes);
+        GpuSha3Hasher::with_persistent_buffers(
+            context,
+            self.variant,
+            self.persistent.then_some(config),
+        )
+    }
+}
+
+impl GpuSha3Hasher {
+    /// Start configuring a hasher with named settings (see [`GpuSha3HasherBuilder`])
+    pub fn builder() -> GpuSha3HasherBuilder {
+        GpuSha3HasherBuilder::new()
+    }
+}
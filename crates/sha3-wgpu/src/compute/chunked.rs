@@ -0,0 +1,45 @@
+//! Hashing lazily produced inputs a chunk at a time
+
+use futures::Stream;
+
+use super::GpuSha3Hasher;
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Hash the inputs `inputs` yields, pulling `chunk` (at least one) at a time
+    ///
+    /// Each item of the returned stream is one chunk's concatenated digests, in input order;
+    /// the next chunk is only pulled from `inputs` once the stream is polled again, so at
+    /// most one chunk of inputs and digests is held at once however many inputs there are.
+    /// Inputs may differ in length (a chunk of mixed lengths is hashed as by
+    /// [`hash_batch_grouped`](Self::hash_batch_grouped)). The stream ends after the last
+    /// chunk, or after the first chunk that fails. The stream isn't `Unpin`; pin it (e.g.
+    /// with `futures::pin_mut!`) to call `next` on it.
+    pub fn hash_stream_batch<'a, I>(
+        &'a self,
+        inputs: I,
+        chunk: usize,
+    ) -> impl Stream<Item = Result<Vec<u8>, GpuSha3Error>> + 'a
+    where
+        I: IntoIterator + 'a,
+        I::Item: AsRef<[u8]>,
+    {
+        let chunk = chunk.max(1);
+        futures::stream::unfold(Some(inputs.into_iter()), move |remaining| async move {
+            let mut remaining = remaining?;
+            let items: Vec<I::Item> = remaining.by_ref().take(chunk).collect();
+            if items.is_empty() {
+                return None;
+            }
+
+            let refs: Vec<&[u8]> = items.iter().map(AsRef::as_ref).collect();
+            let result = if refs.iter().all(|input| input.len() == refs[0].len()) {
+                self.hash_batch(&refs).await
+            } else {
+                self.hash_batch_grouped(&refs).await
+            };
+            let remaining = result.is_ok().then_some(remaining);
+            Some((result, remaining))
+        })
+    }
+}
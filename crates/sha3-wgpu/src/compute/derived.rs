@@ -0,0 +1,269 @@
+//! NIST SP 800-185 functions built on cSHAKE: cSHAKE itself, KMAC, TupleHash and
+//! ParallelHash
+
+use std::collections::BTreeMap;
+
+use sha3_core::encoding::{bytepad, cshake_header, encode_string, left_encode, right_encode};
+use sha3_core::{BatchHashParams, Sha3Error, Sha3Variant};
+use wgpu::*;
+
+use super::{
+    cpu_hash_batch, extend_host_copy, host_copy, state_to_gpu_words, FixedPrefix, GpuHashParams,
+    GpuSha3Hasher, HostCopy, Tuple, STATE_BYTES,
+};
+use crate::error::GpuSha3Error;
+
+/// Padding domain byte of cSHAKE with a non-empty function name or customization string
+const CSHAKE_DOMAIN_SEPARATOR: u8 = 0x04;
+
+impl GpuSha3Hasher {
+    /// Compute cSHAKE of each input with function name `function_name` and customization
+    /// string `custom`
+    ///
+    /// Returns one `output_len`-byte output per input, in input order; inputs may differ in
+    /// length. Runs on SHAKE and cSHAKE hashers of either strength. With both strings empty
+    /// the result is exactly SHAKE, as SP 800-185 requires. Independent of any fixed prefix
+    /// set on the hasher.
+    pub async fn cshake(
+        &self,
+        inputs: &[&[u8]],
+        output_len: usize,
+        function_name: &[u8],
+        custom: &[u8],
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        if !matches!(
+            self.variant,
+            Sha3Variant::Shake128
+                | Sha3Variant::Shake256
+                | Sha3Variant::CShake128
+                | Sha3Variant::CShake256
+        ) {
+            return Err(GpuSha3Error::UnsupportedVariant(self.variant));
+        }
+        let header = if function_name.is_empty() && custom.is_empty() {
+            host_copy(Vec::new())
+        } else {
+            host_copy(cshake_header(function_name, custom, self.variant.rate_bytes()))
+        };
+        self.cshake_batch(header, &[], inputs, output_len).await
+    }
+
+    /// Compute KMAC128 (SHAKE128 hashers) or KMAC256 (SHAKE256 hashers) of each input
+    ///
+    /// Returns one `output_len`-byte tag per input, in input order. Per SP 800-185 this is
+    /// cSHAKE over `bytepad(encode_string(key), rate) || input || right_encode(8 *
+    /// output_len)` with function name `"KMAC"` and customization string `custom`. The
+    /// padded key block is absorbed once for the whole batch; inputs may differ in length.
+    /// Independent of any fixed prefix set on the hasher.
+    pub async fn kmac(
+        &self,
+        key: &[u8],
+        inputs: &[&[u8]],
+        output_len: usize,
+        custom: &[u8],
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        if !matches!(self.variant, Sha3Variant::Shake128 | Sha3Variant::Shake256) {
+            return Err(GpuSha3Error::UnsupportedVariant(self.variant));
+        }
+        let rate_bytes = self.variant.rate_bytes();
+        let encoded_key = host_copy(encode_string(key));
+        let padded_key = host_copy(bytepad(&encoded_key, rate_bytes));
+        let header = cshake_header(b"KMAC", custom, rate_bytes);
+        let header = host_copy([&header[..], &padded_key[..]].concat());
+        let suffix = right_encode(output_len as u64 * 8);
+        self.cshake_batch(header, &suffix, inputs, output_len).await
+    }
+
+    /// Compute TupleHash128 (SHAKE128 hashers) or TupleHash256 (SHAKE256 hashers) of each
+    /// tuple
+    ///
+    /// Returns one `output_len`-byte output per tuple, in order. Per SP 800-185 this is
+    /// cSHAKE over `encode_string(x_1) || ... || encode_string(x_n) || right_encode(8 *
+    /// output_len)` with function name `"TupleHash"` and customization string `custom`, so
+    /// tuples whose concatenations coincide (e.g. `["ab", "c"]` and `["a", "bc"]`) still
+    /// hash differently. Independent of any fixed prefix set on the hasher.
+    pub async fn tuple_hash(
+        &self,
+        tuples: &[Tuple<'_>],
+        output_len: usize,
+        custom: &[u8],
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        if !matches!(self.variant, Sha3Variant::Shake128 | Sha3Variant::Shake256) {
+            return Err(GpuSha3Error::UnsupportedVariant(self.variant));
+        }
+        let header = host_copy(cshake_header(b"TupleHash", custom, self.variant.rate_bytes()));
+        let encoded: Vec<HostCopy> = tuples
+            .iter()
+            .map(|tuple| {
+                let mut message = host_copy(Vec::new());
+                for string in tuple.iter() {
+                    extend_host_copy(&mut message, &host_copy(encode_string(string)));
+                }
+                message
+            })
+            .collect();
+        let encoded_refs: Vec<&[u8]> = encoded.iter().map(|v| v.as_slice()).collect();
+        let suffix = right_encode(output_len as u64 * 8);
+        self.cshake_batch(header, &suffix, &encoded_refs, output_len).await
+    }
+
+    /// Compute ParallelHash128 (SHAKE128 hashers) or ParallelHash256 (SHAKE256 hashers) of
+    /// `data`
+    ///
+    /// Returns `output_len` bytes. Per SP 800-185, `data` is cut into `block_size`-byte
+    /// blocks (the last may be shorter), each block is hashed with SHAKE to twice the
+    /// security strength (32 or 64 bytes), and the result is cSHAKE over
+    /// `left_encode(block_size) || digests || right_encode(blocks) || right_encode(8 *
+    /// output_len)` with function name `"ParallelHash"` and customization string `custom`.
+    /// The blocks are hashed on the GPU as one batch; the final absorb, over one digest per
+    /// block, runs on the CPU. Fails with `InvalidInputLength` for a zero block size.
+    /// Independent of any fixed prefix set on the hasher.
+    pub async fn parallel_hash(
+        &self,
+        data: &[u8],
+        block_size: usize,
+        output_len: usize,
+        custom: &[u8],
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        let (final_variant, digest_len) = match self.variant {
+            Sha3Variant::Shake128 => (Sha3Variant::CShake128, 32),
+            Sha3Variant::Shake256 => (Sha3Variant::CShake256, 64),
+            variant => return Err(GpuSha3Error::UnsupportedVariant(variant)),
+        };
+        if block_size == 0 {
+            return Err(GpuSha3Error::InvalidInputLength(block_size));
+        }
+
+        let blocks: Vec<&[u8]> = data.chunks(block_size).collect();
+        let shake_domain = Sha3Variant::Shake128.domain_separator();
+        let digests = self
+            .hash_after_header(host_copy(Vec::new()), &blocks, digest_len, shake_domain)
+            .await?;
+        let mut message = left_encode(block_size as u64);
+        message.extend(digests.concat());
+        message.extend(right_encode(blocks.len() as u64));
+        message.extend(right_encode(output_len as u64 * 8));
+
+        let params = BatchHashParams::new(final_variant, 1, message.len())
+            .with_output_length(output_len)
+            .with_function_name("ParallelHash")
+            .with_customization(custom);
+        cpu_hash_batch(&[], &[&message], &params)
+    }
+
+    /// `hash_batch_with_params` for cSHAKE batches with a header: each message is the whole
+    /// fixed prefix, if any, followed by the input, since the header must be absorbed first
+    pub(super) async fn hash_batch_cshake(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if params.variant != self.variant {
+            // The header is absorbed with the hasher's own variant
+            let prefix = self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..]);
+            return self.hash_on_cpu(prefix, inputs, params);
+        }
+        let output_len = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let rate_bytes = self.variant.rate_bytes();
+        let header =
+            host_copy(cshake_header(&params.function_name, &params.customization, rate_bytes));
+        let domain_separator = params.domain_separator();
+        let outputs = match &self.prefix {
+            Some(prefix) => {
+                let framed: Vec<HostCopy> = inputs
+                    .iter()
+                    .map(|input| host_copy([&prefix.bytes[..], input].concat()))
+                    .collect();
+                let framed_refs: Vec<&[u8]> = framed.iter().map(|v| v.as_slice()).collect();
+                self.hash_after_header(header, &framed_refs, output_len, domain_separator).await?
+            }
+            None => self.hash_after_header(header, inputs, output_len, domain_separator).await?,
+        };
+        Ok(outputs.concat())
+    }
+
+    /// Sponge each `input || suffix` after the whole-block `header`
+    ///
+    /// Pads with the cSHAKE domain byte, or SHAKE's when `header` is empty.
+    async fn cshake_batch(
+        &self,
+        header: HostCopy,
+        suffix: &[u8],
+        inputs: &[&[u8]],
+        output_len: usize,
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        let domain_separator = if header.is_empty() {
+            Sha3Variant::Shake128.domain_separator()
+        } else {
+            CSHAKE_DOMAIN_SEPARATOR
+        };
+        if suffix.is_empty() {
+            return self.hash_after_header(header, inputs, output_len, domain_separator).await;
+        }
+        let messages: Vec<HostCopy> =
+            inputs.iter().map(|input| host_copy([input, suffix].concat())).collect();
+        let message_refs: Vec<&[u8]> = messages.iter().map(|v| v.as_slice()).collect();
+        self.hash_after_header(header, &message_refs, output_len, domain_separator).await
+    }
+
+    /// Sponge each input after `header`, a whole number of rate blocks, grouping by length
+    /// and padding with `domain_separator`
+    ///
+    /// `header` is absorbed once on the CPU; every input then starts from that state.
+    pub(super) async fn hash_after_header(
+        &self,
+        header: HostCopy,
+        inputs: &[&[u8]],
+        output_len: usize,
+        domain_separator: u8,
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        self.check_shader_support(self.variant)?;
+        if output_len == 0 {
+            return Err(GpuSha3Error::Core(Sha3Error::InvalidOutputLength(output_len)));
+        }
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // A whole number of rate blocks, so nothing is left to prepend
+        let prefix = FixedPrefix::new(header, self.variant.rate_bytes());
+        debug_assert!(prefix.tail().is_empty());
+        let state_buffer = self.context.device().create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Header State Buffer"),
+            size: STATE_BYTES as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        self.context.queue().write_buffer(
+            &state_buffer,
+            0,
+            bytemuck::cast_slice(&state_to_gpu_words(&prefix.state)),
+        );
+
+        let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for (i, input) in inputs.iter().enumerate() {
+            groups.entry(input.len()).or_default().push(i);
+        }
+
+        let mut outputs = vec![Vec::new(); inputs.len()];
+        for (length, indices) in groups {
+            let group: Vec<&[u8]> = indices.iter().map(|&i| inputs[i]).collect();
+            let params = BatchHashParams::new(self.variant, indices.len(), length)
+                .with_output_length(output_len);
+            if !self.fits_device(params.num_hashes, params.input_length, output_len) {
+                return Err(GpuSha3Error::InvalidInputLength(length));
+            }
+
+            let gpu_params = GpuHashParams {
+                domain_separator: domain_separator as u32,
+                ..GpuHashParams::new(&params, output_len)
+            };
+            let digests =
+                self.hash_batch_with_dynamic_buffers(&group, gpu_params, &state_buffer).await?;
+            for (&i, digest) in indices.iter().zip(digests.chunks_exact(output_len)) {
+                outputs[i] = digest.to_vec();
+            }
+        }
+        Ok(outputs)
+    }
+}
@@ -0,0 +1,54 @@
+//! HMAC (RFC 2104) over the fixed-length SHA-3 and Keccak variants
+
+use sha3_core::BatchHashParams;
+
+use super::{cpu_hash_batch, host_copy, GpuSha3Hasher, HostCopy};
+use crate::error::GpuSha3Error;
+
+/// Byte XORed into the key block for the inner hash
+const IPAD: u8 = 0x36;
+/// Byte XORed into the key block for the outer hash
+const OPAD: u8 = 0x5c;
+
+impl GpuSha3Hasher {
+    /// Compute HMAC of each input under `key`, with the hasher's variant as the hash
+    ///
+    /// Returns one tag per input, in input order, each as long as the variant's digest. This
+    /// is `H((K ^ opad) || H((K ^ ipad) || input))` with the variant's rate as the block
+    /// size, as in NIST's HMAC-SHA3; a key longer than the block is hashed first. The two
+    /// padded key blocks are absorbed once for the whole batch, then the inner hashes run
+    /// on the GPU grouped by input length and the outer hashes as a single batch. Prefer
+    /// [`kmac`](Self::kmac) outside protocols that require HMAC. Runs on the fixed-length
+    /// SHA-3 and Keccak hashers; independent of any fixed prefix set on the hasher.
+    pub async fn hmac(&self, key: &[u8], inputs: &[&[u8]]) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        let output_bytes = self.variant.output_bytes();
+        // Zero for the variable-length SHAKE and cSHAKE variants
+        if output_bytes == 0 {
+            return Err(GpuSha3Error::UnsupportedVariant(self.variant));
+        }
+        let rate_bytes = self.variant.rate_bytes();
+        let hashed_key;
+        let short_key = if key.len() > rate_bytes {
+            let params = BatchHashParams::new(self.variant, 1, key.len());
+            hashed_key = host_copy(cpu_hash_batch(&[], &[key], &params)?);
+            &hashed_key[..]
+        } else {
+            key
+        };
+        // Zero-filled up front, so filling it in never reallocates and leaves a stray copy
+        let mut key_block = host_copy(vec![0; rate_bytes]);
+        key_block[..short_key.len()].copy_from_slice(short_key);
+
+        let domain_separator = self.variant.domain_separator();
+        let inner_header = host_copy(key_block.iter().map(|byte| byte ^ IPAD).collect());
+        let inner: Vec<HostCopy> = self
+            .hash_after_header(inner_header, inputs, output_bytes, domain_separator)
+            .await?
+            .into_iter()
+            .map(host_copy)
+            .collect();
+        let inner_refs: Vec<&[u8]> = inner.iter().map(|v| v.as_slice()).collect();
+        let outer_header = host_copy(key_block.iter().map(|byte| byte ^ OPAD).collect());
+        self.hash_after_header(outer_header, &inner_refs, output_bytes, domain_separator).await
+    }
+}
@@ -0,0 +1,39 @@
+//! Binary Merkle tree roots, with every layer above the leaves built on the GPU
+
+use super::GpuSha3Hasher;
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Compute the root of the binary Merkle tree over `leaves`
+    ///
+    /// Each leaf is hashed, then adjacent pairs of nodes are hashed (`H(left || right)`)
+    /// layer by layer until one node remains; a layer with an odd number of nodes pairs its
+    /// last node with itself. A single leaf's root is its hash. The hasher's variant is used
+    /// at every level, so it must have a fixed digest length.
+    ///
+    /// The layers stay on the GPU (see [`hash_retained`](Self::hash_retained)) and only the
+    /// root is read back. Leaves of equal length are uploaded and hashed in one batch;
+    /// mixed-length leaves go through [`hash_batch_grouped`](Self::hash_batch_grouped)
+    /// first. As with `hash_retained`, a fixed prefix is supported only when it is fully
+    /// absorbed. Fails with `InvalidInputLength` for an empty tree.
+    pub async fn merkle_root(&self, leaves: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        if leaves.is_empty() {
+            return Err(GpuSha3Error::InvalidInputLength(0));
+        }
+        let leaf_length = leaves[0].len();
+        let mut layer = if leaves.iter().all(|leaf| leaf.len() == leaf_length) {
+            self.hash_batch_retained(leaves)?
+        } else {
+            let digests = self.hash_batch_grouped(leaves).await?;
+            self.retain_digests(&digests, digests.len() / leaves.len())
+        };
+
+        while layer.num_hashes() > 1 {
+            if layer.num_hashes() % 2 == 1 {
+                layer = self.duplicate_last_digest(&layer);
+            }
+            layer = self.hash_retained(&layer, 2 * layer.digest_len())?;
+        }
+        self.read(&layer).await
+    }
+}
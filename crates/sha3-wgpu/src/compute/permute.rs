@@ -0,0 +1,128 @@
+//! Direct access to the Keccak-f[1600] permutation and sponge state, for sponge
+//! constructions of your own
+
+use sha3_core::BatchHashParams;
+use wgpu::util::DeviceExt;
+use wgpu::*;
+
+use super::{
+    begin_readback, finish_readback, state_to_gpu_words, GpuHashParams, GpuSha3Hasher, STATE_BYTES,
+};
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Apply the 24-round Keccak-f\[1600\] permutation to each state on the GPU
+    ///
+    /// Lanes are indexed `x + 5 * y` as in FIPS 202, matching `keccak::f1600`. Returns the
+    /// permuted states in input order. Independent of the hasher's variant and any fixed
+    /// prefix; fails with `InvalidInputLength` if the batch exceeds the device limits.
+    pub async fn permute_states(
+        &self,
+        states: &[[u64; 25]],
+    ) -> Result<Vec<[u64; 25]>, GpuSha3Error> {
+        if states.is_empty() {
+            return Ok(Vec::new());
+        }
+        if !self.fits_device(states.len(), STATE_BYTES, STATE_BYTES) {
+            return Err(GpuSha3Error::InvalidInputLength(states.len() * STATE_BYTES));
+        }
+
+        let bytes: Vec<u8> = states.iter().flatten().flat_map(|lane| lane.to_le_bytes()).collect();
+        // Only `num_hashes` matters to the permute entry point
+        let params = BatchHashParams::new(self.variant, states.len(), STATE_BYTES);
+        let gpu_params = GpuHashParams::new(&params, STATE_BYTES);
+        let batch = self.create_dynamic_batch(&[&bytes], gpu_params, &self.initial_state_buffer);
+
+        let mut encoder = self.context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Permute Command Encoder"),
+        });
+        self.encode_pass(&mut encoder, &self.permute_pipeline, &[&batch.bind_group], states.len());
+        encoder.copy_buffer_to_buffer(
+            &batch.output_buffer,
+            0,
+            &batch.staging_buffer,
+            0,
+            batch.output_buffer_size as u64,
+        );
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&batch.staging_buffer);
+        self.wait_for_readback(&[&batch.staging_buffer]).await?;
+        let permuted =
+            finish_readback(&batch.staging_buffer, receiver, batch.total_output_bytes).await?;
+
+        Ok(permuted
+            .chunks_exact(STATE_BYTES)
+            .map(|state| {
+                let mut lanes = [0u64; 25];
+                for (lane, bytes) in lanes.iter_mut().zip(state.chunks_exact(8)) {
+                    *lane = u64::from_le_bytes(bytes.try_into().unwrap());
+                }
+                lanes
+            })
+            .collect())
+    }
+
+    /// Absorb `seed` into a fresh sponge and squeeze `total_output` bytes from that one state
+    ///
+    /// The seed is padded with the variant's domain byte, then the shader emits a rate block
+    /// per permutation until `total_output` bytes are out, so a SHAKE hasher returns the
+    /// first `total_output` bytes of its XOF stream: a deterministic byte stream, e.g. for a
+    /// sponge-based RNG. Fixed-output variants keep squeezing past their digest size the same
+    /// way (deterministic, but not a standardized function). A fixed prefix set on the
+    /// hasher is absorbed before `seed`. The permutations depend on each other, so the
+    /// stream is produced by a single GPU invocation; hash many seeds together with
+    /// [`hash_batch_with_params`](Self::hash_batch_with_params) instead. Fails with
+    /// `InvalidOutputLength` if `total_output` is 0.
+    pub async fn absorb_and_squeeze(
+        &self,
+        seed: &[u8],
+        total_output: usize,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        let params =
+            BatchHashParams::new(self.variant, 1, seed.len()).with_output_length(total_output);
+        self.hash_batch_with_params(&[seed], &params).await
+    }
+
+    /// Hash a batch starting every input from `initial_state` instead of the all-zero state
+    ///
+    /// `initial_state` is the 200-byte Keccak state, lane `x + 5 * y` stored little-endian at
+    /// byte `8 * (x + 5 * y)`. Each input is absorbed into its own copy of it, then padded
+    /// with `params`' domain byte and squeezed to `params`' output length, so starting from
+    /// the state after absorbing whole rate blocks of a prefix gives the digest of
+    /// `prefix || input`. Inputs must all be `params.input_length` bytes; a cSHAKE header in
+    /// `params` is not absorbed. Independent of any fixed prefix set on the hasher.
+    pub async fn hash_batch_from_state(
+        &self,
+        initial_state: &[u8; STATE_BYTES],
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.len() != params.num_hashes {
+            return Err(GpuSha3Error::InvalidInputLength(inputs.len()));
+        }
+        if let Some(input) = inputs.iter().find(|input| input.len() != params.input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input.len()));
+        }
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.check_shader_support(params.variant)?;
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        if !self.fits_device(params.num_hashes, params.input_length, output_bytes) {
+            return Err(GpuSha3Error::InvalidInputLength(params.input_length));
+        }
+
+        let mut lanes = [0u64; 25];
+        for (lane, bytes) in lanes.iter_mut().zip(initial_state.chunks_exact(8)) {
+            *lane = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        let state_buffer = self.context.device().create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Caller State Buffer"),
+            contents: bytemuck::cast_slice(&state_to_gpu_words(&lanes)),
+            usage: BufferUsages::STORAGE,
+        });
+        let gpu_params = GpuHashParams::new(params, output_bytes);
+        self.hash_batch_with_dynamic_buffers(inputs, gpu_params, &state_buffer).await
+    }
+}
@@ -0,0 +1,71 @@
+//! Helper thread running the blocking `device.poll` waits of one hasher
+
+use std::time::Duration;
+
+use futures::channel::{mpsc, oneshot};
+use wgpu::*;
+
+use super::check_poll_result;
+use crate::error::GpuSha3Error;
+
+/// One wait: the submission to wait for (`None` for all submitted work), the timeout, and
+/// where to send the outcome
+type PollRequest =
+    (Option<SubmissionIndex>, Option<Duration>, oneshot::Sender<Result<PollStatus, PollError>>);
+
+/// Long-lived thread that waits on the device for the hasher's readbacks
+///
+/// Waits are run one after another on the same thread, so awaiting a readback never blocks
+/// the executor and never spawns a thread of its own. The thread exits once the poller is
+/// dropped.
+#[derive(Debug)]
+pub(super) struct DevicePoller {
+    requests: mpsc::UnboundedSender<PollRequest>,
+    /// Taken by the thread before each wait, so a test can hold readbacks back
+    #[cfg(test)]
+    gate: std::sync::Arc<std::sync::Mutex<()>>,
+}
+
+impl DevicePoller {
+    /// Start the polling thread for `device`
+    pub(super) fn new(device: &Device) -> Result<Self, GpuSha3Error> {
+        let device = device.clone();
+        let (requests, receiver) = mpsc::unbounded::<PollRequest>();
+        #[cfg(test)]
+        let gate = std::sync::Arc::<std::sync::Mutex<()>>::default();
+        #[cfg(test)]
+        let thread_gate = gate.clone();
+        std::thread::Builder::new().name("sha3-wgpu-poll".into()).spawn(move || {
+            for (submission_index, timeout, sender) in futures::executor::block_on_stream(receiver)
+            {
+                #[cfg(test)]
+                let _held = thread_gate.lock();
+                let _ = sender.send(device.poll(PollType::Wait { submission_index, timeout }));
+            }
+        })?;
+        Ok(Self {
+            requests,
+            #[cfg(test)]
+            gate,
+        })
+    }
+
+    /// Wait for `submission` (or all submitted work, if `None`) on the polling thread
+    pub(super) async fn wait(
+        &self,
+        submission: Option<SubmissionIndex>,
+        timeout: Option<Duration>,
+    ) -> Result<(), GpuSha3Error> {
+        let exited =
+            || GpuSha3Error::GpuOperationFailed("Device poll thread exited unexpectedly".into());
+        let (sender, receiver) = oneshot::channel();
+        self.requests.unbounded_send((submission, timeout, sender)).map_err(|_| exited())?;
+        check_poll_result(receiver.await.map_err(|_| exited())?)
+    }
+
+    /// Hold every wait back until the returned guard is dropped
+    #[cfg(test)]
+    pub(super) fn hold(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.gate.lock().unwrap()
+    }
+}
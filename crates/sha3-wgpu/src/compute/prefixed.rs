@@ -0,0 +1,88 @@
+//! Hashing a batch behind a prefix given per call, such as a salt
+
+use sha3_core::BatchHashParams;
+use wgpu::*;
+
+use super::{
+    host_copy, state_to_gpu_words, FixedPrefix, GpuHashParams, GpuSha3Hasher, HostCopy, STATE_BYTES,
+};
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Hash each `prefix || input` (inputs must all be the same length)
+    ///
+    /// Same as [`hash_batch_with_prefix_and_params`](Self::hash_batch_with_prefix_and_params)
+    /// with the hasher's default parameters.
+    pub async fn hash_batch_with_prefix(
+        &self,
+        prefix: &[u8],
+        inputs: &[&[u8]],
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        let input_length = inputs.first().map_or(0, |input| input.len());
+        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
+        self.hash_batch_with_prefix_and_params(prefix, inputs, &params).await
+    }
+
+    /// Hash each `prefix || input` with custom parameters
+    ///
+    /// Meant for a salt or domain tag that changes between batches; for one that never
+    /// changes, [`with_fixed_prefix`](Self::with_fixed_prefix) avoids the per-call work.
+    /// Complete rate blocks of `prefix` are absorbed once on the CPU into a starting state
+    /// for this batch, and only the remaining tail is prepended to each input. The shader
+    /// has no prefix buffer of its own, so a prefix shorter than one rate block (136 bytes
+    /// for SHA3-256), as most salts are, is still copied whole into every input and saves
+    /// nothing over concatenating it yourself. A fixed prefix set on the hasher still comes
+    /// first. Batches the shader can't take are hashed on the CPU, as by
+    /// [`hash_batch_with_params`](Self::hash_batch_with_params).
+    pub async fn hash_batch_with_prefix_and_params(
+        &self,
+        prefix: &[u8],
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if prefix.is_empty() || inputs.is_empty() {
+            return self.hash_batch_with_params(inputs, params).await;
+        }
+        params.total_output_bytes().map_err(GpuSha3Error::Core)?;
+        if inputs.len() != params.num_hashes {
+            return Err(GpuSha3Error::InvalidInputLength(params.num_hashes));
+        }
+        if !inputs.iter().all(|input| input.len() == params.input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(params.input_length));
+        }
+
+        let fixed = self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..]);
+        let full_prefix = host_copy([fixed, prefix].concat());
+        if !(self.shader_supports)(params.variant) || params.has_cshake_header() {
+            // The header must be absorbed before the prefix; leave that to the reference
+            return self.hash_on_cpu(&full_prefix, inputs, params);
+        }
+
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let absorbed = FixedPrefix::new(full_prefix, params.variant.rate_bytes());
+        let tail = absorbed.tail();
+        let mut framed_params = params.clone();
+        framed_params.input_length = tail.len() + params.input_length;
+        if !self.routes_to_gpu(params.num_hashes, framed_params.input_length, output_bytes) {
+            return self.hash_on_cpu(&absorbed.bytes, inputs, params);
+        }
+
+        let state_buffer = self.context.device().create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Prefix State Buffer"),
+            size: STATE_BYTES as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        self.context.queue().write_buffer(
+            &state_buffer,
+            0,
+            bytemuck::cast_slice(&state_to_gpu_words(&absorbed.state)),
+        );
+
+        let framed: Vec<HostCopy> =
+            inputs.iter().map(|input| host_copy([tail, input].concat())).collect();
+        let framed_refs: Vec<&[u8]> = framed.iter().map(|v| v.as_slice()).collect();
+        let gpu_params = GpuHashParams::new(&framed_params, output_bytes);
+        self.hash_batch_with_dynamic_buffers(&framed_refs, gpu_params, &state_buffer).await
+    }
+}
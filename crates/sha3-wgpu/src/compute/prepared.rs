@@ -0,0 +1,114 @@
+//! Repeated hashing of one fixed batch shape with all per-call setup done up front
+
+use futures::lock::Mutex;
+use sha3_core::{BatchHashParams, PreparedShape};
+use wgpu::*;
+
+use super::stats::BatchPath;
+use super::{
+    begin_readback, finish_readback, host_copy, wipe_upload, DynamicBatch, GpuHashParams,
+    GpuSha3Hasher, HostCopy,
+};
+use crate::error::GpuSha3Error;
+
+/// A hasher bound to one batch shape, created by [`GpuSha3Hasher::prepared`]
+///
+/// Owns a buffer set, uniform and bind group built for the shape, so each
+/// [`hash`](Self::hash) skips parameter construction, validation of the shape and
+/// allocation: it uploads the inputs, dispatches and reads back. Calls on the same
+/// prepared hasher run one at a time, as they share its buffers.
+pub struct PreparedHasher<'a> {
+    hasher: &'a GpuSha3Hasher,
+    shape: BatchHashParams,
+    /// Unabsorbed tail of the hasher's fixed prefix, prepended to each input
+    tail: HostCopy,
+    /// Absorb schedule of the tail plus one input, baked into the uniform
+    schedule: PreparedShape,
+    batch: Mutex<DynamicBatch>,
+}
+
+impl<'a> PreparedHasher<'a> {
+    pub(super) fn new(
+        hasher: &'a GpuSha3Hasher,
+        shape: &BatchHashParams,
+    ) -> Result<Self, GpuSha3Error> {
+        hasher.check_shader_support(shape.variant)?;
+        let tail = match &hasher.prefix {
+            Some(_) if shape.variant.rate_bytes() != hasher.variant.rate_bytes() => {
+                // The pre-absorbed state is only valid for the rate it was computed with
+                return Err(GpuSha3Error::UnsupportedVariant(shape.variant));
+            }
+            Some(prefix) => host_copy(prefix.tail().to_vec()),
+            None => host_copy(Vec::new()),
+        };
+        let input_length = tail.len() + shape.input_length;
+        let output_bytes = shape.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        if !hasher.fits_device(shape.num_hashes, input_length, output_bytes) {
+            return Err(GpuSha3Error::InvalidInputLength(shape.input_length));
+        }
+
+        let schedule = PreparedShape::new(shape.variant, input_length);
+        let gpu_params = GpuHashParams::new(shape, output_bytes).with_shape(schedule);
+        let batch = hasher.create_dynamic_batch(&[], gpu_params, &hasher.initial_state_buffer);
+
+        Ok(Self { hasher, shape: shape.clone(), tail, schedule, batch: Mutex::new(batch) })
+    }
+
+    /// The batch shape this hasher was prepared for
+    pub fn shape(&self) -> &BatchHashParams {
+        &self.shape
+    }
+
+    /// Block count and padding position the shader uses for every input
+    ///
+    /// Covers the prepended fixed-prefix tail, if any, along with the input itself.
+    pub fn schedule(&self) -> PreparedShape {
+        self.schedule
+    }
+
+    /// Hash exactly `shape.num_hashes` inputs of `shape.input_length` bytes each
+    ///
+    /// Returns a flattened vector of all output hashes, as from `hash_batch_with_params`.
+    pub async fn hash(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.len() != self.shape.num_hashes {
+            return Err(GpuSha3Error::InvalidInputLength(inputs.len()));
+        }
+        if let Some(input) = inputs.iter().find(|input| input.len() != self.shape.input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input.len()));
+        }
+
+        let batch = self.batch.lock().await;
+        let mut input_data = Vec::with_capacity(batch.input_buffer.size() as usize);
+        for input in inputs {
+            input_data.extend_from_slice(&self.tail);
+            input_data.extend_from_slice(input);
+        }
+        input_data.resize(batch.input_buffer.size() as usize, 0);
+
+        let hasher = self.hasher;
+        hasher.context.queue().write_buffer(&batch.input_buffer, 0, &input_data);
+        wipe_upload(&mut input_data);
+        let mut encoder =
+            hasher.context.device().create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("SHA-3 Prepared Command Encoder"),
+            });
+        hasher.encode_dynamic_batch(&mut encoder, &batch);
+        let input_length = self.tail.len() + self.shape.input_length;
+        hasher.stats.record(BatchPath::Persistent, inputs.len(), inputs.len() * input_length);
+        hasher.context.queue().submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&batch.staging_buffer);
+        hasher.wait_for_readback(&[&batch.staging_buffer]).await?;
+        finish_readback(&batch.staging_buffer, receiver, batch.total_output_bytes).await
+    }
+}
+
+impl std::fmt::Debug for PreparedHasher<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PreparedHasher")
+            .field("variant", &self.shape.variant)
+            .field("num_hashes", &self.shape.num_hashes)
+            .field("input_length", &self.shape.input_length)
+            .finish()
+    }
+}
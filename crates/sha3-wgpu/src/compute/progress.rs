@@ -0,0 +1,57 @@
+//! Large batches split into submissions, with progress reported between them
+
+use super::GpuSha3Hasher;
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Hash a batch like [`hash_batch`](Self::hash_batch), calling `callback(completed, total)`
+    /// after each GPU submission
+    ///
+    /// The batch is split into chunks of as many inputs as the persistent buffers currently
+    /// hold, or, without buffers of that shape, as many as the device can bind at once; a
+    /// batch within that size is a single submission and reports once. Chunks run one after
+    /// another, so buffers are never grown for the batch. `completed` counts the inputs
+    /// hashed so far and reaches `total` (`inputs.len()`) on success; nothing is reported
+    /// for an empty batch or after a failed chunk. The callback runs on the task awaiting
+    /// this future, between submissions, so it can update UI state but delays the next
+    /// chunk while it runs.
+    pub async fn hash_batch_with_progress(
+        &self,
+        inputs: &[&[u8]],
+        mut callback: impl FnMut(usize, usize),
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input_length = inputs[0].len();
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+
+        let chunk_size = self.progress_chunk_size(input_length);
+        let mut digests = Vec::with_capacity(inputs.len() * self.variant.output_bytes());
+        let mut completed = 0;
+        for chunk in inputs.chunks(chunk_size) {
+            digests.extend(self.hash_batch(chunk).await?);
+            completed += chunk.len();
+            callback(completed, inputs.len());
+        }
+        Ok(digests)
+    }
+
+    /// Inputs of `input_length` bytes hashed per submission by `hash_batch_with_progress`
+    fn progress_chunk_size(&self, input_length: usize) -> usize {
+        let tail = self.prefix.as_ref().map_or(0, |prefix| prefix.tail().len());
+        let input_length = tail + input_length;
+        let output_bytes = self.variant.output_bytes();
+        match self.current_capacity() {
+            Some((batch, input, output)) if input_length <= input && output_bytes <= output => {
+                batch.max(1)
+            }
+            _ => {
+                let limit = self.context.limits().max_storage_buffer_binding_size as usize;
+                (limit / input_length.max(output_bytes).max(1)).max(1)
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Recording the SHA-3 pass into a caller's command encoder
+
+use sha3_core::{BatchHashParams, Sha3Error};
+use wgpu::util::DeviceExt;
+use wgpu::*;
+
+use super::{GpuHashParams, GpuSha3Hasher};
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Layout of the bind group the SHA-3 pipeline runs with
+    ///
+    /// | Binding | Resource                                             | Usage     |
+    /// |---------|------------------------------------------------------|-----------|
+    /// | 0       | inputs, back to back from offset 0                   | `STORAGE` |
+    /// | 1       | digests, back to back from offset 0                  | `STORAGE` |
+    /// | 2       | [`GpuHashParams`] for the batch                      | `UNIFORM` |
+    /// | 3       | [`initial_state_buffer`](Self::initial_state_buffer) | `STORAGE` |
+    ///
+    /// The output buffer must hold the batch's digests rounded up to a multiple of 16
+    /// bytes. A bind group built against this layout can be passed to
+    /// [`record_dispatch`](Self::record_dispatch);
+    /// [`create_dispatch_bind_group`](Self::create_dispatch_bind_group) builds one from an
+    /// input and output buffer.
+    pub fn bind_group_layout(&self) -> &BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    /// Sponge state every hash starts from, bound at binding 3
+    ///
+    /// All zeros, unless a fixed prefix was absorbed into it (see
+    /// [`with_fixed_prefix`](Self::with_fixed_prefix)).
+    pub fn initial_state_buffer(&self) -> &Buffer {
+        &self.initial_state_buffer
+    }
+
+    /// Build a bind group hashing `params.num_hashes` inputs from `input` into `output`
+    ///
+    /// The group follows [`bind_group_layout`](Self::bind_group_layout). `input` must hold
+    /// `params.num_hashes * params.input_length` bytes and `output` the digests rounded up
+    /// to a multiple of 16 bytes; the parameter buffer is created here and kept alive by the
+    /// returned group. Pass the group to [`record_dispatch`](Self::record_dispatch). As with
+    /// [`hash_batch_from_buffer`](Self::hash_batch_from_buffer), a fixed prefix is
+    /// supported only when it is fully absorbed.
+    pub fn create_dispatch_bind_group(
+        &self,
+        input: &Buffer,
+        output: &Buffer,
+        params: &BatchHashParams,
+    ) -> Result<BindGroup, GpuSha3Error> {
+        self.check_shader_support(params.variant)?;
+        if let Some(prefix) = &self.prefix {
+            if params.variant.rate_bytes() != self.variant.rate_bytes() {
+                // The pre-absorbed state is only valid for the rate it was computed with
+                return Err(GpuSha3Error::UnsupportedVariant(params.variant));
+            }
+            if !prefix.tail().is_empty() {
+                return Err(GpuSha3Error::GpuOperationFailed(format!(
+                    "Fixed prefix leaves a {}-byte tail that can't be prepended to buffer inputs",
+                    prefix.tail().len()
+                )));
+            }
+        }
+        let input_bytes = params
+            .num_hashes
+            .checked_mul(params.input_length)
+            .ok_or(GpuSha3Error::InvalidInputLength(params.input_length))?;
+        if input_bytes as u64 > input.size() {
+            return Err(GpuSha3Error::InvalidInputLength(input_bytes));
+        }
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let total_output_bytes = params.total_output_bytes().map_err(GpuSha3Error::Core)?;
+        let output_buffer_size = (total_output_bytes + 15) / 16 * 16;
+        if output_buffer_size as u64 > output.size() {
+            return Err(GpuSha3Error::Core(Sha3Error::InvalidOutputLength(output_buffer_size)));
+        }
+
+        let device = self.context.device();
+        let gpu_params = GpuHashParams::new(params, output_bytes);
+        let uniform_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Recorded Dispatch Uniform Buffer"),
+            contents: gpu_params.as_bytes(),
+            usage: BufferUsages::UNIFORM,
+        });
+        Ok(device.create_bind_group(&BindGroupDescriptor {
+            label: Some("SHA-3 Recorded Dispatch Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                BindGroupEntry { binding: 0, resource: input.as_entire_binding() },
+                BindGroupEntry { binding: 1, resource: output.as_entire_binding() },
+                BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: self.initial_state_buffer.as_entire_binding(),
+                },
+            ],
+        }))
+    }
+
+    /// Record the SHA-3 compute pass for `num_hashes` inputs into a caller's `encoder`
+    ///
+    /// Lets the hash run in the same submission as the caller's own passes, e.g. one that
+    /// produces the inputs and one that consumes the digests. Nothing is submitted or read
+    /// back; the digests are in the bound output buffer once the caller's submission
+    /// completes. `bind_group` must match [`bind_group_layout`](Self::bind_group_layout),
+    /// e.g. as built by [`create_dispatch_bind_group`](Self::create_dispatch_bind_group),
+    /// and `num_hashes` must not exceed the batch it describes.
+    pub fn record_dispatch(
+        &self,
+        encoder: &mut CommandEncoder,
+        bind_group: &BindGroup,
+        num_hashes: usize,
+    ) {
+        if num_hashes == 0 {
+            return;
+        }
+        self.encode_dispatch(encoder, bind_group, num_hashes);
+    }
+}
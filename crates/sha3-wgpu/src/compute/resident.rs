@@ -0,0 +1,161 @@
+//! Hashing inputs that already live in a GPU buffer, with per-buffer bind groups cached
+
+use std::sync::atomic::Ordering;
+
+use sha3_core::{BatchHashParams, Sha3Error};
+use wgpu::*;
+
+use super::stats::BatchPath;
+use super::{begin_readback, finish_readback, GpuHashParams, GpuSha3Hasher};
+use crate::error::GpuSha3Error;
+
+/// Bind group and output buffers built for one caller-owned input buffer
+#[derive(Debug)]
+pub(super) struct ResidentBinding {
+    bind_group: BindGroup,
+    uniform_buffer: Buffer,
+    output_buffer: Buffer,
+    staging_buffer: Buffer,
+}
+
+impl GpuSha3Hasher {
+    /// Hash a batch whose inputs are already in a GPU buffer
+    ///
+    /// `buffer` must have `BufferUsages::STORAGE` and hold `params.num_hashes` inputs of
+    /// `params.input_length` bytes each, back to back from offset 0. Nothing is uploaded;
+    /// only the digests are read back.
+    ///
+    /// The bind group (and the output buffers behind it) is built on the first call for a
+    /// buffer and cached, keyed by the buffer's identity, so hashing the same resident
+    /// dataset again, e.g. under different params, only rewrites the uniform. Changing the
+    /// buffer's contents needs no invalidation; call
+    /// [`invalidate_bind_group_cache`](Self::invalidate_bind_group_cache) before destroying
+    /// a cached buffer, or to release buffers that are no longer hashed (cached bind groups
+    /// keep them alive). Calls run one at a time, as they share the cache.
+    ///
+    /// A fixed prefix is supported only when it is fully absorbed (a multiple of the rate),
+    /// as the unabsorbed tail can't be prepended to GPU-resident inputs.
+    pub async fn hash_batch_from_buffer(
+        &self,
+        buffer: &Buffer,
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if params.num_hashes == 0 {
+            return Ok(Vec::new());
+        }
+        self.check_shader_support(params.variant)?;
+        if let Some(prefix) = &self.prefix {
+            if params.variant.rate_bytes() != self.variant.rate_bytes() {
+                // The pre-absorbed state is only valid for the rate it was computed with
+                return Err(GpuSha3Error::UnsupportedVariant(params.variant));
+            }
+            if !prefix.tail().is_empty() {
+                return Err(GpuSha3Error::GpuOperationFailed(format!(
+                    "Fixed prefix leaves a {}-byte tail that can't be prepended to buffer inputs",
+                    prefix.tail().len()
+                )));
+            }
+        }
+        let input_bytes = params
+            .num_hashes
+            .checked_mul(params.input_length)
+            .ok_or(GpuSha3Error::InvalidInputLength(params.input_length))?;
+        if input_bytes as u64 > buffer.size() {
+            return Err(GpuSha3Error::InvalidInputLength(input_bytes));
+        }
+
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let total_output_bytes = params.total_output_bytes().map_err(GpuSha3Error::Core)?;
+        if !self.fits_device(params.num_hashes, 0, output_bytes) {
+            return Err(GpuSha3Error::Core(Sha3Error::InvalidOutputLength(output_bytes)));
+        }
+        let output_buffer_size = ((total_output_bytes + 15) / 16 * 16) as u64;
+        let gpu_params = GpuHashParams::new(params, output_bytes);
+
+        let mut cache = self.bind_group_cache.lock().await;
+        if cache
+            .get(buffer)
+            .map_or(true, |binding| binding.output_buffer.size() < output_buffer_size)
+        {
+            // First use of this buffer, or its outputs don't fit the cached allocation
+            let binding = self.create_resident_binding(buffer, output_buffer_size);
+            cache.insert(buffer.clone(), binding);
+        }
+        let binding = &cache[buffer];
+
+        let queue = self.context.queue();
+        queue.write_buffer(&binding.uniform_buffer, 0, bytemuck::cast_slice(&[gpu_params]));
+        let mut encoder = self.context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Resident Input Encoder"),
+        });
+        self.encode_dispatch(&mut encoder, &binding.bind_group, params.num_hashes);
+        encoder.copy_buffer_to_buffer(
+            &binding.output_buffer,
+            0,
+            &binding.staging_buffer,
+            0,
+            output_buffer_size,
+        );
+        self.stats.record(BatchPath::Persistent, params.num_hashes, input_bytes);
+        queue.submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&binding.staging_buffer);
+        self.wait_for_readback(&[&binding.staging_buffer]).await?;
+        finish_readback(&binding.staging_buffer, receiver, total_output_bytes).await
+    }
+
+    /// Drop every cached bind group built by
+    /// [`hash_batch_from_buffer`](Self::hash_batch_from_buffer)
+    ///
+    /// Releases the cache's references to the input buffers; the next call for any buffer
+    /// rebuilds its bind group.
+    pub async fn invalidate_bind_group_cache(&self) {
+        self.bind_group_cache.lock().await.clear();
+    }
+
+    /// Number of bind groups `hash_batch_from_buffer` has built (cache misses)
+    #[cfg(test)]
+    pub(crate) fn bind_group_builds(&self) -> usize {
+        self.bind_group_builds.load(Ordering::Relaxed)
+    }
+
+    /// Build the bind group binding `buffer` as input, with outputs of `output_size` bytes
+    fn create_resident_binding(&self, buffer: &Buffer, output_size: u64) -> ResidentBinding {
+        let device = self.context.device();
+        self.bind_group_builds.fetch_add(1, Ordering::Relaxed);
+
+        let uniform_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Resident Uniform Buffer"),
+            size: std::mem::size_of::<GpuHashParams>() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let output_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Resident Output Buffer"),
+            size: output_size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Resident Staging Buffer"),
+            size: output_size,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("SHA-3 Resident Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() },
+                BindGroupEntry { binding: 1, resource: output_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: self.initial_state_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        ResidentBinding { bind_group, uniform_buffer, output_buffer, staging_buffer }
+    }
+}
@@ -0,0 +1,241 @@
+//! Digests kept on the GPU, so one batch's outputs can feed the next without a readback
+
+use sha3_core::BatchHashParams;
+use wgpu::util::DeviceExt;
+use wgpu::*;
+
+use super::stats::BatchPath;
+use super::{begin_readback, finish_readback, host_copy, GpuHashParams, GpuSha3Hasher};
+use crate::error::GpuSha3Error;
+
+/// A batch of digests left in a GPU buffer by
+/// [`GpuSha3Hasher::hash_batch_retained`] or [`GpuSha3Hasher::hash_retained`]
+///
+/// The digests sit back to back from offset 0. Pass the handle to `hash_retained` to hash
+/// them again on the GPU, e.g. for the next layer of a Merkle tree, or to
+/// [`GpuSha3Hasher::read`] to download them. Cloning shares the buffer.
+#[derive(Debug, Clone)]
+pub struct GpuBufferHandle {
+    buffer: Buffer,
+    num_hashes: usize,
+    digest_len: usize,
+}
+
+impl GpuBufferHandle {
+    /// Buffer holding the digests (`STORAGE | COPY_SRC`, padded to a multiple of 16 bytes)
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Number of digests in the buffer
+    pub fn num_hashes(&self) -> usize {
+        self.num_hashes
+    }
+
+    /// Length of each digest in bytes
+    pub fn digest_len(&self) -> usize {
+        self.digest_len
+    }
+
+    /// Total digest bytes, excluding the buffer's padding
+    pub fn len(&self) -> usize {
+        self.num_hashes * self.digest_len
+    }
+
+    /// Whether the handle holds no digests
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl GpuSha3Hasher {
+    /// Hash a batch and keep the digests on the GPU
+    ///
+    /// Like [`hash_batch`](Self::hash_batch), but nothing is read back: the returned handle
+    /// refers to the output buffer, ready for [`hash_retained`](Self::hash_retained) or
+    /// [`read`](Self::read). The work is submitted but not waited on; later submissions
+    /// see its results. Inputs must all be the same length, and the batch always runs on
+    /// the GPU, so it must fit the device's buffer limits.
+    pub fn hash_batch_retained(&self, inputs: &[&[u8]]) -> Result<GpuBufferHandle, GpuSha3Error> {
+        let input_length = inputs.first().map_or(0, |input| input.len());
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+        let framed = self.frame_inputs(inputs);
+        let framed_refs: Vec<&[u8]>;
+        let inputs = match &framed {
+            Some(framed) => {
+                framed_refs = framed.iter().map(|v| v.as_slice()).collect();
+                &framed_refs[..]
+            }
+            None => inputs,
+        };
+
+        let params = BatchHashParams::new(
+            self.variant,
+            inputs.len(),
+            inputs.first().map_or(0, |input| input.len()),
+        );
+        // Align to 16 bytes, with a minimum size for an empty batch
+        let input_len: usize = inputs.iter().map(|input| input.len()).sum();
+        let padded_len = (input_len.max(1) + 15) / 16 * 16;
+        let mut input_data = host_copy(Vec::with_capacity(padded_len));
+        for input in inputs {
+            input_data.extend_from_slice(input);
+        }
+        input_data.resize(padded_len, 0);
+        let input_buffer = self.context.device().create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Retained Input Buffer"),
+            contents: &input_data,
+            usage: BufferUsages::STORAGE,
+        });
+        self.dispatch_retained(&input_buffer, &params)
+    }
+
+    /// Hash the digests in `input` again, `input_length` bytes per hash, keeping the
+    /// results on the GPU
+    ///
+    /// The handle's bytes are split into consecutive `input_length`-byte inputs, e.g. twice
+    /// the digest length to hash sibling pairs into a Merkle tree's next layer. They never
+    /// leave the GPU. Fails with `InvalidInputLength` unless `input_length` is non-zero and
+    /// divides the handle's length. As with
+    /// [`hash_batch_from_buffer`](Self::hash_batch_from_buffer), a fixed prefix is
+    /// supported only when it is fully absorbed.
+    pub fn hash_retained(
+        &self,
+        input: &GpuBufferHandle,
+        input_length: usize,
+    ) -> Result<GpuBufferHandle, GpuSha3Error> {
+        if input_length == 0 || input.len() % input_length != 0 {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+        if let Some(prefix) = &self.prefix {
+            if !prefix.tail().is_empty() {
+                return Err(GpuSha3Error::GpuOperationFailed(format!(
+                    "Fixed prefix leaves a {}-byte tail that can't be prepended to buffer inputs",
+                    prefix.tail().len()
+                )));
+            }
+        }
+        let params = BatchHashParams::new(self.variant, input.len() / input_length, input_length);
+        self.dispatch_retained(&input.buffer, &params)
+    }
+
+    /// Download the digests held by `handle`
+    ///
+    /// Waits for the work that produced them; returns `handle.len()` bytes.
+    pub async fn read(&self, handle: &GpuBufferHandle) -> Result<Vec<u8>, GpuSha3Error> {
+        if handle.is_empty() {
+            return Ok(Vec::new());
+        }
+        let size = handle.buffer.size();
+        let staging_buffer = self.context.device().create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Retained Staging Buffer"),
+            size,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Retained Readback Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(&handle.buffer, 0, &staging_buffer, 0, size);
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&staging_buffer);
+        self.wait_for_readback(&[&staging_buffer]).await?;
+        finish_readback(&staging_buffer, receiver, handle.len()).await
+    }
+
+    /// Upload `digests`, `digest_len` bytes each, into a new handle
+    pub(super) fn retain_digests(&self, digests: &[u8], digest_len: usize) -> GpuBufferHandle {
+        // Align to 16 bytes, with a minimum size for an empty batch
+        let mut contents = digests.to_vec();
+        contents.resize((contents.len().max(1) + 15) / 16 * 16, 0);
+        let buffer = self.context.device().create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Retained Output Buffer"),
+            contents: &contents,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+        });
+        GpuBufferHandle { buffer, num_hashes: digests.len() / digest_len, digest_len }
+    }
+
+    /// Copy `handle` with its last digest repeated at the end, on the GPU
+    ///
+    /// Digest lengths are multiples of 4 bytes, as buffer copies require.
+    pub(super) fn duplicate_last_digest(&self, handle: &GpuBufferHandle) -> GpuBufferHandle {
+        let len = handle.len() as u64;
+        let digest_len = handle.digest_len as u64;
+        let buffer = self.context.device().create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Retained Output Buffer"),
+            size: (len + digest_len + 15) / 16 * 16,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self.context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Retained Copy Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(&handle.buffer, 0, &buffer, 0, len);
+        encoder.copy_buffer_to_buffer(&handle.buffer, len - digest_len, &buffer, len, digest_len);
+        self.context.queue().submit(Some(encoder.finish()));
+        GpuBufferHandle { buffer, num_hashes: handle.num_hashes + 1, digest_len: handle.digest_len }
+    }
+
+    /// Submit the SHA-3 pass over `params.num_hashes` inputs in `input_buffer` into a new
+    /// output buffer
+    fn dispatch_retained(
+        &self,
+        input_buffer: &Buffer,
+        params: &BatchHashParams,
+    ) -> Result<GpuBufferHandle, GpuSha3Error> {
+        self.check_shader_support(params.variant)?;
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        if !self.fits_device(params.num_hashes, params.input_length, output_bytes) {
+            return Err(GpuSha3Error::InvalidInputLength(params.input_length));
+        }
+
+        let device = self.context.device();
+        let total_output_bytes = params.num_hashes * output_bytes;
+        let output_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Retained Output Buffer"),
+            size: ((total_output_bytes.max(1) + 15) / 16 * 16) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let handle = GpuBufferHandle {
+            buffer: output_buffer,
+            num_hashes: params.num_hashes,
+            digest_len: output_bytes,
+        };
+        if params.num_hashes == 0 {
+            return Ok(handle);
+        }
+
+        let uniform_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+            label: Some("SHA-3 Retained Uniform Buffer"),
+            contents: GpuHashParams::new(params, output_bytes).as_bytes(),
+            usage: BufferUsages::UNIFORM,
+        });
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("SHA-3 Retained Bind Group"),
+            layout: &self.bind_group_layout,
+            entries: &[
+                BindGroupEntry { binding: 0, resource: input_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 1, resource: handle.buffer.as_entire_binding() },
+                BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                BindGroupEntry {
+                    binding: 3,
+                    resource: self.initial_state_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Retained Command Encoder"),
+        });
+        self.encode_dispatch(&mut encoder, &bind_group, params.num_hashes);
+        let input_bytes = params.num_hashes * params.input_length;
+        self.stats.record(BatchPath::Dynamic, params.num_hashes, input_bytes);
+        self.context.queue().submit(Some(encoder.finish()));
+        Ok(handle)
+    }
+}
@@ -0,0 +1,121 @@
+//! Reading back only the digests a caller asks for
+
+use sha3_core::BatchHashParams;
+use wgpu::*;
+
+use super::stats::BatchPath;
+use super::{begin_readback, finish_readback, GpuHashParams, GpuSha3Hasher};
+use crate::error::GpuSha3Error;
+
+impl GpuSha3Hasher {
+    /// Hash a batch and return only the digests at `indices`, in the order given
+    ///
+    /// Every input is hashed on the GPU, but only the requested digests are copied out of
+    /// the output buffer, so readback scales with `indices` rather than the batch. Indices
+    /// may repeat. Inputs must all be the same length, as for
+    /// [`hash_batch`](Self::hash_batch); an index past the end of the batch fails with
+    /// `InvalidInputLength` carrying that index.
+    pub async fn hash_batch_select(
+        &self,
+        inputs: &[&[u8]],
+        indices: &[usize],
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        if let Some(&index) = indices.iter().find(|&&index| index >= inputs.len()) {
+            return Err(GpuSha3Error::InvalidInputLength(index));
+        }
+        if indices.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input_length = inputs[0].len();
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+
+        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let framed = self.frame_inputs(inputs);
+        let framed_refs: Vec<&[u8]>;
+        let framed_inputs = match &framed {
+            Some(framed) => {
+                framed_refs = framed.iter().map(|v| v.as_slice()).collect();
+                &framed_refs[..]
+            }
+            None => inputs,
+        };
+
+        if !self.routes_to_gpu(inputs.len(), framed_inputs[0].len(), output_bytes)
+            || !(self.shader_supports)(self.variant)
+        {
+            let prefix = self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..]);
+            let digests = self.hash_on_cpu(prefix, inputs, &params)?;
+            return Ok(indices
+                .iter()
+                .map(|&i| digests[i * output_bytes..(i + 1) * output_bytes].to_vec())
+                .collect());
+        }
+        self.select_on_gpu(framed_inputs, indices, output_bytes).await
+    }
+
+    /// Hash `inputs` and copy the word-aligned span of each selected digest into one
+    /// compact staging buffer
+    async fn select_on_gpu(
+        &self,
+        inputs: &[&[u8]],
+        indices: &[usize],
+        output_bytes: usize,
+    ) -> Result<Vec<Vec<u8>>, GpuSha3Error> {
+        let device = self.context.device();
+        let params = BatchHashParams::new(self.variant, inputs.len(), inputs[0].len());
+        let gpu_params = GpuHashParams::new(&params, output_bytes);
+        let batch = self.create_dynamic_batch(inputs, gpu_params, &self.initial_state_buffer);
+
+        // Buffer copies must start and end on 4-byte boundaries
+        let spans: Vec<(usize, usize)> = indices
+            .iter()
+            .map(|&i| {
+                let start = i * output_bytes;
+                (start / 4 * 4, (start + output_bytes + 3) / 4 * 4)
+            })
+            .collect();
+        let staging_size: usize = spans.iter().map(|(start, end)| end - start).sum();
+        let staging_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Select Staging Buffer"),
+            size: staging_size as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Select Command Encoder"),
+        });
+        self.encode_dispatch(&mut encoder, &batch.bind_group, inputs.len());
+        let mut staging_offset = 0;
+        for &(start, end) in &spans {
+            encoder.copy_buffer_to_buffer(
+                &batch.output_buffer,
+                start as u64,
+                &staging_buffer,
+                staging_offset as u64,
+                (end - start) as u64,
+            );
+            staging_offset += end - start;
+        }
+        self.stats.record(BatchPath::Dynamic, inputs.len(), inputs.len() * inputs[0].len());
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&staging_buffer);
+        self.wait_for_readback(&[&staging_buffer]).await?;
+        let staged = finish_readback(&staging_buffer, receiver, staging_size).await?;
+
+        let mut staging_offset = 0;
+        Ok(indices
+            .iter()
+            .zip(&spans)
+            .map(|(&i, &(start, end))| {
+                let offset = staging_offset + i * output_bytes - start;
+                staging_offset += end - start;
+                staged[offset..offset + output_bytes].to_vec()
+            })
+            .collect())
+    }
+}
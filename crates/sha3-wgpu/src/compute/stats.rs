@@ -0,0 +1,124 @@
+//! Running counters of a hasher's activity, for telemetry
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Which path a batch was hashed through
+#[derive(Debug, Clone, Copy)]
+pub(super) enum BatchPath {
+    /// Reused buffers: the persistent set, a prepared hasher or the single-hash set
+    Persistent,
+    /// Buffers allocated for the call
+    Dynamic,
+    /// Reference implementation on the CPU
+    Cpu,
+}
+
+/// Counters updated by every batch; atomics so `&self` hashing can record concurrently
+#[derive(Debug, Default)]
+pub(super) struct SessionCounters {
+    persistent_batches: AtomicU64,
+    dynamic_batches: AtomicU64,
+    cpu_batches: AtomicU64,
+    hashes: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl SessionCounters {
+    /// Count one batch of `num_hashes` hashes over `bytes` input bytes
+    pub(super) fn record(&self, path: BatchPath, num_hashes: usize, bytes: usize) {
+        let batches = match path {
+            BatchPath::Persistent => &self.persistent_batches,
+            BatchPath::Dynamic => &self.dynamic_batches,
+            BatchPath::Cpu => &self.cpu_batches,
+        };
+        batches.fetch_add(1, Ordering::Relaxed);
+        self.hashes.fetch_add(num_hashes as u64, Ordering::Relaxed);
+        self.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(super) fn snapshot(&self) -> SessionStats {
+        let persistent_batches = self.persistent_batches.load(Ordering::Relaxed);
+        let dynamic_batches = self.dynamic_batches.load(Ordering::Relaxed);
+        let cpu_batches = self.cpu_batches.load(Ordering::Relaxed);
+        let batches = persistent_batches + dynamic_batches + cpu_batches;
+        let hashes = self.hashes.load(Ordering::Relaxed);
+        SessionStats {
+            batches,
+            hashes,
+            bytes: self.bytes.load(Ordering::Relaxed),
+            persistent_batches,
+            dynamic_batches,
+            cpu_batches,
+            average_batch_size: if batches == 0 { 0.0 } else { hashes as f64 / batches as f64 },
+        }
+    }
+
+    pub(super) fn reset(&self) {
+        for counter in [
+            &self.persistent_batches,
+            &self.dynamic_batches,
+            &self.cpu_batches,
+            &self.hashes,
+            &self.bytes,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Summary of the batches a hasher has run since creation or the last `reset_stats`
+///
+/// Every GPU dispatch or CPU fallback counts as one batch: grouped hashing counts each
+/// length group, `hash_commit` each of its two passes. Serializable with the `serde`
+/// feature, e.g. for JSON dashboards.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionStats {
+    /// Total batches, over all paths
+    pub batches: u64,
+    /// Total hashes computed
+    pub hashes: u64,
+    /// Total input bytes hashed (including any prepended fixed-prefix tail)
+    pub bytes: u64,
+    /// Batches run through reused buffers
+    pub persistent_batches: u64,
+    /// Batches run through buffers allocated for the call
+    pub dynamic_batches: u64,
+    /// Batches routed to the CPU
+    pub cpu_batches: u64,
+    /// Mean hashes per batch (0 when no batch ran)
+    pub average_batch_size: f64,
+}
+
+/// Byte sizes of a hasher's persistent buffers, for capacity planning
+///
+/// Reported by [`buffer_stats`](super::GpuSha3Hasher::buffer_stats); all zero for a hasher
+/// without persistent buffers. Covers the batch buffer set only, not buffers allocated per
+/// call. Serializable with the `serde` feature.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BufferStats {
+    /// Size of the input buffer
+    pub input_bytes: u64,
+    /// Size of the output buffer
+    pub output_bytes: u64,
+    /// Combined size of the staging ring's buffers
+    pub staging_bytes: u64,
+    /// Size of the parameter uniform buffer
+    pub uniform_bytes: u64,
+    /// Number of buffers in the staging ring
+    pub staging_buffers: usize,
+    /// Hashes per batch the buffers hold
+    pub max_batch_size: usize,
+    /// Input bytes per hash the buffers hold
+    pub max_input_length: usize,
+    /// Output bytes per hash the buffers hold
+    pub max_output_bytes: usize,
+}
+
+impl BufferStats {
+    /// Total bytes across all the buffers
+    pub fn total_bytes(&self) -> u64 {
+        self.input_bytes + self.output_bytes + self.staging_bytes + self.uniform_bytes
+    }
+}
@@ -0,0 +1,354 @@
+//! Incremental (streaming) SHA-3 hashing with the sponge state kept on the GPU
+
+use sha3_core::BatchHashParams;
+use wgpu::*;
+
+use super::{
+    clear_host_copy, encode_input_wipe, extend_host_copy, host_copy, state_to_gpu_words,
+    GpuHashParams, GpuSha3Hasher, HostCopy, INPUT_LAYOUT_AOS, STATE_BYTES,
+};
+use crate::error::GpuSha3Error;
+
+/// Maximum number of rate blocks uploaded and absorbed per dispatch
+pub(super) const BLOCKS_PER_DISPATCH: usize = 64;
+
+/// Incremental SHA-3 hash of one message fed in pieces
+///
+/// Complete rate blocks are uploaded and absorbed into a GPU-resident sponge state as soon
+/// as `update` has accumulated them; only the trailing partial block (shorter than the
+/// rate) is buffered on the host, so host memory stays bounded by one rate block
+/// regardless of the total message size. `finalize` pads the remainder and squeezes the
+/// digest.
+///
+/// A stream is a single sequential computation: `update` takes `&mut self`, so concurrent
+/// updates of the same stream aren't supported. Independent streams from the same hasher
+/// may be used side by side, and [`fork`](Self::fork) branches off a copy of the running
+/// state.
+pub struct GpuSha3Stream<'a> {
+    hasher: &'a GpuSha3Hasher,
+    /// Sponge state after absorbing every complete block so far (storage, read-write)
+    state_buffer: Buffer,
+    /// Upload area for up to `BLOCKS_PER_DISPATCH` rate blocks
+    block_buffer: Buffer,
+    /// Absorb parameters (input_length = bytes of whole blocks in `block_buffer`)
+    uniform_buffer: Buffer,
+    bind_group: BindGroup,
+    /// Bytes received that don't fill a complete rate block yet
+    pending: HostCopy,
+    /// Requested output length (required for SHAKE variants)
+    output_length: Option<usize>,
+    /// Total bytes fed through `update`
+    total_bytes: u64,
+    /// Fork the running state every this many bytes (see `with_snapshot_interval`)
+    snapshot_interval: Option<u64>,
+    /// Forks taken at snapshot offsets, not yet finalized
+    snapshots: Vec<(u64, GpuSha3Stream<'a>)>,
+}
+
+impl<'a> GpuSha3Stream<'a> {
+    /// Create a stream starting from the hasher's initial state (including any fixed prefix)
+    pub(super) fn new(hasher: &'a GpuSha3Hasher) -> Self {
+        let (initial_state, pending) = match &hasher.prefix {
+            Some(prefix) => (prefix.state, host_copy(prefix.tail().to_vec())),
+            None => ([0u64; 25], host_copy(Vec::new())),
+        };
+
+        let stream = Self::allocate(hasher, pending);
+        hasher.context.queue().write_buffer(
+            &stream.state_buffer,
+            0,
+            bytemuck::cast_slice(&state_to_gpu_words(&initial_state)),
+        );
+        stream
+    }
+
+    /// Create the stream's GPU buffers; the caller seeds the state buffer
+    fn allocate(hasher: &'a GpuSha3Hasher, pending: HostCopy) -> Self {
+        let device = hasher.context.device();
+        let rate_bytes = hasher.variant.rate_bytes();
+
+        // Create the stream's own sponge state buffer
+        let state_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Stream State Buffer"),
+            size: STATE_BYTES as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create the block upload buffer (rates are multiples of 8, so this is aligned)
+        let block_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Stream Block Buffer"),
+            size: (BLOCKS_PER_DISPATCH * rate_bytes) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create uniform buffer
+        let uniform_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Stream Uniform Buffer"),
+            size: std::mem::size_of::<GpuHashParams>() as u64,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Create bind group
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("SHA-3 Stream Absorb Bind Group"),
+            layout: &hasher.absorb_bind_group_layout,
+            entries: &[
+                BindGroupEntry { binding: 0, resource: block_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                BindGroupEntry { binding: 4, resource: state_buffer.as_entire_binding() },
+            ],
+        });
+
+        Self {
+            hasher,
+            state_buffer,
+            block_buffer,
+            uniform_buffer,
+            bind_group,
+            pending,
+            output_length: None,
+            total_bytes: 0,
+            snapshot_interval: None,
+            snapshots: Vec::new(),
+        }
+    }
+
+    /// Set the output length in bytes (required for SHAKE variants)
+    pub fn with_output_length(mut self, length: usize) -> Self {
+        self.output_length = Some(length);
+        self
+    }
+
+    /// Record a snapshot of the running hash every `interval` bytes of streamed data
+    ///
+    /// At each multiple of `interval` the stream forks its state on the GPU; the main hash
+    /// is unaffected. Collect the snapshot digests with
+    /// [`take_snapshots`](Self::take_snapshots). An `interval` of zero disables snapshots.
+    pub fn with_snapshot_interval(mut self, interval: u64) -> Self {
+        self.snapshot_interval = (interval > 0).then_some(interval);
+        self
+    }
+
+    /// Branch off an independent stream that continues from the current state
+    ///
+    /// The state is copied on the GPU, so forking doesn't wait for pending work. Finalizing
+    /// the fork yields the digest of everything streamed so far without disturbing `self`.
+    pub fn fork(&self) -> GpuSha3Stream<'a> {
+        let mut fork = Self::allocate(self.hasher, self.pending.clone());
+        fork.output_length = self.output_length;
+        fork.total_bytes = self.total_bytes;
+
+        let context = &self.hasher.context;
+        let mut encoder = context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Stream Fork Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(
+            &self.state_buffer,
+            0,
+            &fork.state_buffer,
+            0,
+            STATE_BYTES as u64,
+        );
+        context.queue().submit(Some(encoder.finish()));
+        fork
+    }
+
+    /// Finalize and drain the snapshots recorded so far, as `(offset, digest)` pairs
+    ///
+    /// Each digest equals hashing the first `offset` streamed bytes.
+    pub async fn take_snapshots(&mut self) -> Result<Vec<(u64, Vec<u8>)>, GpuSha3Error> {
+        let mut digests = Vec::with_capacity(self.snapshots.len());
+        for (offset, snapshot) in std::mem::take(&mut self.snapshots) {
+            digests.push((offset, snapshot.finalize().await?));
+        }
+        Ok(digests)
+    }
+
+    /// Feed the next piece of the message
+    ///
+    /// Every complete rate block is absorbed on the GPU right away; the remainder is
+    /// buffered until more data (or `finalize`) arrives.
+    pub fn update(&mut self, mut data: &[u8]) {
+        let Some(interval) = self.snapshot_interval else {
+            self.absorb(data);
+            self.total_bytes += data.len() as u64;
+            return;
+        };
+
+        // Split the data at every snapshot offset it crosses
+        while !data.is_empty() {
+            let until_snapshot = interval - self.total_bytes % interval;
+            let len = data.len().min(usize::try_from(until_snapshot).unwrap_or(usize::MAX));
+            self.absorb(&data[..len]);
+            self.total_bytes += len as u64;
+            data = &data[len..];
+            if self.total_bytes % interval == 0 {
+                let snapshot = self.fork();
+                self.snapshots.push((self.total_bytes, snapshot));
+            }
+        }
+    }
+
+    /// Absorb complete blocks from `data` and buffer the remainder
+    fn absorb(&mut self, mut data: &[u8]) {
+        let rate_bytes = self.hasher.variant.rate_bytes();
+
+        // Top up the buffered partial block first
+        if !self.pending.is_empty() {
+            let take = (rate_bytes - self.pending.len()).min(data.len());
+            extend_host_copy(&mut self.pending, &data[..take]);
+            data = &data[take..];
+            if self.pending.len() < rate_bytes {
+                return;
+            }
+            self.absorb_blocks(&self.pending);
+            clear_host_copy(&mut self.pending);
+        }
+
+        // Absorb whole blocks straight from the caller's slice, buffer the rest
+        let whole = data.len() / rate_bytes * rate_bytes;
+        for blocks in data[..whole].chunks(BLOCKS_PER_DISPATCH * rate_bytes) {
+            self.absorb_blocks(blocks);
+        }
+        extend_host_copy(&mut self.pending, &data[whole..]);
+    }
+
+    /// Pad the buffered remainder, finish the sponge on the GPU and read back the digest
+    ///
+    /// Fails with `UnsupportedVariant` if the shader can't finish this variant; unlike the
+    /// batch API a stream can't fall back to the CPU since its state lives on the GPU.
+    pub async fn finalize(self) -> Result<Vec<u8>, GpuSha3Error> {
+        self.hasher.check_shader_support(self.hasher.variant)?;
+
+        let mut params = BatchHashParams::new(self.hasher.variant, 1, self.pending.len());
+        if let Some(length) = self.output_length {
+            params = params.with_output_length(length);
+        }
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+
+        let gpu_params = GpuHashParams::new(&params, output_bytes);
+        self.hasher
+            .hash_batch_with_dynamic_buffers(
+                &[self.pending.as_slice()],
+                gpu_params,
+                &self.state_buffer,
+            )
+            .await
+    }
+
+    /// Upload whole rate blocks and absorb them into the GPU-resident state
+    fn absorb_blocks(&self, blocks: &[u8]) {
+        let context = &self.hasher.context;
+        let gpu_params = GpuHashParams {
+            num_hashes: 1,
+            input_length: blocks.len() as u32,
+            rate_bytes: self.hasher.variant.rate_bytes() as u32,
+            output_bytes: 0,
+            input_layout: INPUT_LAYOUT_AOS,
+            domain_separator: self.hasher.variant.domain_separator() as u32,
+            // Whole blocks only; `absorb` applies no padding
+            absorb_blocks: (blocks.len() / self.hasher.variant.rate_bytes()) as u32,
+            padded_length: blocks.len() as u32,
+        };
+
+        // Queue writes land before the next submission, in order with earlier dispatches
+        context.queue().write_buffer(&self.block_buffer, 0, blocks);
+        context.queue().write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(&[gpu_params]));
+
+        let mut encoder = context.device().create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Stream Absorb Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("SHA-3 Stream Absorb Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.hasher.absorb_pipeline);
+            compute_pass.set_bind_group(0, &self.bind_group, &[]);
+            compute_pass.dispatch_workgroups(1, 1, 1);
+        }
+        encode_input_wipe(&mut encoder, &self.block_buffer, blocks.len() as u64);
+        context.queue().submit(Some(encoder.finish()));
+
+        // Reclaim finished submissions and their upload staging without blocking
+        let _ = context.device().poll(PollType::Poll);
+    }
+}
+
+/// [`std::io::Write`] sink that hashes everything written to it
+///
+/// Built on [`GpuSha3Stream`], so written bytes are absorbed on the GPU block by block
+/// rather than accumulated; call [`finalize`](Self::finalize) for the digest. Useful with
+/// serializers that write to a sink, e.g. `serde_json::to_writer(&mut writer, &value)`.
+#[derive(Debug)]
+pub struct GpuSha3Writer<'a> {
+    stream: GpuSha3Stream<'a>,
+}
+
+impl<'a> GpuSha3Writer<'a> {
+    /// Wrap a stream (e.g. one configured with an output length) as a writer
+    pub fn new(stream: GpuSha3Stream<'a>) -> Self {
+        Self { stream }
+    }
+
+    /// Finish the hash of all bytes written so far
+    pub async fn finalize(self) -> Result<Vec<u8>, GpuSha3Error> {
+        self.stream.finalize().await
+    }
+}
+
+impl std::io::Write for GpuSha3Writer<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.stream.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Running digest over an append-only log
+///
+/// Each [`append`](Self::append) absorbs only the new entry into a [`GpuSha3Stream`] and
+/// returns the digest of all entries so far (the concatenation of every appended entry),
+/// finalized from a fork of the running state so the log never has to be rehashed.
+#[derive(Debug)]
+pub struct LogHasher<'a> {
+    stream: GpuSha3Stream<'a>,
+    entries: usize,
+}
+
+impl<'a> LogHasher<'a> {
+    /// Start a log on top of `stream` (e.g. one configured with an output length)
+    pub fn new(stream: GpuSha3Stream<'a>) -> Self {
+        Self { stream, entries: 0 }
+    }
+
+    /// Append an entry and return the digest of the whole log so far
+    pub async fn append(&mut self, entry: &[u8]) -> Result<Vec<u8>, GpuSha3Error> {
+        self.stream.update(entry);
+        self.entries += 1;
+        self.stream.fork().finalize().await
+    }
+
+    /// Number of entries appended so far
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+}
+
+impl std::fmt::Debug for GpuSha3Stream<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuSha3Stream")
+            .field("variant", &self.hasher.variant)
+            .field("buffered_bytes", &self.pending.len())
+            .field("output_length", &self.output_length)
+            .field("total_bytes", &self.total_bytes)
+            .field("pending_snapshots", &self.snapshots.len())
+            .finish()
+    }
+}
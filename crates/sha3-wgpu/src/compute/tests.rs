@@ -0,0 +1,244 @@
+//! Tests of `compute` internals, such as the uniform layout and the constants shared
+//! with the shader, that the crate-level tests can't reach
+
+use super::*;
+#[cfg(feature = "secure")]
+use sha3::{Digest, Sha3_256};
+
+/// Compare the constants duplicated between this module and the WGSL shader
+///
+/// Checks each `const NAME: u32` the shader shares with this module, the default of its
+/// `override WORKGROUP_SIZE`, and that every entry point dispatched in workgroups of that
+/// size declares `@workgroup_size(WORKGROUP_SIZE, ...)`, returning a description of each
+/// one that is missing from the shader or disagrees.
+fn shader_constant_mismatches() -> Vec<String> {
+    let shader_const = |keyword: &str, name: &str| -> Option<u32> {
+        let prefix = format!("{keyword} {name}: u32 = ");
+        SHADER_SOURCE.lines().find_map(|line| {
+            let value = line.trim().strip_prefix(&prefix)?;
+            value.trim_end_matches(';').trim_end_matches('u').parse().ok()
+        })
+    };
+    let lines: Vec<&str> = SHADER_SOURCE.lines().collect();
+    let workgroup_size = |entry_point: &str| -> Option<&str> {
+        let declaration = format!("fn {entry_point}(");
+        let pair = lines.windows(2).find(|pair| pair[1].starts_with(&declaration))?;
+        Some(pair[0].split("@workgroup_size(").nth(1)?.split([',', ')']).next()?.trim())
+    };
+
+    let checks = vec![
+        ("const INPUT_LAYOUT_AOS", INPUT_LAYOUT_AOS, shader_const("const", "INPUT_LAYOUT_AOS")),
+        ("const INPUT_LAYOUT_SOA", INPUT_LAYOUT_SOA, shader_const("const", "INPUT_LAYOUT_SOA")),
+        (
+            "override WORKGROUP_SIZE",
+            DEFAULT_WORKGROUP_SIZE,
+            shader_const("override", WORKGROUP_SIZE_CONSTANT),
+        ),
+    ];
+    let mut mismatches: Vec<String> = checks
+        .into_iter()
+        .filter(|&(_, rust, shader)| shader != Some(rust))
+        .map(|(name, rust, shader)| format!("{name}: Rust has {rust}, shader has {shader:?}"))
+        .collect();
+
+    // `absorb` runs a single invocation, so it is not tied to `WORKGROUP_SIZE`
+    for entry_point in
+        [ENTRY_POINT, PERMUTE_ENTRY_POINT, ARGMIN_ENTRY_POINT, INTERLEAVE_ENTRY_POINT]
+    {
+        let shader = workgroup_size(entry_point);
+        if shader != Some(WORKGROUP_SIZE_CONSTANT) {
+            let name = format!("@workgroup_size of {entry_point}");
+            mismatches
+                .push(format!("{name}: Rust has {WORKGROUP_SIZE_CONSTANT}, shader has {shader:?}"));
+        }
+    }
+    mismatches
+}
+
+/// Echo a known `GpuHashParams` through the shader's own `HashParams` declaration
+///
+/// Writes `{ num_hashes: 1, input_length: 2, rate_bytes: 3, output_bytes: 4, input_layout: 5,
+/// domain_separator: 6, absorb_blocks: 7, padded_length: 8 }` to a uniform buffer and
+/// returns each WGSL field name (in declaration order) with the value the GPU read for it,
+/// so layout drift between the two definitions shows up as a mismatch.
+async fn echo_uniform_params(context: &GpuContext) -> Result<Vec<(String, u32)>, GpuSha3Error> {
+    let start = SHADER_SOURCE.find("struct HashParams {").expect("HashParams in shader");
+    let end = start + SHADER_SOURCE[start..].find('}').expect("end of HashParams") + 1;
+    let declaration = &SHADER_SOURCE[start..end];
+    let fields: Vec<String> = declaration
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split("//").next()?.split(':').next())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty() && name != "}")
+        .collect();
+
+    let copies: String = fields
+        .iter()
+        .enumerate()
+        .map(|(i, name)| format!("out[{i}u] = params.{name};\n"))
+        .collect();
+    let source = format!(
+        "{declaration}\n\
+         @group(0) @binding(0) var<uniform> params: HashParams;\n\
+         @group(0) @binding(1) var<storage, read_write> out: array<u32>;\n\
+         @compute @workgroup_size(1)\nfn main() {{\n{copies}}}\n"
+    );
+
+    let device = context.device();
+    let module = device.create_shader_module(ShaderModuleDescriptor {
+        label: Some("SHA-3 Uniform Layout Check Shader"),
+        source: ShaderSource::Wgsl(source.into()),
+    });
+    let pipeline = device.create_compute_pipeline(&ComputePipelineDescriptor {
+        label: Some("SHA-3 Uniform Layout Check Pipeline"),
+        layout: None,
+        module: &module,
+        entry_point: Some("main"),
+        compilation_options: Default::default(),
+        cache: None,
+    });
+
+    let known = GpuHashParams {
+        num_hashes: 1,
+        input_length: 2,
+        rate_bytes: 3,
+        output_bytes: 4,
+        input_layout: 5,
+        domain_separator: 6,
+        absorb_blocks: 7,
+        padded_length: 8,
+    };
+    let uniform_buffer = device.create_buffer_init(&util::BufferInitDescriptor {
+        label: Some("SHA-3 Uniform Layout Check Params"),
+        contents: bytemuck::cast_slice(&[known]),
+        usage: BufferUsages::UNIFORM,
+    });
+    let output_size = (fields.len() * 4).max(16) as u64;
+    let output_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("SHA-3 Uniform Layout Check Output"),
+        size: output_size,
+        usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let staging_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("SHA-3 Uniform Layout Check Staging"),
+        size: output_size,
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("SHA-3 Uniform Layout Check Bind Group"),
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[
+            BindGroupEntry { binding: 0, resource: uniform_buffer.as_entire_binding() },
+            BindGroupEntry { binding: 1, resource: output_buffer.as_entire_binding() },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    {
+        let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor::default());
+        compute_pass.set_pipeline(&pipeline);
+        compute_pass.set_bind_group(0, &bind_group, &[]);
+        compute_pass.dispatch_workgroups(1, 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&output_buffer, 0, &staging_buffer, 0, output_size);
+    context.queue().submit(Some(encoder.finish()));
+
+    let receiver = begin_readback(&staging_buffer);
+    check_poll_result(device.poll(PollType::Wait { submission_index: None, timeout: None }))?;
+    let words = finish_readback(&staging_buffer, receiver, fields.len() * 4).await?;
+    let values = bytemuck::pod_collect_to_vec::<u8, u32>(&words);
+    Ok(fields.into_iter().zip(values).collect())
+}
+
+// Uniform layout tests
+#[tokio::test]
+async fn test_uniform_layout_matches_wgsl() {
+    let context = GpuContext::new().await.unwrap();
+    let fields = echo_uniform_params(&context).await.unwrap();
+    let expected = [
+        ("num_hashes", 1),
+        ("input_length", 2),
+        ("rate_bytes", 3),
+        ("output_bytes", 4),
+        ("input_layout", 5),
+        ("domain_separator", 6),
+        ("absorb_blocks", 7),
+        ("padded_length", 8),
+    ];
+    let expected: Vec<(String, u32)> =
+        expected.iter().map(|&(name, value)| (name.to_string(), value)).collect();
+    assert_eq!(fields, expected);
+}
+
+// Shader constant tests
+#[test]
+fn test_shader_constants_match_rust() {
+    assert_eq!(shader_constant_mismatches(), Vec::<String>::new());
+}
+
+/// Copy the contents of `buffer`, which needs `COPY_SRC`, back to the host
+#[cfg(feature = "secure")]
+async fn read_buffer(context: &GpuContext, buffer: &Buffer) -> Vec<u8> {
+    let device = context.device();
+    let staging_buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("SHA-3 Test Readback Buffer"),
+        size: buffer.size(),
+        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    encoder.copy_buffer_to_buffer(buffer, 0, &staging_buffer, 0, buffer.size());
+    context.queue().submit(Some(encoder.finish()));
+
+    let receiver = begin_readback(&staging_buffer);
+    check_poll_result(device.poll(PollType::Wait { submission_index: None, timeout: None }))
+        .unwrap();
+    finish_readback(&staging_buffer, receiver, buffer.size() as usize).await.unwrap()
+}
+
+// Secure mode tests
+#[cfg(feature = "secure")]
+#[tokio::test]
+async fn test_secure_mode_zeroes_input_buffers() {
+    let context = GpuContext::new().await.unwrap();
+    let hasher =
+        GpuSha3Hasher::with_persistent_buffers(context, Sha3Variant::Sha3_256, Some((8, 64, 32)))
+            .unwrap();
+
+    // Each batch's input buffer is zeroed after its dispatch; later, shorter batches
+    // must not depend on what an earlier batch left behind
+    let long_data: Vec<Vec<u8>> = (0..8u8).map(|i| vec![0xa0 | i; 64]).collect();
+    let long: Vec<&[u8]> = long_data.iter().map(Vec::as_slice).collect();
+    let short: Vec<&[u8]> = vec![b"key", b"pwd"];
+    for inputs in [&long, &short, &long] {
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+        assert_eq!(hasher.hash_batch(inputs).await.unwrap(), expected);
+    }
+    assert_eq!(hasher.session_stats().persistent_batches, 3);
+    let input_buffer = hasher.lock_buffers().as_ref().unwrap().input_buffer.clone();
+    assert!(read_buffer(&hasher.context, &input_buffer).await.iter().all(|&byte| byte == 0));
+
+    for _ in 0..2 {
+        assert_eq!(
+            hasher.hash_one_fast(b"secret").await.unwrap(),
+            Sha3_256::digest(b"secret").to_vec()
+        );
+    }
+    let single = hasher.single_buffers.try_lock().unwrap().as_ref().unwrap().input_buffer.clone();
+    assert!(read_buffer(&hasher.context, &single).await.iter().all(|&byte| byte == 0));
+
+    // Dynamic and prepared paths
+    let params = BatchHashParams::new(Sha3Variant::Sha3_256, 2, 3);
+    let expected: Vec<u8> = short.iter().flat_map(Sha3_256::digest).collect();
+    for _ in 0..2 {
+        let result = hasher.hash_batch_from_state(&[0; 200], &short, &params).await.unwrap();
+        assert_eq!(result, expected);
+    }
+    let prepared = hasher.prepared(&params).unwrap();
+    for _ in 0..2 {
+        assert_eq!(prepared.hash(&short).await.unwrap(), expected);
+    }
+}
@@ -0,0 +1,258 @@
+//! Hashing pixel data straight out of a wgpu texture
+
+use sha3_core::BatchHashParams;
+use wgpu::*;
+
+use super::stats::BatchPath;
+use super::stream::BLOCKS_PER_DISPATCH;
+use super::{
+    begin_readback, encode_input_wipe, finish_readback, host_copy, state_to_gpu_words,
+    GpuHashParams, GpuSha3Hasher, INPUT_LAYOUT_AOS, STATE_BYTES,
+};
+use crate::error::GpuSha3Error;
+
+/// Rectangular region of a texture's base mip level
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureRegion {
+    /// First texel (x, y) and array layer (z) of the region
+    pub origin: Origin3d,
+    /// Width, height and array layer count of the region, in texels
+    pub size: Extent3d,
+}
+
+impl TextureRegion {
+    /// Region covering all of `texture`'s base mip level
+    pub fn full(texture: &Texture) -> Self {
+        Self { origin: Origin3d::ZERO, size: texture.size() }
+    }
+}
+
+impl GpuSha3Hasher {
+    /// Hash the pixels of a texture region without reading them back to the host
+    ///
+    /// The message is the region's texels tightly packed in row-major order (rows of each
+    /// layer top to bottom, layers in order), i.e. exactly the bytes that were uploaded with
+    /// `queue.write_texture` at an unpadded `bytes_per_row`. Texture copies require a row
+    /// pitch aligned to 256 bytes, so rows are copied one by one into a packed storage buffer
+    /// when the region's row size isn't already aligned; the whole blocks are then absorbed
+    /// a bounded number per dispatch and the remainder padded and squeezed on the GPU, with
+    /// only the digest read back.
+    ///
+    /// The texture needs `TextureUsages::COPY_SRC` and an uncompressed color format. A
+    /// fixed prefix is hashed in front of the pixels, as with the batch methods; since copy
+    /// destinations must be texel-aligned, the part of the prefix not yet absorbed (its
+    /// length modulo the rate) must be a whole number of texels.
+    pub async fn hash_texture(
+        &self,
+        texture: &Texture,
+        region: TextureRegion,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        self.check_shader_support(self.variant)?;
+        let params = BatchHashParams::new(self.variant, 1, 0);
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+
+        let format = texture.format();
+        let texel_bytes = match (format.block_dimensions(), format.block_copy_size(None)) {
+            ((1, 1), Some(size)) => size,
+            _ => {
+                return Err(GpuSha3Error::GpuOperationFailed(format!(
+                    "Texture format {format:?} can't be hashed (compressed or depth/stencil)"
+                )))
+            }
+        };
+        let bounds = texture.size();
+        let TextureRegion { origin, size } = region;
+        let exceeds = |start: u32, len: u32, bound: u32| {
+            start.checked_add(len).map_or(true, |end| end > bound)
+        };
+        if exceeds(origin.x, size.width, bounds.width)
+            || exceeds(origin.y, size.height, bounds.height)
+            || exceeds(origin.z, size.depth_or_array_layers, bounds.depth_or_array_layers)
+        {
+            return Err(GpuSha3Error::GpuOperationFailed(format!(
+                "Texture region {region:?} exceeds the texture size {bounds:?}"
+            )));
+        }
+
+        let device = self.context.device();
+        let queue = self.context.queue();
+        let rate_bytes = self.variant.rate_bytes();
+        let row_bytes = (size.width * texel_bytes) as usize;
+        let rows = (size.height * size.depth_or_array_layers) as usize;
+        let tail = self.prefix.as_ref().map_or(&[][..], |prefix| prefix.tail());
+        if tail.len() % texel_bytes as usize != 0 {
+            return Err(GpuSha3Error::GpuOperationFailed(format!(
+                "Fixed prefix tail of {} bytes isn't aligned to {texel_bytes}-byte texels",
+                tail.len()
+            )));
+        }
+        let message_len = tail.len() + rows * row_bytes;
+
+        // Packed message (fixed-prefix tail, then every row of the region back to back).
+        // Sized so the remainder copy below may round up to a whole word.
+        let packed_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Texture Message Buffer"),
+            size: (((message_len + 15) / 16).max(1) * 16) as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        if !tail.is_empty() {
+            // Buffer writes must be whole words; the zero padding is overwritten by the
+            // texture copies, which execute after queued writes
+            let mut words = host_copy(vec![0; (tail.len() + 3) / 4 * 4]);
+            words[..tail.len()].copy_from_slice(tail);
+            queue.write_buffer(&packed_buffer, 0, &words);
+        }
+
+        let initial_state = self.prefix.as_ref().map_or([0u64; 25], |prefix| prefix.state);
+        let state_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Texture State Buffer"),
+            size: STATE_BYTES as u64,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(
+            &state_buffer,
+            0,
+            bytemuck::cast_slice(&state_to_gpu_words(&initial_state)),
+        );
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Texture Encoder"),
+        });
+
+        // Copy the region's rows into the packed buffer
+        let aligned = row_bytes % COPY_BYTES_PER_ROW_ALIGNMENT as usize == 0;
+        if aligned {
+            encoder.copy_texture_to_buffer(
+                TexelCopyTextureInfo { texture, mip_level: 0, origin, aspect: TextureAspect::All },
+                TexelCopyBufferInfo {
+                    buffer: &packed_buffer,
+                    layout: TexelCopyBufferLayout {
+                        offset: tail.len() as u64,
+                        bytes_per_row: Some(row_bytes as u32),
+                        rows_per_image: Some(size.height),
+                    },
+                },
+                size,
+            );
+        } else {
+            // Single-row copies need no row pitch, and buffer offsets only have to be
+            // texel-aligned, so each row lands right after the previous one
+            for row in 0..rows {
+                let row_origin = Origin3d {
+                    x: origin.x,
+                    y: origin.y + row as u32 % size.height,
+                    z: origin.z + row as u32 / size.height,
+                };
+                encoder.copy_texture_to_buffer(
+                    TexelCopyTextureInfo {
+                        texture,
+                        mip_level: 0,
+                        origin: row_origin,
+                        aspect: TextureAspect::All,
+                    },
+                    TexelCopyBufferInfo {
+                        buffer: &packed_buffer,
+                        layout: TexelCopyBufferLayout {
+                            offset: (tail.len() + row * row_bytes) as u64,
+                            bytes_per_row: None,
+                            rows_per_image: None,
+                        },
+                    },
+                    Extent3d { width: size.width, height: 1, depth_or_array_layers: 1 },
+                );
+            }
+        }
+
+        // Absorb every whole block straight from the packed buffer, at most
+        // BLOCKS_PER_DISPATCH blocks per dispatch (as GpuSha3Stream does) so no single
+        // invocation runs long enough to trip a GPU watchdog. Chunks are multiples of
+        // 512 bytes, so their offsets meet any storage binding alignment.
+        let full_len = message_len / rate_bytes * rate_bytes;
+        if full_len > 0 {
+            let chunk_len = BLOCKS_PER_DISPATCH * rate_bytes;
+            let absorb_uniform = |len: usize| {
+                let absorb_params = GpuHashParams {
+                    num_hashes: 1,
+                    input_length: len as u32,
+                    rate_bytes: rate_bytes as u32,
+                    output_bytes: 0,
+                    input_layout: INPUT_LAYOUT_AOS,
+                    domain_separator: self.variant.domain_separator() as u32,
+                    // Whole blocks only; `absorb` applies no padding
+                    absorb_blocks: (len / rate_bytes) as u32,
+                    padded_length: len as u32,
+                };
+                let uniform_buffer = device.create_buffer(&BufferDescriptor {
+                    label: Some("SHA-3 Texture Absorb Uniform Buffer"),
+                    size: std::mem::size_of::<GpuHashParams>() as u64,
+                    usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                });
+                queue.write_buffer(&uniform_buffer, 0, bytemuck::cast_slice(&[absorb_params]));
+                uniform_buffer
+            };
+            // Every chunk is full except possibly the last, so two uniforms cover them all
+            let full_uniform = absorb_uniform(chunk_len.min(full_len));
+            let last_len = full_len % chunk_len;
+            let last_uniform =
+                (full_len > chunk_len && last_len > 0).then(|| absorb_uniform(last_len));
+
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("SHA-3 Texture Absorb Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.absorb_pipeline);
+            for offset in (0..full_len).step_by(chunk_len) {
+                let len = chunk_len.min(full_len - offset);
+                let uniform_buffer = match &last_uniform {
+                    Some(last_uniform) if len < chunk_len => last_uniform,
+                    _ => &full_uniform,
+                };
+                let blocks = BufferBinding {
+                    buffer: &packed_buffer,
+                    offset: offset as u64,
+                    size: BufferSize::new(len as u64),
+                };
+                let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("SHA-3 Texture Absorb Bind Group"),
+                    layout: &self.absorb_bind_group_layout,
+                    entries: &[
+                        BindGroupEntry { binding: 0, resource: BindingResource::Buffer(blocks) },
+                        BindGroupEntry { binding: 2, resource: uniform_buffer.as_entire_binding() },
+                        BindGroupEntry { binding: 4, resource: state_buffer.as_entire_binding() },
+                    ],
+                });
+                compute_pass.set_bind_group(0, &bind_group, &[]);
+                compute_pass.dispatch_workgroups(1, 1, 1);
+            }
+        }
+
+        // Finish from the absorbed state: move the partial block into a one-hash batch's
+        // input buffer (zero-filled on upload, then overwritten in command order)
+        let remainder = message_len - full_len;
+        let gpu_params =
+            GpuHashParams::new(&BatchHashParams::new(self.variant, 1, remainder), output_bytes);
+        let batch = self.create_dynamic_batch(&[&vec![0u8; remainder]], gpu_params, &state_buffer);
+        if remainder > 0 {
+            // Whole blocks are multiples of 8 bytes; the packed buffer covers the rounding
+            let copy_len = (remainder + 3) / 4 * 4;
+            encoder.copy_buffer_to_buffer(
+                &packed_buffer,
+                full_len as u64,
+                &batch.input_buffer,
+                0,
+                copy_len as u64,
+            );
+        }
+        self.encode_dynamic_batch(&mut encoder, &batch);
+        encode_input_wipe(&mut encoder, &packed_buffer, packed_buffer.size());
+        self.stats.record(BatchPath::Dynamic, 1, rows * row_bytes);
+        queue.submit(Some(encoder.finish()));
+
+        let receiver = begin_readback(&batch.staging_buffer);
+        self.wait_for_readback(&[&batch.staging_buffer]).await?;
+        finish_readback(&batch.staging_buffer, receiver, batch.total_output_bytes).await
+    }
+}
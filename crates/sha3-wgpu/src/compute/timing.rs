@@ -0,0 +1,161 @@
+//! GPU-side timing of the hashing kernel with timestamp queries
+
+use std::time::Duration;
+
+use sha3_core::BatchHashParams;
+use wgpu::*;
+
+use super::{begin_readback, finish_readback, BatchPath, GpuHashParams, GpuSha3Hasher};
+use crate::error::GpuSha3Error;
+
+/// Bytes of the two resolved timestamps (start and end of the pass)
+const TIMESTAMP_BYTES: u64 = 2 * std::mem::size_of::<u64>() as u64;
+
+/// Query set timing one compute pass, and the buffers its timestamps are read back through
+struct TimestampQueries {
+    query_set: QuerySet,
+    resolve_buffer: Buffer,
+    staging_buffer: Buffer,
+}
+
+impl TimestampQueries {
+    fn new(device: &Device) -> Self {
+        let query_set = device.create_query_set(&QuerySetDescriptor {
+            label: Some("SHA-3 Timestamp Query Set"),
+            ty: QueryType::Timestamp,
+            count: 2,
+        });
+        let resolve_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Timestamp Resolve Buffer"),
+            size: TIMESTAMP_BYTES,
+            usage: BufferUsages::QUERY_RESOLVE | BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("SHA-3 Timestamp Staging Buffer"),
+            size: TIMESTAMP_BYTES,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self { query_set, resolve_buffer, staging_buffer }
+    }
+
+    /// Writes bracketing the pass they're attached to
+    fn pass_writes(&self) -> ComputePassTimestampWrites<'_> {
+        ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(0),
+            end_of_pass_write_index: Some(1),
+        }
+    }
+
+    /// Record copying the timestamps into the staging buffer, after the timed pass
+    fn encode_resolve(&self, encoder: &mut CommandEncoder) {
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.staging_buffer,
+            0,
+            TIMESTAMP_BYTES,
+        );
+    }
+}
+
+/// Time between the two raw timestamps in `bytes`, `period` nanoseconds per tick
+fn elapsed(bytes: &[u8], period: f32) -> Duration {
+    let start = u64::from_le_bytes(bytes[..8].try_into().unwrap());
+    let end = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+    Duration::from_nanos((end.saturating_sub(start) as f64 * f64::from(period)) as u64)
+}
+
+impl GpuSha3Hasher {
+    /// [`hash_batch`](Self::hash_batch), also returning how long the compute pass ran on
+    /// the GPU
+    ///
+    /// The duration covers the kernel only, as measured by the device's timestamp queries:
+    /// uploads, readback and host overhead are excluded, so wall-clock time minus this is
+    /// the cost outside the kernel. It is zero when the device doesn't support
+    /// `TIMESTAMP_QUERY` (see [`GpuContext::supports_timestamps`]) or the batch was hashed
+    /// on the CPU. Timed batches always use freshly allocated buffers.
+    ///
+    /// [`GpuContext::supports_timestamps`]: crate::GpuContext::supports_timestamps
+    pub async fn hash_batch_timed(
+        &self,
+        inputs: &[&[u8]],
+    ) -> Result<(Vec<u8>, Duration), GpuSha3Error> {
+        if inputs.is_empty() {
+            return Ok((Vec::new(), Duration::ZERO));
+        }
+        let input_length = inputs[0].len();
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+        if !self.context.supports_timestamps() {
+            return Ok((self.hash_batch(inputs).await?, Duration::ZERO));
+        }
+
+        let params = BatchHashParams::new(self.variant, inputs.len(), input_length);
+        let output_bytes = params.get_output_bytes().map_err(GpuSha3Error::Core)?;
+        let framed = self.frame_inputs(inputs);
+        let framed_refs: Vec<&[u8]>;
+        let framed_inputs = match &framed {
+            Some(framed) => {
+                framed_refs = framed.iter().map(|v| v.as_slice()).collect();
+                &framed_refs[..]
+            }
+            None => inputs,
+        };
+        let framed_length = framed_inputs[0].len();
+        if !self.routes_to_gpu(inputs.len(), framed_length, output_bytes)
+            || !(self.shader_supports)(self.variant)
+        {
+            let prefix = self.prefix.as_ref().map_or(&[][..], |p| &p.bytes[..]);
+            return Ok((self.hash_on_cpu(prefix, inputs, &params)?, Duration::ZERO));
+        }
+
+        let device = self.context.device();
+        let framed_params = BatchHashParams::new(self.variant, inputs.len(), framed_length);
+        let gpu_params = GpuHashParams::new(&framed_params, output_bytes);
+        let batch =
+            self.create_dynamic_batch(framed_inputs, gpu_params, &self.initial_state_buffer);
+        let queries = TimestampQueries::new(device);
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("SHA-3 Timed Command Encoder"),
+        });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+                label: Some("SHA-3 Timed Compute Pass"),
+                timestamp_writes: Some(queries.pass_writes()),
+            });
+            self.dispatch_in_pass(
+                &mut compute_pass,
+                &self.pipeline,
+                &[&batch.bind_group],
+                inputs.len(),
+            );
+        }
+        encoder.copy_buffer_to_buffer(
+            &batch.output_buffer,
+            0,
+            &batch.staging_buffer,
+            0,
+            batch.output_buffer_size as u64,
+        );
+        queries.encode_resolve(&mut encoder);
+        self.stats.record(BatchPath::Dynamic, inputs.len(), inputs.len() * framed_length);
+        self.context.queue().submit(Some(encoder.finish()));
+
+        let digest_receiver = begin_readback(&batch.staging_buffer);
+        let timestamp_receiver = begin_readback(&queries.staging_buffer);
+        self.wait_for_readback(&[&batch.staging_buffer, &queries.staging_buffer]).await?;
+        let digests =
+            finish_readback(&batch.staging_buffer, digest_receiver, batch.total_output_bytes)
+                .await?;
+        let timestamps =
+            finish_readback(&queries.staging_buffer, timestamp_receiver, TIMESTAMP_BYTES as usize)
+                .await?;
+        Ok((digests, elapsed(&timestamps, self.context.queue().get_timestamp_period())))
+    }
+}
@@ -0,0 +1,79 @@
+//! Picking the workgroup size by measuring the hashing kernel on the current adapter
+
+use std::time::{Duration, Instant};
+
+use sha3_core::BatchHashParams;
+
+use super::{GpuHashParams, GpuSha3Hasher};
+use crate::error::GpuSha3Error;
+
+/// Workgroup sizes `auto_tune` measures, in order of preference on ties
+const AUTO_TUNE_WORKGROUP_SIZES: [u32; 3] = [64, 128, 256];
+
+/// Inputs in each benchmark batch, enough to occupy every compute unit of typical GPUs
+const AUTO_TUNE_BATCH_SIZE: usize = 1 << 14;
+
+/// Bytes per benchmark input (under one rate block for every variant)
+const AUTO_TUNE_INPUT_LENGTH: usize = 64;
+
+/// Timed batches per workgroup size; the fastest counts, filtering out scheduling noise
+const AUTO_TUNE_ROUNDS: usize = 3;
+
+impl GpuSha3Hasher {
+    /// Measure the hashing kernel at 64, 128 and 256 invocations per workgroup and keep the
+    /// fastest, returning it
+    ///
+    /// Each size is warmed up once, then timed (wall clock) over a few batches of synthetic
+    /// inputs hashed with freshly allocated buffers, so persistent buffers are left
+    /// untouched; the batches do count towards [`session_stats`](Self::session_stats).
+    /// Sizes beyond the device's workgroup limits are skipped, and with none left the size
+    /// is unchanged. Takes a few tens of milliseconds on a typical GPU, so run it once
+    /// after construction rather than per batch. Native only: WebAssembly has no
+    /// monotonic clock in `std`.
+    pub async fn auto_tune(&mut self) -> Result<u32, GpuSha3Error> {
+        self.check_shader_support(self.variant)?;
+        let limits = self.context.limits();
+        let limit = self.context.capabilities().max_workgroup_size;
+        let output_bytes = self.variant.output_bytes();
+        let num_hashes =
+            if self.fits_device(AUTO_TUNE_BATCH_SIZE, AUTO_TUNE_INPUT_LENGTH, output_bytes) {
+                AUTO_TUNE_BATCH_SIZE
+            } else {
+                let binding = limits.max_storage_buffer_binding_size as usize;
+                (binding / AUTO_TUNE_INPUT_LENGTH.max(output_bytes)).max(1)
+            };
+
+        let data: Vec<u8> = (0..num_hashes * AUTO_TUNE_INPUT_LENGTH).map(|i| i as u8).collect();
+        let inputs: Vec<&[u8]> = data.chunks(AUTO_TUNE_INPUT_LENGTH).collect();
+        let params = BatchHashParams::new(self.variant, num_hashes, AUTO_TUNE_INPUT_LENGTH);
+        let gpu_params = GpuHashParams::new(&params, output_bytes);
+
+        let mut best: Option<(u32, Duration)> = None;
+        for threads in AUTO_TUNE_WORKGROUP_SIZES.into_iter().filter(|&threads| threads <= limit) {
+            self.set_workgroup_size(threads);
+            // Warm-up: the first dispatch of a new pipeline may include driver compilation
+            self.hash_batch_with_dynamic_buffers(&inputs, gpu_params, &self.initial_state_buffer)
+                .await?;
+
+            let mut fastest = Duration::MAX;
+            for _ in 0..AUTO_TUNE_ROUNDS {
+                let start = Instant::now();
+                self.hash_batch_with_dynamic_buffers(
+                    &inputs,
+                    gpu_params,
+                    &self.initial_state_buffer,
+                )
+                .await?;
+                fastest = fastest.min(start.elapsed());
+            }
+            if best.map_or(true, |(_, time)| fastest < time) {
+                best = Some((threads, fastest));
+            }
+        }
+
+        if let Some((threads, _)) = best {
+            self.set_workgroup_size(threads);
+        }
+        Ok(self.workgroup_size)
+    }
+}
@@ -1,13 +1,208 @@
 //! WebGPU context management
 
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
 use crate::error::GpuSha3Error;
 use wgpu::*;
 
+/// GPU and driver version strings, for bug reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DriverInfo {
+    /// Adapter name (e.g. "NVIDIA GeForce RTX 4090")
+    pub name: String,
+    /// Driver name (e.g. "NVIDIA", "llvmpipe"); may be empty on some backends
+    pub driver: String,
+    /// Driver version details; may be empty on some backends
+    pub driver_info: String,
+    /// Backend the adapter runs on (e.g. "vulkan")
+    pub backend: String,
+}
+
+impl std::fmt::Display for DriverInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}, driver: {} {})", self.name, self.backend, self.driver, self.driver_info)
+    }
+}
+
+/// Device memory limits and, where the backend reports them, current allocations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MemoryReport {
+    /// Largest buffer the device allows, in bytes
+    pub max_buffer_size: u64,
+    /// Largest storage buffer binding, in bytes; caps a batch's input and output buffers
+    pub max_storage_buffer_binding_size: u64,
+    /// Bytes in live allocations, if the backend's allocator reports them
+    pub allocated_bytes: Option<u64>,
+    /// Bytes reserved in memory blocks, including unallocated regions, if reported
+    pub reserved_bytes: Option<u64>,
+}
+
+impl std::fmt::Display for MemoryReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "max buffer {} bytes, max storage binding {} bytes",
+            self.max_buffer_size, self.max_storage_buffer_binding_size
+        )?;
+        match (self.allocated_bytes, self.reserved_bytes) {
+            (Some(allocated), Some(reserved)) => {
+                write!(f, ", {allocated} bytes allocated of {reserved} reserved")
+            }
+            _ => write!(f, ", allocations not reported by this backend"),
+        }
+    }
+}
+
+/// What the device a context created actually grants, for feature detection
+///
+/// Reported by [`GpuContext::capabilities`]. Features are requested only where the adapter
+/// supports them, so these can be less than asked for, notably on fallback adapters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities {
+    /// Whether `SHADER_INT64` was enabled on the device; the shader emulates 64-bit lanes
+    /// with `u32` pairs, so digests are the same either way
+    pub has_int64: bool,
+    /// Largest buffer the device allows, in bytes
+    pub max_buffer_size: u64,
+    /// Most invocations a workgroup can have (the per-workgroup and x-size limits)
+    pub max_workgroup_size: u32,
+    /// Whether the adapter is a software (CPU) implementation, such as the one selected by
+    /// `WGPU_FORCE_FALLBACK_ADAPTER`
+    pub is_fallback_adapter: bool,
+}
+
+impl std::fmt::Display for Capabilities {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "int64 {}, max buffer {} bytes, max workgroup size {}{}",
+            if self.has_int64 { "enabled" } else { "unavailable" },
+            self.max_buffer_size,
+            self.max_workgroup_size,
+            if self.is_fallback_adapter { ", fallback adapter" } else { "" }
+        )
+    }
+}
+
+/// Identity of an adapter, enough to compare the candidates a system reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterDescriptor {
+    /// Device name reported by the driver
+    pub name: String,
+    /// PCI vendor id (0 if unknown)
+    pub vendor: u32,
+    /// Backend the adapter was enumerated through
+    pub backend: Backend,
+    /// Kind of device (discrete, integrated, software...)
+    pub device_type: DeviceType,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl AdapterDescriptor {
+    /// Sort key, lower is better: hardware before software, then native backends
+    /// (Vulkan/Metal/DX12) before GL, then discrete before integrated GPUs
+    fn rank(&self) -> (u8, u8, u8) {
+        let software = u8::from(self.device_type == DeviceType::Cpu);
+        let backend = match self.backend {
+            Backend::Vulkan | Backend::Metal | Backend::Dx12 | Backend::BrowserWebGpu => 0,
+            Backend::Gl => 1,
+            Backend::Noop => 2,
+        };
+        let device_type = match self.device_type {
+            DeviceType::DiscreteGpu => 0,
+            DeviceType::IntegratedGpu => 1,
+            DeviceType::VirtualGpu => 2,
+            DeviceType::Other => 3,
+            DeviceType::Cpu => 4,
+        };
+        (software, backend, device_type)
+    }
+}
+
+impl From<&AdapterInfo> for AdapterDescriptor {
+    fn from(info: &AdapterInfo) -> Self {
+        Self {
+            name: info.name.clone(),
+            vendor: info.vendor,
+            backend: info.backend,
+            device_type: info.device_type,
+        }
+    }
+}
+
+/// Indices of `adapters` with duplicates of the same device removed, best first
+///
+/// A GPU reported under several backends (e.g. Vulkan and GL) is kept once, through its
+/// best backend.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn rank_adapters(adapters: &[AdapterDescriptor]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..adapters.len()).collect();
+    order.sort_by_key(|&i| adapters[i].rank());
+    let mut seen = std::collections::HashSet::new();
+    order.retain(|&i| seen.insert((adapters[i].vendor, adapters[i].name.as_str())));
+    order
+}
+
+/// Index of the best adapter among `adapters`, if any
+#[cfg(not(target_arch = "wasm32"))]
+fn best_of(adapters: &[Adapter]) -> Option<usize> {
+    let descriptors: Vec<AdapterDescriptor> =
+        adapters.iter().map(|a| AdapterDescriptor::from(&a.get_info())).collect();
+    rank_adapters(&descriptors).first().copied()
+}
+
+/// Limits the pipelines need: 256-wide workgroups, and up to four storage buffers in two
+/// bind groups (digest interleaving binds its output as a second group)
+fn required_limits() -> Limits {
+    Limits {
+        max_bind_groups: 2,
+        max_storage_buffers_per_shader_stage: 4,
+        max_compute_invocations_per_workgroup: 256,
+        max_compute_workgroup_size_x: 256,
+        max_compute_workgroup_size_y: 1,
+        max_compute_workgroup_size_z: 1,
+        max_compute_workgroups_per_dimension: 1,
+        ..Limits::downlevel_webgl2_defaults()
+    }
+}
+
+/// Check an adapter's reported capabilities against what the crate needs, listing every
+/// shortfall in the error
+pub(crate) fn check_capabilities(
+    features: Features,
+    limits: &Limits,
+    flags: DownlevelFlags,
+) -> Result<(), GpuSha3Error> {
+    let mut problems = Vec::new();
+    if !flags.contains(DownlevelFlags::COMPUTE_SHADERS) {
+        problems.push("no compute shader support".to_string());
+    }
+    let missing = GpuContext::required_features() - features;
+    if !missing.is_empty() {
+        problems.push(format!("missing features {missing:?}"));
+    }
+    required_limits().check_limits_with_fail_fn(limits, false, |name, needed, available| {
+        problems.push(format!("{name} is {available}, needs {needed}"));
+    });
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(GpuSha3Error::AdapterUnsupported(problems.join("; ")))
+    }
+}
+
 /// WebGPU context for SHA-3 computation
 pub struct GpuContext {
     device: Device,
     queue: Queue,
     adapter_info: AdapterInfo,
+    adapter_features: Features,
+    /// Set by the device-lost callback; the device can't be used again afterwards
+    device_lost: Arc<AtomicBool>,
 }
 
 impl GpuContext {
@@ -16,13 +211,41 @@ impl GpuContext {
         Self::new_with_features(None).await
     }
 
+    /// [`new`](Self::new), blocking the calling thread until the device is ready
+    #[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
+    pub fn new_blocking() -> Result<Self, GpuSha3Error> {
+        pollster::block_on(Self::new())
+    }
+
     /// Create a new GPU context with specific feature requirements
     pub async fn new_with_features(
         required_features: Option<Features>,
+    ) -> Result<Self, GpuSha3Error> {
+        Self::create(Backends::all(), PowerPreference::HighPerformance, required_features).await
+    }
+
+    /// Create a new GPU context on one of `backends`, choosing the adapter by `power`
+    ///
+    /// Pins the context to e.g. `Backends::VULKAN` or `Backends::METAL`, for comparing
+    /// backends on the same machine; [`adapter_info`](Self::adapter_info) reports the
+    /// backend in use. With `HighPerformance` the adapter is ranked as in [`new`](Self::new)
+    /// among those the given backends report; otherwise wgpu picks it by `power`. Fails
+    /// with `AdapterNotFound` if no adapter is available on those backends.
+    pub async fn new_with_backend(
+        backends: Backends,
+        power: PowerPreference,
+    ) -> Result<Self, GpuSha3Error> {
+        Self::create(backends, power, None).await
+    }
+
+    /// Create a context on the best adapter of `backends` for `power`
+    async fn create(
+        backends: Backends,
+        power: PowerPreference,
+        required_features: Option<Features>,
     ) -> Result<Self, GpuSha3Error> {
         // Create wgpu instance
-        let instance_descriptor =
-            InstanceDescriptor { backends: Backends::all(), ..Default::default() };
+        let instance_descriptor = InstanceDescriptor { backends, ..Default::default() };
         let instance = Instance::new(&instance_descriptor);
 
         // Allow fallback adapter in CI environments (e.g., GitHub Actions without GPU)
@@ -30,18 +253,73 @@ impl GpuContext {
             .map(|v| v == "1" || v.to_lowercase() == "true")
             .unwrap_or(false);
 
+        // Prefer our own ranking over wgpu's default, which may pick a GL duplicate
+        #[cfg(not(target_arch = "wasm32"))]
+        let best = if force_fallback || power != PowerPreference::HighPerformance {
+            None
+        } else {
+            let mut adapters = instance.enumerate_adapters(backends);
+            best_of(&adapters).map(|i| adapters.swap_remove(i))
+        };
+        #[cfg(target_arch = "wasm32")]
+        let best = None;
+
         // Request adapter (GPU or fallback)
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptions {
-                power_preference: PowerPreference::HighPerformance,
-                force_fallback_adapter: force_fallback,
-                compatible_surface: None,
-            })
-            .await
-            .map_err(|e| {
-                GpuSha3Error::AdapterNotFound(format!("Failed to find GPU adapter: {e}"))
-            })?;
+        let adapter = match best {
+            Some(adapter) => adapter,
+            None => instance
+                .request_adapter(&RequestAdapterOptions {
+                    power_preference: power,
+                    force_fallback_adapter: force_fallback,
+                    compatible_surface: None,
+                })
+                .await
+                .map_err(|e| {
+                    GpuSha3Error::AdapterNotFound(format!("Failed to find GPU adapter: {e}"))
+                })?,
+        };
+
+        Self::from_adapter(adapter, required_features).await
+    }
+
+    /// List every adapter the system reports, in the order
+    /// [`new_with_adapter_index`](Self::new_with_adapter_index) indexes them
+    ///
+    /// Each entry carries the adapter's name, backend and device type, e.g. for a selection
+    /// menu. Unlike [`best_adapter`](Self::best_adapter), a GPU exposed through several
+    /// backends is listed once per backend, and adapters that can't run the pipelines are
+    /// included (creating a context on them fails with `AdapterUnsupported`).
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn list_adapters() -> Vec<AdapterInfo> {
+        let instance =
+            Instance::new(&InstanceDescriptor { backends: Backends::all(), ..Default::default() });
+        instance.enumerate_adapters(Backends::all()).iter().map(Adapter::get_info).collect()
+    }
+
+    /// Create a new GPU context on the adapter at `index` in
+    /// [`list_adapters`](Self::list_adapters)
+    ///
+    /// Fails with `AdapterNotFound` if `index` is out of range.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn new_with_adapter_index(index: usize) -> Result<Self, GpuSha3Error> {
+        let instance =
+            Instance::new(&InstanceDescriptor { backends: Backends::all(), ..Default::default() });
+        let mut adapters = instance.enumerate_adapters(Backends::all());
+        if index >= adapters.len() {
+            return Err(GpuSha3Error::AdapterNotFound(format!(
+                "Adapter index {index} out of range ({} adapters found)",
+                adapters.len()
+            )));
+        }
+        Self::from_adapter(adapters.swap_remove(index), None).await
+    }
 
+    /// Create the device and queue on `adapter`
+    async fn from_adapter(
+        adapter: Adapter,
+        required_features: Option<Features>,
+    ) -> Result<Self, GpuSha3Error> {
+        Self::check_adapter(&adapter)?;
         let adapter_info = adapter.get_info();
 
         // Start with downlevel defaults which should be browser-compatible
@@ -73,9 +351,10 @@ impl GpuContext {
         // Check what features the adapter supports
         let adapter_features = adapter.features();
         let desired_features = required_features.unwrap_or({
-            // Request features needed for SHA-3 compute shader
-            // SHADER_INT64 is required for u64 operations in the shader
-            Features::SHADER_INT64
+            // Request optional features; the shader itself needs none of them
+            // SHADER_INT64 is reported by `capabilities` (lanes are u32 pairs either way)
+            // TIMESTAMP_QUERY lets `hash_batch_timed` measure the kernel on the GPU
+            Features::SHADER_INT64 | Features::TIMESTAMP_QUERY
         });
 
         // Only request features that the adapter actually supports
@@ -95,7 +374,47 @@ impl GpuContext {
             .await
             .map_err(|e| GpuSha3Error::DeviceCreation(format!("Failed to create device: {e}")))?;
 
-        Ok(Self { device, queue, adapter_info })
+        let device_lost = Arc::new(AtomicBool::new(false));
+        let lost_flag = Arc::clone(&device_lost);
+        device.set_device_lost_callback(move |_reason, _message| {
+            lost_flag.store(true, Ordering::Relaxed);
+        });
+
+        Ok(Self { device, queue, adapter_info, adapter_features, device_lost })
+    }
+
+    /// Device features the crate can't run without
+    ///
+    /// Empty: the shader emulates 64-bit Keccak lanes with `u32` pairs, so it runs on any
+    /// adapter with compute shaders. `SHADER_INT64` is enabled when available, but never
+    /// required.
+    pub fn required_features() -> Features {
+        Features::empty()
+    }
+
+    /// Check that `adapter` can run the crate's pipelines before creating a device on it
+    ///
+    /// Verifies compute shader support, [`required_features`](Self::required_features) and
+    /// the buffer and workgroup limits the pipelines use. Fails with `AdapterUnsupported`
+    /// naming every shortfall. Contexts run the same check on the adapter they select.
+    pub fn check_adapter(adapter: &Adapter) -> Result<(), GpuSha3Error> {
+        check_capabilities(
+            adapter.features(),
+            &adapter.limits(),
+            adapter.get_downlevel_capabilities().flags,
+        )
+    }
+
+    /// Describe the adapter `new` would pick: duplicates of the same device across
+    /// backends are collapsed and Vulkan/Metal/DX12 preferred over GL, then software
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn best_adapter() -> Result<AdapterDescriptor, GpuSha3Error> {
+        let instance =
+            Instance::new(&InstanceDescriptor { backends: Backends::all(), ..Default::default() });
+        let adapters = instance.enumerate_adapters(Backends::all());
+        let best = best_of(&adapters)
+            .ok_or_else(|| GpuSha3Error::AdapterNotFound("No GPU adapters found".to_string()))?;
+        Ok(AdapterDescriptor::from(&adapters[best].get_info()))
     }
 
     /// Get reference to the device
@@ -113,10 +432,72 @@ impl GpuContext {
         &self.adapter_info
     }
 
+    /// Get the GPU and driver version strings to include in bug reports
+    pub fn driver_info(&self) -> DriverInfo {
+        DriverInfo {
+            name: self.adapter_info.name.clone(),
+            driver: self.adapter_info.driver.clone(),
+            driver_info: self.adapter_info.driver_info.clone(),
+            backend: self.adapter_info.backend.to_str().to_string(),
+        }
+    }
+
+    /// Get the features supported by the adapter (a superset of those enabled on the device)
+    pub fn adapter_features(&self) -> Features {
+        self.adapter_features
+    }
+
+    /// Report the features and limits the device was granted
+    ///
+    /// Check `has_int64` here rather than [`adapter_features`](Self::adapter_features):
+    /// the adapter may advertise a feature the device was created without.
+    pub fn capabilities(&self) -> Capabilities {
+        let limits = self.device.limits();
+        Capabilities {
+            has_int64: self.device.features().contains(Features::SHADER_INT64),
+            max_buffer_size: limits.max_buffer_size,
+            max_workgroup_size: limits
+                .max_compute_invocations_per_workgroup
+                .min(limits.max_compute_workgroup_size_x),
+            is_fallback_adapter: self.adapter_info.device_type == DeviceType::Cpu,
+        }
+    }
+
+    /// Whether the device can time compute passes (`TIMESTAMP_QUERY` was enabled)
+    pub fn supports_timestamps(&self) -> bool {
+        self.device.features().contains(Features::TIMESTAMP_QUERY)
+    }
+
     /// Get device limits
     pub fn limits(&self) -> Limits {
         self.device.limits()
     }
+
+    /// Summarize the device's buffer limits and current memory allocations
+    ///
+    /// A batch whose input or output buffer would exceed `max_storage_buffer_binding_size`
+    /// is refused with `InvalidInputLength` (or hashed on the CPU), while allocation
+    /// failures below that limit mean the device is out of memory. Allocation totals come
+    /// from the backend's allocator and cover every resource on the device; they are
+    /// `None` on backends that don't report them.
+    pub fn memory_report(&self) -> MemoryReport {
+        let limits = self.device.limits();
+        let report = self.device.generate_allocator_report();
+        MemoryReport {
+            max_buffer_size: limits.max_buffer_size,
+            max_storage_buffer_binding_size: u64::from(limits.max_storage_buffer_binding_size),
+            allocated_bytes: report.as_ref().map(|r| r.total_allocated_bytes),
+            reserved_bytes: report.as_ref().map(|r| r.total_reserved_bytes),
+        }
+    }
+
+    /// Whether the device has been lost (driver reset, GPU removed, or destroyed)
+    ///
+    /// Loss is permanent: every later submission fails, so callers should create a new
+    /// context rather than retry.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
 }
 
 impl std::fmt::Debug for GpuContext {
@@ -0,0 +1,23 @@
+//! Encodings used by the convenience helpers: hex digests and length-prefixed wire
+//! framing (the SP 800-185 encodings live in `sha3_core::encoding`)
+
+/// Lowercase hex encoding of `bytes`
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        let _ = write!(hex, "{byte:02x}");
+    }
+    hex
+}
+
+/// Re-frame concatenated `digest_len`-byte digests as `u32 BE length || digest` records
+pub(crate) fn length_prefixed(digests: &[u8], digest_len: usize) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(digests.len() + digests.len() / digest_len.max(1) * 4);
+    for digest in digests.chunks_exact(digest_len.max(1)) {
+        framed.extend_from_slice(&(digest_len as u32).to_be_bytes());
+        framed.extend_from_slice(digest);
+    }
+    framed
+}
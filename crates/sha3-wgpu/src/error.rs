@@ -1,6 +1,6 @@
 //! GPU-specific error types
 
-use sha3_core::Sha3Error;
+use sha3_core::{Sha3Error, Sha3Variant};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -14,6 +14,9 @@ pub enum GpuSha3Error {
     #[error("Device creation failed: {0}")]
     DeviceCreation(String),
 
+    #[error("Adapter can't run the SHA-3 pipelines: {0}")]
+    AdapterUnsupported(String),
+
     #[error("Buffer mapping failed: {0}")]
     BufferMapping(String),
 
@@ -25,4 +28,13 @@ pub enum GpuSha3Error {
 
     #[error("GPU operation failed: {0}")]
     GpuOperationFailed(String),
+
+    #[error("GPU device lost: {0}")]
+    DeviceLost(String),
+
+    #[error("Variant not supported by the GPU shader: {0:?}")]
+    UnsupportedVariant(Sha3Variant),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
 }
@@ -1,17 +1,30 @@
 //! GPU-accelerated SHA-3 implementation using WGSL and wgpu
 
+pub mod backend;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batching;
 pub mod compute;
 pub mod context;
+mod encoding;
 pub mod error;
+pub mod multi;
+#[cfg(all(feature = "digest", not(target_arch = "wasm32")))]
+pub mod rustcrypto;
 
+pub use backend::*;
+#[cfg(not(target_arch = "wasm32"))]
+pub use batching::*;
 pub use compute::*;
 pub use context::*;
 pub use error::*;
+pub use multi::*;
+#[cfg(all(feature = "digest", not(target_arch = "wasm32")))]
+pub use rustcrypto::*;
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sha3::{Digest, Sha3_224, Sha3_256, Sha3_384, Sha3_512};
+    use sha3::{Digest, Keccak256, Keccak512, Sha3_224, Sha3_256, Sha3_384, Sha3_512};
     use sha3_core::Sha3Variant;
 
     async fn test_variant_against_reference(
@@ -51,6 +64,8 @@ mod tests {
                     hasher.update(input);
                     hasher.finalize().to_vec()
                 }
+                Sha3Variant::Keccak256 => Keccak256::digest(input).to_vec(),
+                Sha3Variant::Keccak512 => Keccak512::digest(input).to_vec(),
                 _ => panic!("Unsupported variant for reference test"),
             };
             expected.extend_from_slice(&hash);
@@ -97,6 +112,127 @@ mod tests {
         test_variant_against_reference(Sha3Variant::Sha3_256, &inputs).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_hash_batch_vec_matches_flattened_chunks() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_224).unwrap();
+        let inputs = vec![b"alpha".as_slice(), b"bravo".as_slice(), b"delta".as_slice()];
+
+        let flat = hasher.hash_batch(&inputs).await.unwrap();
+        let digests = hasher.hash_batch_vec(&inputs).await.unwrap();
+        assert_eq!(digests.len(), inputs.len());
+        for (digest, chunk) in digests.iter().zip(flat.chunks(28)) {
+            assert_eq!(digest, chunk);
+        }
+        assert!(hasher.hash_batch_vec(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_vec_with_params_shake() {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+        use sha3_core::BatchHashParams;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let inputs = vec![b"alpha".as_slice(), b"bravo".as_slice(), b"delta".as_slice()];
+        assert!(matches!(
+            hasher.hash_batch_vec(&inputs).await,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::MissingOutputLength))
+        ));
+
+        let params = BatchHashParams::new(Sha3Variant::Shake256, 3, 5).with_output_length(40);
+        let digests = hasher.hash_batch_vec_with_params(&inputs, &params).await.unwrap();
+        assert_eq!(digests.len(), inputs.len());
+        for (digest, input) in digests.iter().zip(&inputs) {
+            let mut expected = vec![0u8; 40];
+            sha3::Shake256::default().chain(input).finalize_xof().read(&mut expected);
+            assert_eq!(digest, &expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_hex() {
+        use sha3_core::BatchHashParams;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let hex = hasher.hash_batch_hex(&[b"".as_slice()]).await.unwrap();
+        assert_eq!(hex, vec!["a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a"]);
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let inputs = vec![b"abc".as_slice(), b"xyz".as_slice()];
+        let result = hasher.hash_batch_hex(&inputs).await;
+        assert!(matches!(
+            result,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::MissingOutputLength))
+        ));
+        let params = BatchHashParams::new(Sha3Variant::Shake128, 2, 3).with_output_length(20);
+        let hex = hasher.hash_batch_hex_with_params(&inputs, &params).await.unwrap();
+        let flat = hasher.hash_batch_with_params(&inputs, &params).await.unwrap();
+        assert_eq!(hex, flat.chunks(20).map(hex::encode).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_results() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let inputs = vec![b"abc".as_slice(), b"xyz".as_slice()];
+        let results = hasher.hash_batch_results(&inputs).await.unwrap();
+        assert_eq!(results.len(), 2);
+        for (result, input) in results.iter().zip(&inputs) {
+            assert_eq!(result.variant, Sha3Variant::Sha3_256);
+            assert_eq!(result.bytes, Sha3_256::digest(input).to_vec());
+            assert_eq!(result.as_hex(), hex::encode(Sha3_256::digest(input)));
+        }
+        assert_ne!(results[0], results[1]);
+        assert_eq!(
+            results[0],
+            sha3_core::HashResult::new(Sha3Variant::Sha3_256, results[0].bytes.clone())
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&results[0]).unwrap();
+            let decoded: sha3_core::HashResult = serde_json::from_str(&json).unwrap();
+            assert_eq!(decoded, results[0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_batch() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let inputs = vec![b"abc".as_slice(), b"xyz".as_slice(), b"123".as_slice()];
+        let abc = Sha3_256::digest(b"abc");
+        let xyz = Sha3_256::digest(b"xyz");
+        let one_two_three = Sha3_256::digest(b"123");
+        let mut tampered = one_two_three;
+        tampered[31] ^= 1;
+
+        let expected = vec![abc.as_slice(), abc.as_slice(), tampered.as_slice()];
+        assert_eq!(hasher.verify_batch(&inputs, &expected).await.unwrap(), [true, false, false]);
+        let expected = vec![abc.as_slice(), xyz.as_slice(), one_two_three.as_slice()];
+        assert_eq!(hasher.verify_batch(&inputs, &expected).await.unwrap(), [true, true, true]);
+
+        let result = hasher.verify_batch(&inputs, &expected[..2]).await;
+        assert!(matches!(result, Err(GpuSha3Error::InvalidInputLength(2))));
+        let expected = vec![abc.as_slice(), xyz.as_slice(), &abc[..31]];
+        let result = hasher.verify_batch(&inputs, &expected).await;
+        assert!(matches!(
+            result,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(31)))
+        ));
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let result = hasher.verify_batch(&inputs[..1], &[abc.as_slice()]).await;
+        assert!(matches!(
+            result,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::MissingOutputLength))
+        ));
+    }
+
     #[tokio::test]
     async fn test_sha3_256_large_batch() {
         // Ensure all inputs have the same length by using fixed-width formatting
@@ -139,9 +275,29 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_keccak_matches_ethereum_vectors() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Keccak256).unwrap();
+        let vectors = [
+            (&b""[..], "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"),
+            (&b"abc"[..], "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(hex::encode(hasher.hash_batch(&[input]).await.unwrap()), expected);
+        }
+
+        let inputs = vec![b"".as_slice(), b"abc".as_slice(), &[0x5Au8; 135][..], &[1u8; 73]];
+        for variant in [Sha3Variant::Keccak256, Sha3Variant::Keccak512] {
+            for input in &inputs {
+                test_variant_against_reference(variant, &[input]).await.unwrap();
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_long_input() {
-        // Test with 8000 bytes (within the 8KB GPU buffer limit)
+        // Test with 8000 bytes (many rate-sized blocks)
         let long_input = vec![b'a'; 8000];
         let inputs = vec![long_input.as_slice()];
 
@@ -221,15 +377,19 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_error_input_too_large() {
-        // Test that inputs exceeding the 8KB GPU buffer limit are rejected
+    async fn test_megabyte_input_on_gpu() {
+        // The shader absorbs straight from the storage buffer, so inputs aren't capped at 8KB
         let context = GpuContext::new().await.unwrap();
         let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
-        let oversized_input = vec![b'x'; 10000]; // Exceeds 8192 byte limit
-        let inputs = vec![oversized_input.as_slice()];
-        let result = hasher.hash_batch(&inputs).await;
-        assert!(result.is_err());
-        assert!(matches!(result.unwrap_err(), GpuSha3Error::InvalidInputLength(_)));
+        let large_input: Vec<u8> = (0..1 << 20).map(|i: u32| (i % 251) as u8).collect();
+        let result = hasher.hash_batch(&[&large_input]).await.unwrap();
+        assert_eq!(result, Sha3_256::digest(&large_input)[..]);
+        assert_eq!(hasher.session_stats().cpu_batches, 0);
+
+        // Several inputs past the old limit in one batch, unaligned to the rate
+        let inputs: Vec<Vec<u8>> = (0..3u8).map(|i| vec![i; 10_001]).collect();
+        let refs: Vec<&[u8]> = inputs.iter().map(Vec::as_slice).collect();
+        test_variant_against_reference(Sha3Variant::Sha3_512, &refs).await.unwrap();
     }
 
     #[tokio::test]
@@ -246,6 +406,26 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_error_absurd_output_length() {
+        use sha3_core::BatchHashParams;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let inputs = vec![b"test".as_slice(); 2];
+
+        // Too large for the device, and for the CPU route it would otherwise take
+        let params =
+            BatchHashParams::new(Sha3Variant::Shake256, 2, 4).with_output_length(usize::MAX / 2);
+        let result = hasher.hash_batch_with_params(&inputs, &params).await;
+        assert!(matches!(
+            result,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(_)))
+        ));
+        let cpu = CpuSha3Hasher::new(Sha3Variant::Shake256);
+        assert!(cpu.hash_batch_with_params(&inputs, &params).await.is_err());
+    }
+
     // Rate boundary tests (from audit report)
     #[tokio::test]
     async fn test_sha3_224_at_rate_boundary() {
@@ -303,6 +483,2584 @@ mod tests {
         }
     }
 
+    // Fixed prefix tests
+    async fn assert_fixed_prefix_matches_reference(prefix: Vec<u8>) {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_fixed_prefix(prefix.clone());
+
+        let data: Vec<Vec<u8>> =
+            (0..8).map(|i| format!("message body {i:02}").into_bytes()).collect();
+        let inputs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+        let results = hasher.hash_batch(&inputs).await.unwrap();
+
+        for (input, gpu_hash) in inputs.iter().zip(results.chunks(32)) {
+            let mut reference = Sha3_256::new();
+            reference.update(&prefix);
+            reference.update(input);
+            assert_eq!(gpu_hash, reference.finalize().as_slice());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixed_prefix_64_bytes() {
+        let prefix: Vec<u8> = (0..64).collect();
+        assert_fixed_prefix_matches_reference(prefix).await;
+    }
+
+    #[tokio::test]
+    async fn test_fixed_prefix_spanning_rate_blocks() {
+        // 300 bytes = two absorbed SHA3-256 rate blocks plus a 28-byte tail
+        let prefix: Vec<u8> = (0..300).map(|i| (i % 251) as u8).collect();
+        assert_fixed_prefix_matches_reference(prefix).await;
+    }
+
+    #[tokio::test]
+    async fn test_fixed_prefix_exact_rate_multiple() {
+        // 136 bytes = exactly one SHA3-256 rate block, leaving no tail
+        let prefix = vec![0x5Au8; 136];
+        assert_fixed_prefix_matches_reference(prefix).await;
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_with_prefix_matches_concatenation() {
+        use sha3_core::BatchHashParams;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let data: Vec<Vec<u8>> = (0..8).map(|i| format!("password {i:02}").into_bytes()).collect();
+        let inputs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+
+        // A short salt, and one spanning two SHA3-256 rate blocks plus a 28-byte tail
+        for salt in [b"0123456789abcdef".to_vec(), (0..300).map(|i| (i % 251) as u8).collect()] {
+            let salted: Vec<Vec<u8>> =
+                inputs.iter().map(|input| [&salt, *input].concat()).collect();
+            let salted_refs: Vec<&[u8]> = salted.iter().map(|v| v.as_slice()).collect();
+            assert_eq!(
+                hasher.hash_batch_with_prefix(&salt, &inputs).await.unwrap(),
+                hasher.hash_batch(&salted_refs).await.unwrap()
+            );
+        }
+        assert_eq!(
+            hasher.hash_batch_with_prefix(b"", &inputs).await.unwrap(),
+            hasher.hash_batch(&inputs).await.unwrap()
+        );
+
+        // SHAKE output lengths come from the params
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let params = BatchHashParams::new(Sha3Variant::Shake128, inputs.len(), inputs[0].len())
+            .with_output_length(40);
+        let salt = b"salt";
+        let salted: Vec<Vec<u8>> = inputs.iter().map(|input| [&salt[..], input].concat()).collect();
+        let salted_refs: Vec<&[u8]> = salted.iter().map(|v| v.as_slice()).collect();
+        let salted_params =
+            BatchHashParams::new(Sha3Variant::Shake128, inputs.len(), salted[0].len())
+                .with_output_length(40);
+        assert_eq!(
+            hasher.hash_batch_with_prefix_and_params(salt, &inputs, &params).await.unwrap(),
+            hasher.hash_batch_with_params(&salted_refs, &salted_params).await.unwrap()
+        );
+
+        // A fixed prefix on the hasher comes before the salt
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_fixed_prefix(b"fixed:".to_vec());
+        let digests = hasher.hash_batch_with_prefix(b"salt:", &inputs).await.unwrap();
+        for (input, digest) in inputs.iter().zip(digests.chunks(32)) {
+            assert_eq!(digest, Sha3_256::digest([&b"fixed:salt:"[..], input].concat()).as_slice());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_namespaced_hashers_differ() {
+        let namespaced = |tag: &'static [u8]| async move {
+            let context = GpuContext::new().await.unwrap();
+            GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap().namespaced(tag)
+        };
+        let ns1 = namespaced(b"ns1").await;
+        let ns2 = namespaced(b"ns2").await;
+        let ns = namespaced(b"ns").await;
+
+        let input = b"same input".as_slice();
+        let digest1 = ns1.hash_batch(&[input]).await.unwrap();
+        assert_ne!(digest1, ns2.hash_batch(&[input]).await.unwrap());
+        // The tag is framed with its bit length: left_encode(24) = [1, 24]
+        assert_eq!(digest1, Sha3_256::digest([&[1u8, 24][..], b"ns1", input].concat())[..]);
+
+        // Moving bytes between tag and data changes the digest
+        assert_ne!(
+            ns.hash_batch(&[b"1x".as_slice()]).await.unwrap(),
+            ns1.hash_batch(&[b"x".as_slice()]).await.unwrap()
+        );
+    }
+
+    // Grouped hashing tests
+    #[tokio::test]
+    async fn test_hash_batch_grouped_matches_sequential() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+
+        // Interleave five distinct lengths so each group's results must be scattered back
+        let lengths = [3usize, 17, 40, 64, 100];
+        let data: Vec<Vec<u8>> =
+            (0..20).map(|i| vec![i as u8; lengths[i % lengths.len()]]).collect();
+        let inputs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+
+        let grouped = hasher.hash_batch_grouped(&inputs).await.unwrap();
+
+        let mut sequential = Vec::new();
+        for input in &inputs {
+            sequential.extend(hasher.hash_batch(&[input]).await.unwrap());
+        }
+        assert_eq!(grouped, sequential);
+
+        for (input, gpu_hash) in inputs.iter().zip(grouped.chunks(32)) {
+            assert_eq!(gpu_hash, Sha3_256::digest(input).as_slice());
+        }
+    }
+
+    // Memory-mapped output tests
+    #[tokio::test]
+    async fn test_hash_batch_to_file_matches_in_memory() {
+        let context = GpuContext::new().await.unwrap();
+        // Small persistent buffers so the batch is split across several sub-batches
+        let hasher = GpuSha3Hasher::with_persistent_buffers(
+            context,
+            Sha3Variant::Sha3_256,
+            Some((4, 64, 32)),
+        )
+        .unwrap();
+
+        let data: Vec<Vec<u8>> =
+            (0..10).map(|i| format!("mmap input {i:02}").into_bytes()).collect();
+        let inputs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+
+        let path =
+            std::env::temp_dir().join(format!("sha3-wgpu-mmap-test-{}.bin", std::process::id()));
+        hasher.hash_batch_to_file(&inputs, &path).await.unwrap();
+        let from_file = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let in_memory = hasher.hash_batch(&inputs).await.unwrap();
+        assert_eq!(from_file, in_memory);
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_to_channel_delivers_in_order() {
+        use futures::StreamExt;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::with_persistent_buffers(
+            context,
+            Sha3Variant::Sha3_256,
+            Some((4, 64, 32)),
+        )
+        .unwrap();
+        let data: Vec<Vec<u8>> =
+            (0..10).map(|i| format!("channel input {i:02}").into_bytes()).collect();
+        let inputs: Vec<&[u8]> = data.iter().map(|v| v.as_slice()).collect();
+
+        // Capacity 0 still buffers one message per sender: the producer runs at most one
+        // sub-batch ahead of the consumer
+        let (tx, rx) = futures::channel::mpsc::channel(0);
+        let (sent, received) =
+            futures::join!(hasher.hash_batch_to_channel(&inputs, tx), rx.collect::<Vec<Vec<u8>>>());
+        sent.unwrap();
+        assert_eq!(received.iter().map(Vec::len).collect::<Vec<_>>(), [128, 128, 64]);
+        assert_eq!(received.concat(), hasher.hash_batch(&inputs).await.unwrap());
+
+        let (tx, rx) = futures::channel::mpsc::channel(0);
+        drop(rx);
+        let result = hasher.hash_batch_to_channel(&inputs, tx).await;
+        assert!(matches!(result, Err(GpuSha3Error::GpuOperationFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn test_hash_stream_batch_pulls_chunks_lazily() {
+        use futures::StreamExt;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let generate = || (0..50_000u32).map(|i| i.to_le_bytes().repeat(3));
+        let chunks: Vec<Vec<u8>> =
+            hasher.hash_stream_batch(generate(), 4096).map(Result::unwrap).collect().await;
+        assert_eq!(chunks.len(), 13);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= 4096 * 32));
+        let expected: Vec<u8> = generate().flat_map(Sha3_256::digest).collect();
+        assert_eq!(chunks.concat(), expected);
+
+        // Chunks of mixed lengths, from borrowed slices
+        let data: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i; i as usize * 30]).collect();
+        let streamed: Vec<Vec<u8>> = hasher
+            .hash_stream_batch(data.iter().map(Vec::as_slice), 4)
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(streamed.len(), 3);
+        assert_eq!(streamed.concat(), data.iter().flat_map(Sha3_256::digest).collect::<Vec<_>>());
+        assert_eq!(hasher.hash_stream_batch(Vec::<Vec<u8>>::new(), 8).count().await, 0);
+    }
+
+    // Readback timeout tests
+    #[test]
+    fn test_poll_timeout_maps_to_error() {
+        // Simulate a wedged GPU by feeding the poll outcome directly
+        let result = compute::check_poll_result(Err(wgpu::PollError::Timeout));
+        match result {
+            Err(GpuSha3Error::GpuOperationFailed(msg)) => assert_eq!(msg, "readback timed out"),
+            other => panic!("Expected readback timeout error, got {other:?}"),
+        }
+        assert!(compute::check_poll_result(Ok(wgpu::PollStatus::QueueEmpty)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_mapping_failure_is_retried() {
+        let inputs = vec![b"retry me".as_slice()];
+        let expected = Sha3_256::digest(inputs[0]).to_vec();
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_injected_mapping_failures(1);
+        assert_eq!(hasher.mapping_retries(), 2);
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+        // The batch was dispatched twice
+        assert_eq!(hasher.session_stats().batches, 2);
+
+        // Without retries the transient failure surfaces
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_mapping_retries(0)
+            .with_injected_mapping_failures(1);
+        let result = hasher.hash_batch(&inputs).await;
+        assert!(matches!(result, Err(GpuSha3Error::BufferMapping(_))));
+        assert!(!hasher.context().is_device_lost());
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_readback_timeout_generous_succeeds() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_readback_timeout(std::time::Duration::from_secs(60));
+        assert_eq!(hasher.readback_timeout(), Some(std::time::Duration::from_secs(60)));
+
+        let inputs = vec![b"timeout test".as_slice()];
+        let result = hasher.hash_batch(&inputs).await.unwrap();
+        assert_eq!(result, Sha3_256::digest(inputs[0]).to_vec());
+    }
+
+    // Non-blocking readback tests
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_readback_does_not_block_executor() {
+        use std::sync::Arc;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = Arc::new(GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap());
+
+        // More in-flight hashes than worker threads: blocking readbacks would starve them
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let hasher = Arc::clone(&hasher);
+                tokio::spawn(async move {
+                    let input = format!("non-blocking readback {i}");
+                    let digest = hasher.hash_batch(&[input.as_bytes()]).await.unwrap();
+                    (input, digest)
+                })
+            })
+            .collect();
+
+        // The executor keeps scheduling other tasks while readbacks are pending
+        let ticker = tokio::spawn(async {
+            for _ in 0..100 {
+                tokio::task::yield_now().await;
+            }
+        });
+        tokio::time::timeout(std::time::Duration::from_secs(60), ticker).await.unwrap().unwrap();
+
+        for handle in handles {
+            let (input, digest) = handle.await.unwrap();
+            assert_eq!(digest, Sha3_256::digest(input.as_bytes()).to_vec());
+        }
+    }
+
+    // Adaptive persistent buffer tests
+    #[tokio::test]
+    async fn test_adaptive_buffers_sized_by_first_batch() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new_adaptive(context, Sha3Variant::Sha3_256).unwrap();
+        assert_eq!(hasher.current_capacity(), None);
+
+        let check = |inputs: &[Vec<u8>], result: Vec<u8>| {
+            let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+            assert_eq!(result, expected);
+        };
+        let batch = |count: usize, len: usize| -> Vec<Vec<u8>> {
+            (0..count).map(|i| vec![i as u8; len]).collect()
+        };
+
+        for (count, len, capacity) in [
+            (10, 48, (10, 48, 32)),
+            (10, 48, (10, 48, 32)),
+            (3, 20, (10, 48, 32)),
+            (25, 40, (25, 48, 32)),
+        ] {
+            let inputs = batch(count, len);
+            let refs: Vec<&[u8]> = inputs.iter().map(|v| v.as_slice()).collect();
+            check(&inputs, hasher.hash_batch(&refs).await.unwrap());
+            assert_eq!(hasher.current_capacity(), Some(capacity));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_shrink_to_releases_grown_buffers() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new_adaptive(context, Sha3Variant::Sha3_256).unwrap();
+        hasher.shrink_to(1, 1, 1).unwrap();
+        assert_eq!(hasher.current_capacity(), None);
+
+        let check = |inputs: &[Vec<u8>], result: Vec<u8>| {
+            let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+            assert_eq!(result, expected);
+        };
+        let spike: Vec<Vec<u8>> = (0..2000).map(|i| vec![i as u8; 300]).collect();
+        let refs: Vec<&[u8]> = spike.iter().map(|v| v.as_slice()).collect();
+        check(&spike, hasher.hash_batch(&refs).await.unwrap());
+        assert_eq!(hasher.current_capacity(), Some((2000, 300, 32)));
+        let grown = hasher.buffer_stats();
+
+        // Only shrinks: the output dimension stays at 32 bytes
+        hasher.shrink_to(16, 64, 64).unwrap();
+        assert_eq!(hasher.current_capacity(), Some((16, 64, 32)));
+        let shrunk = hasher.buffer_stats();
+        assert!(shrunk.input_bytes < grown.input_bytes);
+        assert!(shrunk.output_bytes < grown.output_bytes);
+
+        let small: Vec<Vec<u8>> = (0..10).map(|i| vec![i as u8; 50]).collect();
+        let refs: Vec<&[u8]> = small.iter().map(|v| v.as_slice()).collect();
+        check(&small, hasher.hash_batch(&refs).await.unwrap());
+        assert_eq!(hasher.current_capacity(), Some((16, 64, 32)));
+        assert!(hasher.session_stats().persistent_batches >= 2);
+    }
+
+    #[tokio::test]
+    async fn test_builder_configures_persistent_buffers() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::builder().build(context).unwrap();
+        assert_eq!(hasher.variant(), Sha3Variant::Sha3_256);
+        assert_eq!(hasher.current_capacity(), Some((1000, 8192, 64)));
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::builder()
+            .variant(Sha3Variant::Keccak256)
+            .max_batch_size(16)
+            .max_input_length(64)
+            .max_output_bytes(32)
+            .build(context)
+            .unwrap();
+        assert_eq!(hasher.variant(), Sha3Variant::Keccak256);
+        assert_eq!(hasher.current_capacity(), Some((16, 64, 32)));
+        let expected: Vec<u8> = Keccak256::digest(b"abc").to_vec();
+        assert_eq!(hasher.hash_batch(&[b"abc"]).await.unwrap(), expected);
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::builder().persistent(false).build(context).unwrap();
+        assert_eq!(hasher.current_capacity(), None);
+        assert_eq!(hasher.hash_batch(&[b"abc"]).await.unwrap(), Sha3_256::digest(b"abc").to_vec());
+
+        // Oversized buffers are refused as by `with_persistent_buffers`
+        let context = GpuContext::new().await.unwrap();
+        let result = GpuSha3Hasher::builder().max_input_length(usize::MAX).build(context);
+        assert!(matches!(result, Err(GpuSha3Error::InvalidInputLength(_))));
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_with_progress_reports_each_chunk() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::builder()
+            .max_batch_size(100)
+            .max_input_length(32)
+            .build(context)
+            .unwrap();
+        let inputs: Vec<Vec<u8>> = (0..1050u16).map(|i| i.to_le_bytes().repeat(16)).collect();
+        let refs: Vec<&[u8]> = inputs.iter().map(Vec::as_slice).collect();
+        let expected: Vec<u8> = refs.iter().flat_map(Sha3_256::digest).collect();
+
+        // Split at the persistent buffers' capacity, which the batch doesn't grow
+        let mut reports = Vec::new();
+        let digests = hasher
+            .hash_batch_with_progress(&refs, |completed, total| reports.push((completed, total)))
+            .await
+            .unwrap();
+        assert_eq!(digests, expected);
+        let chunk_ends: Vec<usize> = (1..=10).map(|i| i * 100).chain([1050]).collect();
+        assert_eq!(reports, chunk_ends.into_iter().map(|end| (end, 1050)).collect::<Vec<_>>());
+        assert_eq!(hasher.current_capacity(), Some((100, 32, 64)));
+        assert_eq!(hasher.session_stats().batches, 11);
+
+        // A batch within capacity is one submission
+        let mut reports = Vec::new();
+        hasher
+            .hash_batch_with_progress(&refs[..10], |completed, total| {
+                reports.push((completed, total))
+            })
+            .await
+            .unwrap();
+        assert_eq!(reports, vec![(10, 10)]);
+
+        let mut called = false;
+        assert!(hasher
+            .hash_batch_with_progress(&[], |_, _| called = true)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(!called);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_buffers_grow_with_batches() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::with_persistent_buffers(
+            context,
+            Sha3Variant::Sha3_256,
+            Some((4, 16, 32)),
+        )
+        .unwrap();
+        assert_eq!(hasher.current_capacity(), Some((4, 16, 32)));
+
+        for (count, len, capacity) in [
+            (2, 8, (4, 16, 32)),
+            (8, 16, (8, 16, 32)),
+            (8, 100, (8, 100, 32)),
+            (300, 50, (300, 100, 32)),
+            (1500, 64, (1500, 100, 32)),
+            (1000, 10, (1500, 100, 32)),
+        ] {
+            let inputs: Vec<Vec<u8>> =
+                (0..count).map(|i: usize| vec![(i % 251) as u8; len]).collect();
+            let refs: Vec<&[u8]> = inputs.iter().map(|v| v.as_slice()).collect();
+            let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+            assert_eq!(hasher.hash_batch(&refs).await.unwrap(), expected);
+            assert_eq!(hasher.current_capacity(), Some(capacity));
+        }
+        let stats = hasher.session_stats();
+        assert_eq!((stats.persistent_batches, stats.dynamic_batches), (6, 0));
+    }
+
+    #[tokio::test]
+    async fn test_buffer_stats_and_memory_report() {
+        let context = GpuContext::new().await.unwrap();
+        let report = context.memory_report();
+        assert_eq!(report.max_buffer_size, context.limits().max_buffer_size);
+        assert!(report.max_storage_buffer_binding_size <= report.max_buffer_size);
+        assert!(report.to_string().contains(&report.max_buffer_size.to_string()));
+
+        let hasher = GpuSha3Hasher::builder()
+            .max_batch_size(10)
+            .max_input_length(100)
+            .max_output_bytes(32)
+            .build(context)
+            .unwrap()
+            .with_staging_buffers(3);
+        let stats = hasher.buffer_stats();
+        assert_eq!(
+            stats,
+            BufferStats {
+                input_bytes: 1008,
+                output_bytes: 320,
+                staging_bytes: 3 * 320,
+                uniform_bytes: 32,
+                staging_buffers: 3,
+                max_batch_size: 10,
+                max_input_length: 100,
+                max_output_bytes: 32,
+            }
+        );
+        assert_eq!(stats.total_bytes(), 1008 + 320 + 960 + 32);
+
+        // Growth shows up in the next report
+        let inputs: Vec<&[u8]> = vec![&[7; 100]; 20];
+        hasher.hash_batch(&inputs).await.unwrap();
+        let stats = hasher.buffer_stats();
+        assert_eq!((stats.max_batch_size, stats.input_bytes, stats.output_bytes), (20, 2000, 640));
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::builder().persistent(false).build(context).unwrap();
+        assert_eq!(hasher.buffer_stats(), BufferStats::default());
+    }
+
+    // Pipeline debug info tests
+    #[tokio::test]
+    async fn test_debug_info_reports_pipeline() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_384).unwrap();
+        let info = hasher.debug_info();
+        assert_eq!(info.entry_point, "main");
+        assert_eq!(info.variant, Sha3Variant::Sha3_384);
+        assert_eq!(info.workgroup_size, 256);
+        assert_eq!(info.features, hasher.context().device().features());
+
+        // A capable adapter gets the features the shader asks for enabled on the device
+        if hasher.context().adapter_features().contains(wgpu::Features::SHADER_INT64) {
+            assert!(info.features.contains(wgpu::Features::SHADER_INT64));
+        }
+    }
+
+    // Streaming hasher tests
+    #[tokio::test]
+    async fn test_stream_byte_by_byte_matches_whole_buffer() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        // Spans more rate blocks than a single absorb dispatch uploads
+        let data: Vec<u8> = (0..9000u32).map(|i| (i * 7 + 3) as u8).collect();
+
+        let mut whole = hasher.stream();
+        whole.update(&data);
+        let whole = whole.finalize().await.unwrap();
+
+        let mut bytewise = hasher.stream();
+        for byte in &data {
+            bytewise.update(std::slice::from_ref(byte));
+        }
+        let bytewise = bytewise.finalize().await.unwrap();
+
+        assert_eq!(bytewise, whole);
+        assert_eq!(whole, Sha3_256::digest(&data).to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_stream_snapshots_match_prefix_hashes() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let data: Vec<u8> = (0..1000u32).map(|i| (i * 13 + 1) as u8).collect();
+
+        // Uneven chunks so snapshot offsets fall inside updates and inside rate blocks
+        let mut stream = hasher.stream().with_snapshot_interval(100);
+        for chunk in data.chunks(37) {
+            stream.update(chunk);
+        }
+        let snapshots = stream.take_snapshots().await.unwrap();
+        let digest = stream.finalize().await.unwrap();
+
+        assert_eq!(snapshots.len(), 10);
+        for (n, (offset, snapshot)) in snapshots.iter().enumerate() {
+            assert_eq!(*offset, (n as u64 + 1) * 100);
+            assert_eq!(*snapshot, Sha3_256::digest(&data[..*offset as usize]).to_vec());
+        }
+        assert_eq!(digest, Sha3_256::digest(&data).to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_writer_matches_concatenation() {
+        use std::io::Write;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_512).unwrap();
+        let chunks: [&[u8]; 4] = [b"{\"id\":", b"42,", &[b'x'; 150], b"}"];
+
+        let mut writer = hasher.writer();
+        for chunk in chunks {
+            writer.write_all(chunk).unwrap();
+        }
+        write!(writer, "trailer {}", 7).unwrap();
+        writer.flush().unwrap();
+        let result = writer.finalize().await.unwrap();
+
+        let mut expected = chunks.concat();
+        expected.extend_from_slice(b"trailer 7");
+        assert_eq!(result, Sha3_512::digest(&expected).to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_log_hasher_intermediate_digests() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_384).unwrap();
+        let entries: [&[u8]; 3] = [b"first entry\n", &[0xAB; 120], b"third\n"];
+
+        let mut log = hasher.log_hasher();
+        let mut so_far = Vec::new();
+        for entry in entries {
+            so_far.extend_from_slice(entry);
+            let digest = log.append(entry).await.unwrap();
+            assert_eq!(digest, Sha3_384::digest(&so_far).to_vec());
+        }
+        assert_eq!(log.entries(), 3);
+    }
+
+    // Unsupported variant tests
+    #[tokio::test]
+    async fn test_unsupported_variant_routes_batches_to_cpu() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_shader_support(|_| false);
+
+        let inputs = vec![b"unsupported".as_slice(), b"on the gpu!".as_slice()];
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+        assert_eq!(hasher.hash_batch_grouped(&inputs).await.unwrap(), expected);
+
+        let mut stream = hasher.stream();
+        stream.update(b"unsupported");
+        let result = stream.finalize().await;
+        assert!(matches!(result, Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Sha3_256))));
+    }
+
+    #[tokio::test]
+    async fn test_shake_batches_match_reference() {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+        use sha3_core::BatchHashParams;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let inputs = vec![b"shake".as_slice()];
+        let params = BatchHashParams::new(Sha3Variant::Shake128, 1, 5).with_output_length(40);
+        let result = hasher.hash_batch_with_params(&inputs, &params).await.unwrap();
+
+        let mut reference = sha3::Shake128::default();
+        reference.update(inputs[0]);
+        let mut expected = vec![0u8; 40];
+        reference.finalize_xof().read(&mut expected);
+        assert_eq!(result, expected);
+    }
+
+    /// Squeeze `input` as one `output_len`-byte read and as successive `read_len`-byte reads,
+    /// panicking unless both (and the reference XOF read the same way) agree
+    ///
+    /// Each incremental read re-squeezes up to its end offset and keeps the new bytes, so
+    /// reads crossing a rate-block boundary exercise the state carried between permutations.
+    async fn check_squeeze_consistency(
+        hasher: &GpuSha3Hasher,
+        input: &[u8],
+        output_len: usize,
+        read_len: usize,
+    ) -> Result<(), GpuSha3Error> {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+
+        let squeeze = |len: usize| {
+            let params = sha3_core::BatchHashParams::new(hasher.variant(), 1, input.len())
+                .with_output_length(len);
+            async move { hasher.hash_batch_with_params(&[input], &params).await }
+        };
+        let one_shot = squeeze(output_len).await?;
+
+        let mut incremental = Vec::with_capacity(output_len);
+        while incremental.len() < output_len {
+            let end = (incremental.len() + read_len).min(output_len);
+            let prefix = squeeze(end).await?;
+            incremental.extend_from_slice(&prefix[incremental.len()..]);
+        }
+        assert_eq!(
+            incremental,
+            one_shot,
+            "{:?}: {read_len}-byte reads diverge from a {output_len}-byte read",
+            hasher.variant()
+        );
+
+        let mut reader: Box<dyn XofReader> = match hasher.variant() {
+            Sha3Variant::Shake128 => {
+                Box::new(sha3::Shake128::default().chain(input).finalize_xof())
+            }
+            Sha3Variant::Shake256 => {
+                Box::new(sha3::Shake256::default().chain(input).finalize_xof())
+            }
+            variant => panic!("{variant:?} is not an XOF"),
+        };
+        let mut reference = vec![0u8; output_len];
+        for chunk in reference.chunks_mut(read_len) {
+            reader.read(chunk);
+        }
+        assert_eq!(one_shot, reference, "{:?}: output diverges from reference", hasher.variant());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shake_incremental_reads_match_one_shot() {
+        for variant in [Sha3Variant::Shake128, Sha3Variant::Shake256] {
+            let context = GpuContext::new().await.unwrap();
+            let hasher = GpuSha3Hasher::new(context, variant).unwrap();
+            let rate = variant.rate_bytes();
+            let cases = [
+                (b"".as_slice(), 32, 1),
+                (b"abc".as_slice(), rate, 7),
+                (b"abc".as_slice(), rate + 1, rate),
+                (&[0x5a; 100][..], 2 * rate + 17, 13),
+                (&[0xa5; 100][..], 3 * rate, rate - 1),
+            ];
+            for (input, output_len, read_len) in cases {
+                check_squeeze_consistency(&hasher, input, output_len, read_len).await.unwrap();
+            }
+        }
+    }
+
+    // Backend trait tests
+    struct MockBackend;
+
+    impl Sha3Backend for MockBackend {
+        fn hash_batch<'a>(
+            &'a self,
+            inputs: &'a [&'a [u8]],
+            params: &'a sha3_core::BatchHashParams,
+        ) -> BackendFuture<'a> {
+            let output = vec![0xab; inputs.len() * params.variant.output_bytes()];
+            Box::pin(async move { Ok(output) })
+        }
+
+        fn variant(&self) -> Sha3Variant {
+            Sha3Variant::Sha3_256
+        }
+    }
+
+    /// Backend-agnostic caller: hash every input with the backend's default variant
+    async fn hash_with(backend: &dyn Sha3Backend, inputs: &[&[u8]]) -> Vec<u8> {
+        let params = sha3_core::BatchHashParams::new(backend.variant(), inputs.len(), 3);
+        backend.hash_batch(inputs, &params).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_backends_share_caller_code() {
+        let inputs = [b"abc".as_slice(), b"xyz".as_slice()];
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+
+        let context = GpuContext::new().await.unwrap();
+        let backends: Vec<Box<dyn Sha3Backend>> = vec![
+            Box::new(GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap()),
+            Box::new(CpuSha3Hasher::new(Sha3Variant::Sha3_256)),
+        ];
+        for backend in &backends {
+            assert_eq!(hash_with(backend.as_ref(), &inputs).await, expected);
+        }
+        assert_eq!(hash_with(&MockBackend, &inputs).await, vec![0xab; 64]);
+
+        let cpu = CpuSha3Hasher::new(Sha3Variant::Sha3_256);
+        assert_eq!(cpu.hash_batch(&inputs).await.unwrap(), expected);
+        assert!(cpu.hash_batch(&[b"ab", b"abc"]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_timed_matches_hash_batch() {
+        let context = GpuContext::new().await.unwrap();
+        let timestamps = context.supports_timestamps();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let owned: Vec<[u8; 8]> = (0..300u64).map(u64::to_le_bytes).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+
+        let (digests, kernel_time) = hasher.hash_batch_timed(&inputs).await.unwrap();
+        assert_eq!(digests, hasher.hash_batch(&inputs).await.unwrap());
+        if !timestamps {
+            assert_eq!(kernel_time, std::time::Duration::ZERO);
+        }
+        assert!(hasher.hash_batch_timed(&[]).await.unwrap().0.is_empty());
+        assert!(hasher.hash_batch_timed(&[b"ab", b"abc"]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_fallback_hasher_matches_either_way() {
+        let inputs = [b"abc".as_slice(), b"xyz".as_slice()];
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+
+        let gpu = GpuSha3Hasher::new_with_fallback(Sha3Variant::Sha3_256).await;
+        assert!(gpu.is_gpu());
+        let cpu = FallbackSha3Hasher::from_context(
+            Err(GpuSha3Error::AdapterNotFound("none".into())),
+            Sha3Variant::Sha3_256,
+        );
+        assert!(!cpu.is_gpu());
+
+        for hasher in [&gpu, &cpu] {
+            assert_eq!(hasher.variant(), Sha3Variant::Sha3_256);
+            assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+            assert_eq!(hash_with(hasher, &inputs).await, expected);
+            assert!(hasher.hash_batch(&[b"ab", b"abc"]).await.is_err());
+            assert!(hasher.hash_batch(&[]).await.unwrap().is_empty());
+        }
+    }
+
+    // Session telemetry tests
+    #[tokio::test]
+    async fn test_session_stats_track_batches() {
+        let context = GpuContext::new().await.unwrap();
+        let mut hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        assert_eq!(hasher.session_stats().batches, 0);
+        assert_eq!(hasher.session_stats().average_batch_size, 0.0);
+
+        // Persistent (fits 1000 hashes), persistent again (grown to 1001), then CPU (over the
+        // threshold)
+        hasher.hash_batch(&[&[1u8; 32][..]; 10]).await.unwrap();
+        let owned: Vec<[u8; 8]> = (0..1001u64).map(u64::to_le_bytes).collect();
+        let distinct: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        hasher.hash_batch(&distinct).await.unwrap();
+        hasher.set_gpu_threshold(8);
+        hasher.hash_batch(&[&[3u8; 16][..]; 5]).await.unwrap();
+
+        let stats = hasher.session_stats();
+        assert_eq!(
+            stats,
+            SessionStats {
+                batches: 3,
+                hashes: 1016,
+                bytes: 320 + 8008 + 80,
+                persistent_batches: 2,
+                dynamic_batches: 0,
+                cpu_batches: 1,
+                average_batch_size: 1016.0 / 3.0,
+            }
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_value(&stats).unwrap();
+            assert_eq!(json["hashes"], 1016);
+            assert_eq!(json["persistent_batches"], 2);
+        }
+
+        hasher.reset_stats();
+        assert_eq!(hasher.session_stats().hashes, 0);
+        assert_eq!(hasher.session_stats().batches, 0);
+    }
+
+    // Empty input tests
+    #[tokio::test]
+    async fn test_batch_of_empty_inputs_on_gpu() {
+        let empty_digest = Sha3_256::digest(b"").to_vec();
+        let inputs: Vec<&[u8]> = vec![b"", b"", b""];
+
+        // Default, adaptive, zero-width persistent and dynamic-only buffer setups
+        let context = GpuContext::new().await.unwrap();
+        let default = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let context = GpuContext::new().await.unwrap();
+        let adaptive = GpuSha3Hasher::new_adaptive(context, Sha3Variant::Sha3_256).unwrap();
+        let context = GpuContext::new().await.unwrap();
+        let zero_width = GpuSha3Hasher::with_persistent_buffers(
+            context,
+            Sha3Variant::Sha3_256,
+            Some((4, 0, 32)),
+        )
+        .unwrap();
+        let context = GpuContext::new().await.unwrap();
+        let dynamic = GpuSha3Hasher::builder().persistent(false).build(context).unwrap();
+
+        for hasher in [&default, &adaptive, &zero_width, &dynamic] {
+            let digests = hasher.hash_batch_vec(&inputs).await.unwrap();
+            assert_eq!(digests, vec![empty_digest.clone(); 3]);
+            assert_eq!(hasher.hash_batch(&[]).await.unwrap(), Vec::<u8>::new());
+            assert_eq!(hasher.hash_batch(&[b""]).await.unwrap(), empty_digest);
+            // Nothing was routed to the CPU
+            assert_eq!(hasher.session_stats().cpu_batches, 0);
+        }
+
+        // Empty inputs then non-empty ones through the same buffers
+        let inputs: Vec<&[u8]> = vec![b"abc"; 3];
+        let expected: Vec<u8> = Sha3_256::digest(b"abc").repeat(3);
+        assert_eq!(adaptive.hash_batch(&inputs).await.unwrap(), expected);
+        assert_eq!(zero_width.hash_batch(&inputs).await.unwrap(), expected);
+    }
+
+    // Identical-input fast path tests
+    #[tokio::test]
+    async fn test_identical_inputs_hashed_once() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let input = b"same input every time";
+        let expected = Sha3_256::digest(input).repeat(1000);
+
+        // Both shared and separately allocated copies are detected
+        let shared = vec![&input[..]; 1000];
+        assert_eq!(hasher.hash_batch(&shared).await.unwrap(), expected);
+        let owned = vec![input.to_vec(); 1000];
+        let copies: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        assert_eq!(hasher.hash_batch(&copies).await.unwrap(), expected);
+        assert_eq!(hasher.session_stats().hashes, 2);
+
+        // One differing input takes the regular path
+        let mut owned = owned;
+        owned[500][0] ^= 1;
+        let mixed: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let result = hasher.hash_batch(&mixed).await.unwrap();
+        assert_eq!(result[500 * 32..501 * 32], Sha3_256::digest(&owned[500])[..]);
+        assert_eq!(hasher.session_stats().hashes, 1002);
+    }
+
+    // GPU routing threshold tests
+    #[tokio::test]
+    async fn test_gpu_threshold_routes_to_cpu() {
+        let inputs = [&[0x42u8; 64][..]; 4];
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+
+        // Adaptive hashers only allocate buffers once a batch reaches the GPU
+        let context = GpuContext::new().await.unwrap();
+        let mut hasher = GpuSha3Hasher::new_adaptive(context, Sha3Variant::Sha3_256).unwrap();
+        assert_eq!(hasher.gpu_threshold(), usize::MAX);
+        hasher.set_gpu_threshold(32);
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+        assert_eq!(hasher.current_capacity(), None);
+
+        hasher.set_gpu_threshold(usize::MAX);
+        assert_eq!(hasher.gpu_threshold(), usize::MAX);
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+        assert_eq!(hasher.current_capacity(), Some((4, 64, 32)));
+    }
+
+    // Single-hash fast path tests
+    #[tokio::test]
+    async fn test_hash_one_fast_matches_hash_batch() {
+        for prefix in [None, Some(b"prefix".to_vec())] {
+            let context = GpuContext::new().await.unwrap();
+            let mut hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_512).unwrap();
+            if let Some(prefix) = prefix {
+                hasher = hasher.with_fixed_prefix(prefix);
+            }
+            // Lengths around the 4-byte write granularity, including empty input
+            for input in [&b""[..], b"a", b"abcd", b"abcde", &[7u8; 71]] {
+                let expected = hasher.hash_batch(&[input]).await.unwrap();
+                assert_eq!(hasher.hash_one_fast(input).await.unwrap(), expected);
+            }
+        }
+
+        // SHAKE takes the batch path (and its missing-output-length error)
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        assert!(hasher.hash_one_fast(b"abc").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_hash_one_fixed_returns_arrays() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let digest: [u8; 32] = hasher.hash256(b"abc").await.unwrap();
+        assert_eq!(digest[..], Sha3_256::digest(b"abc")[..]);
+        assert_eq!(hasher.hash_one_fixed::<32>(b"").await.unwrap()[..], Sha3_256::digest(b"")[..]);
+
+        // The array size must be the variant's output size
+        assert!(matches!(
+            hasher.hash512(b"abc").await,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(64)))
+        ));
+        assert!(hasher.hash_one_fixed::<31>(b"abc").await.is_err());
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_224).unwrap();
+        assert_eq!(hasher.hash224(b"abc").await.unwrap()[..], Sha3_224::digest(b"abc")[..]);
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_384).unwrap();
+        assert_eq!(hasher.hash384(b"abc").await.unwrap()[..], Sha3_384::digest(b"abc")[..]);
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Keccak512).unwrap();
+        assert_eq!(hasher.hash512(b"abc").await.unwrap()[..], Keccak512::digest(b"abc")[..]);
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        assert!(hasher.hash_one_fixed::<32>(b"abc").await.is_err());
+    }
+
+    // Texture hashing tests
+    fn upload_texture(
+        context: &GpuContext,
+        format: wgpu::TextureFormat,
+        size: wgpu::Extent3d,
+        data: &[u8],
+    ) -> wgpu::Texture {
+        let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some("Test Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let row_bytes = data.len() as u32 / (size.height * size.depth_or_array_layers);
+        context.queue().write_texture(
+            texture.as_image_copy(),
+            data,
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(row_bytes),
+                rows_per_image: Some(size.height),
+            },
+            size,
+        );
+        texture
+    }
+
+    #[tokio::test]
+    async fn test_hash_texture_matches_pixel_bytes() {
+        use wgpu::{Extent3d, Origin3d, TextureFormat};
+
+        // 32-byte rows are copied one by one, 256-byte rows in one go (spanning whole
+        // blocks), and 32 KiB spans several absorb dispatches
+        for (width, height) in [(8u32, 3u32), (64, 2), (128, 64)] {
+            let context = GpuContext::new().await.unwrap();
+            let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+            let size = Extent3d { width, height, depth_or_array_layers: 1 };
+            let pixels: Vec<u8> = (0..width * height * 4).map(|i| (i * 7) as u8).collect();
+            let texture =
+                upload_texture(hasher.context(), TextureFormat::Rgba8Unorm, size, &pixels);
+
+            let digest = hasher.hash_texture(&texture, TextureRegion::full(&texture)).await;
+            assert_eq!(digest.unwrap(), Sha3_256::digest(&pixels).to_vec());
+
+            let hasher = hasher.with_fixed_prefix(b"tex:".to_vec());
+            let expected = Sha3_256::digest([&b"tex:"[..], &pixels].concat()).to_vec();
+            let digest = hasher.hash_texture(&texture, TextureRegion::full(&texture)).await;
+            assert_eq!(digest.unwrap(), expected);
+        }
+
+        // Subregion of single-byte texels with an odd row length
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let size = Extent3d { width: 7, height: 4, depth_or_array_layers: 1 };
+        let pixels: Vec<u8> = (0..28u8).collect();
+        let texture = upload_texture(hasher.context(), TextureFormat::R8Unorm, size, &pixels);
+        let region = TextureRegion {
+            origin: Origin3d { x: 2, y: 1, z: 0 },
+            size: Extent3d { width: 3, height: 2, depth_or_array_layers: 1 },
+        };
+        let expected: Vec<u8> = [&pixels[9..12], &pixels[16..19]].concat();
+        let digest = hasher.hash_texture(&texture, region).await.unwrap();
+        assert_eq!(digest, Sha3_256::digest(&expected).to_vec());
+
+        // Out-of-bounds regions are rejected before any copy is recorded
+        let region = TextureRegion { origin: Origin3d { x: 5, y: 0, z: 0 }, ..region };
+        assert!(hasher.hash_texture(&texture, region).await.is_err());
+        // ...including ones whose end overflows u32
+        let region = TextureRegion { origin: Origin3d { x: 2, y: u32::MAX, z: 0 }, ..region };
+        assert!(hasher.hash_texture(&texture, region).await.is_err());
+    }
+
+    // GPU-retained output tests
+    #[tokio::test]
+    async fn test_retained_merkle_root_matches_cpu() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let data: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 40]).collect();
+        let leaves: Vec<&[u8]> = data.iter().map(Vec::as_slice).collect();
+
+        let mut layer = hasher.hash_batch_retained(&leaves).unwrap();
+        assert_eq!((layer.num_hashes(), layer.digest_len()), (8, 32));
+        assert_eq!(hasher.read(&layer).await.unwrap(), hasher.hash_batch(&leaves).await.unwrap());
+        // Each layer hashes sibling pairs of the one below without leaving the GPU
+        while layer.num_hashes() > 1 {
+            layer = hasher.hash_retained(&layer, 2 * layer.digest_len()).unwrap();
+        }
+        let root = hasher.read(&layer).await.unwrap();
+
+        let mut expected: Vec<Vec<u8>> =
+            data.iter().map(|d| Sha3_256::digest(d).to_vec()).collect();
+        while expected.len() > 1 {
+            expected =
+                expected.chunks(2).map(|pair| Sha3_256::digest(pair.concat()).to_vec()).collect();
+        }
+        assert_eq!(root, expected[0]);
+
+        // Inputs must split evenly and share a length
+        let layer = hasher.hash_batch_retained(&leaves).unwrap();
+        assert!(matches!(
+            hasher.hash_retained(&layer, 48),
+            Err(GpuSha3Error::InvalidInputLength(48))
+        ));
+        assert!(hasher.hash_retained(&layer, 0).is_err());
+        assert!(hasher.hash_batch_retained(&[b"a", b"bc"]).is_err());
+        let empty = hasher.hash_batch_retained(&[]).unwrap();
+        assert!(empty.is_empty());
+        assert!(hasher.read(&empty).await.unwrap().is_empty());
+    }
+
+    fn cpu_merkle_root<D: Digest>(leaves: &[Vec<u8>]) -> Vec<u8> {
+        let mut layer: Vec<Vec<u8>> = leaves.iter().map(|leaf| D::digest(leaf).to_vec()).collect();
+        while layer.len() > 1 {
+            if layer.len() % 2 == 1 {
+                layer.push(layer.last().unwrap().clone());
+            }
+            layer = layer.chunks(2).map(|pair| D::digest(pair.concat()).to_vec()).collect();
+        }
+        layer.remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_merkle_root_matches_cpu() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+
+        // 300 leaves leave odd layers of 75, 19, 5 and 3 nodes on the way up
+        let data: Vec<Vec<u8>> = (0..300u32).map(|i| i.to_le_bytes().repeat(8)).collect();
+        let leaves: Vec<&[u8]> = data.iter().map(Vec::as_slice).collect();
+        let root = hasher.merkle_root(&leaves).await.unwrap();
+        assert_eq!(root, cpu_merkle_root::<Sha3_256>(&data));
+
+        // Mixed-length leaves, and a single leaf
+        let data: Vec<Vec<u8>> = (0..7usize).map(|i| vec![i as u8; i * 30]).collect();
+        let leaves: Vec<&[u8]> = data.iter().map(Vec::as_slice).collect();
+        assert_eq!(hasher.merkle_root(&leaves).await.unwrap(), cpu_merkle_root::<Sha3_256>(&data));
+        assert_eq!(hasher.merkle_root(&[b"abc"]).await.unwrap(), Sha3_256::digest(b"abc").to_vec());
+        assert!(matches!(hasher.merkle_root(&[]).await, Err(GpuSha3Error::InvalidInputLength(0))));
+
+        // Every level uses the hasher's variant
+        let context = GpuContext::new().await.unwrap();
+        let keccak = GpuSha3Hasher::new(context, Sha3Variant::Keccak256).unwrap();
+        let data: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 32]).collect();
+        let leaves: Vec<&[u8]> = data.iter().map(Vec::as_slice).collect();
+        assert_eq!(keccak.merkle_root(&leaves).await.unwrap(), cpu_merkle_root::<Keccak256>(&data));
+    }
+
+    // GPU-resident input tests
+    #[tokio::test]
+    async fn test_hash_batch_from_buffer_reuses_bind_group() {
+        use sha3_core::BatchHashParams;
+        use wgpu::util::DeviceExt;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let data: Vec<u8> = (0..128u8).collect();
+        let buffer =
+            hasher.context().device().create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Test Resident Inputs"),
+                contents: &data,
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+
+        // The same dataset under two shapes shares one bind group
+        for (num_hashes, input_length) in [(4, 32), (2, 64)] {
+            let params = BatchHashParams::new(Sha3Variant::Sha3_256, num_hashes, input_length);
+            let result = hasher.hash_batch_from_buffer(&buffer, &params).await.unwrap();
+            let expected: Vec<u8> = data.chunks(input_length).flat_map(Sha3_256::digest).collect();
+            assert_eq!(result, expected);
+        }
+        assert_eq!(hasher.bind_group_builds(), 1);
+
+        hasher.invalidate_bind_group_cache().await;
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 1, 128);
+        let result = hasher.hash_batch_from_buffer(&buffer, &params).await.unwrap();
+        assert_eq!(result, Sha3_256::digest(&data).to_vec());
+        assert_eq!(hasher.bind_group_builds(), 2);
+
+        // Claiming more input than the buffer holds is rejected
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 3, 64);
+        assert!(matches!(
+            hasher.hash_batch_from_buffer(&buffer, &params).await,
+            Err(GpuSha3Error::InvalidInputLength(192))
+        ));
+
+        // Sizes that overflow, or outputs the device can't bind, are rejected up front
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, usize::MAX, 2);
+        assert!(matches!(
+            hasher.hash_batch_from_buffer(&buffer, &params).await,
+            Err(GpuSha3Error::InvalidInputLength(2))
+        ));
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, usize::MAX / 16, 0);
+        assert!(matches!(
+            hasher.hash_batch_from_buffer(&buffer, &params).await,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(32)))
+        ));
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, u32::MAX as usize, 0);
+        assert!(matches!(
+            hasher.hash_batch_from_buffer(&buffer, &params).await,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(32)))
+        ));
+    }
+
+    // Recorded dispatch tests
+    #[tokio::test]
+    async fn test_record_dispatch_into_user_encoder() {
+        use sha3_core::BatchHashParams;
+        use wgpu::util::DeviceExt;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let device = hasher.context().device();
+        let data: Vec<u8> = (0..=255u8).collect();
+        let input = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Test Recorded Inputs"),
+            contents: &data,
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let output = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Test Recorded Outputs"),
+            size: 8 * 32,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Test Recorded Staging"),
+            size: 8 * 32,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 8, 32);
+        let bind_group = hasher.create_dispatch_bind_group(&input, &output, &params).unwrap();
+        let mut encoder = device.create_command_encoder(&Default::default());
+        hasher.record_dispatch(&mut encoder, &bind_group, 8);
+        encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, 8 * 32);
+        hasher.context().queue().submit(Some(encoder.finish()));
+
+        staging.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let result = staging.slice(..).get_mapped_range().to_vec();
+        let expected: Vec<u8> = data.chunks(32).flat_map(Sha3_256::digest).collect();
+        assert_eq!(result, expected);
+
+        // Buffers too small for the described batch are rejected
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 9, 32);
+        assert!(matches!(
+            hasher.create_dispatch_bind_group(&input, &output, &params),
+            Err(GpuSha3Error::InvalidInputLength(288))
+        ));
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 16, 16);
+        assert!(matches!(
+            hasher.create_dispatch_bind_group(&input, &output, &params),
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(512)))
+        ));
+
+        // Sizes that overflow are rejected rather than wrapping past the checks
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, usize::MAX, 2);
+        assert!(matches!(
+            hasher.create_dispatch_bind_group(&input, &output, &params),
+            Err(GpuSha3Error::InvalidInputLength(2))
+        ));
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, usize::MAX / 16, 0);
+        assert!(matches!(
+            hasher.create_dispatch_bind_group(&input, &output, &params),
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_user_built_bind_group_matches_layout() {
+        use sha3_core::BatchHashParams;
+        use wgpu::util::DeviceExt;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let device = hasher.context().device();
+        let (num_hashes, input_length, output_length) = (5, 37, 45);
+        let data: Vec<u8> = (0..num_hashes * input_length).map(|i| (i * 13) as u8).collect();
+        let params = BatchHashParams::new(Sha3Variant::Shake256, num_hashes, input_length)
+            .with_output_length(output_length);
+        let gpu_params = GpuHashParams::for_batch(&params).unwrap();
+        assert_eq!(gpu_params.as_bytes().len(), 32);
+        assert_eq!(gpu_params.as_bytes()[..4], (num_hashes as u32).to_le_bytes());
+
+        let input = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Test User Inputs"),
+            contents: &data,
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        let uniform = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Test User Params"),
+            contents: gpu_params.as_bytes(),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let output_size = ((num_hashes * output_length + 15) / 16 * 16) as u64;
+        let output = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Test User Outputs"),
+            size: output_size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Test User Staging"),
+            size: output_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Test User Bind Group"),
+            layout: hasher.bind_group_layout(),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: input.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: output.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: uniform.as_entire_binding() },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: hasher.initial_state_buffer().as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        hasher.record_dispatch(&mut encoder, &bind_group, num_hashes);
+        encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, output_size);
+        hasher.context().queue().submit(Some(encoder.finish()));
+
+        staging.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+        let result = staging.slice(..).get_mapped_range()[..num_hashes * output_length].to_vec();
+        let refs: Vec<&[u8]> = data.chunks(input_length).collect();
+        assert_eq!(result, hasher.hash_batch_with_params(&refs, &params).await.unwrap());
+    }
+
+    // Prepared hasher tests
+    #[tokio::test]
+    async fn test_prepared_matches_hash_batch() {
+        use sha3_core::BatchHashParams;
+
+        let owned: Vec<Vec<u8>> = (0..20u8).map(|i| vec![i; 33]).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let shape = BatchHashParams::new(Sha3Variant::Sha3_256, inputs.len(), 33);
+
+        for prefix in [None, Some(b"prefix".to_vec())] {
+            let context = GpuContext::new().await.unwrap();
+            let mut hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+            if let Some(prefix) = prefix {
+                hasher = hasher.with_fixed_prefix(prefix);
+            }
+            let expected = hasher.hash_batch(&inputs).await.unwrap();
+
+            let prepared = hasher.prepared(&shape).unwrap();
+            // Repeated calls reuse the same buffers
+            for _ in 0..3 {
+                assert_eq!(prepared.hash(&inputs).await.unwrap(), expected);
+            }
+            assert!(prepared.hash(&inputs[1..]).await.is_err());
+            assert!(prepared.hash(&[&[0u8; 32][..]; 20]).await.is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prepared_schedule_matches_reference() {
+        use sha3_core::BatchHashParams;
+
+        let owned: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 64]).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let shape = BatchHashParams::new(Sha3Variant::Sha3_256, inputs.len(), 64);
+
+        // 64-byte inputs alone, behind a tail filling exactly one rate block, and behind
+        // a tail spilling into a second block
+        for (prefix_len, blocks) in [(0, 1), (72, 2), (100, 2)] {
+            let prefix = vec![0xA5u8; prefix_len];
+            let context = GpuContext::new().await.unwrap();
+            let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+                .unwrap()
+                .with_fixed_prefix(prefix.clone());
+            let prepared = hasher.prepared(&shape).unwrap();
+            assert_eq!(prepared.schedule().absorb_blocks(), blocks);
+            assert_eq!(prepared.schedule().padding_offset(), prefix_len + 64);
+
+            let expected: Vec<u8> = inputs
+                .iter()
+                .flat_map(|input| Sha3_256::digest([&prefix[..], input].concat()))
+                .collect();
+            assert_eq!(prepared.hash(&inputs).await.unwrap(), expected);
+        }
+    }
+
+    // Length-prefixed output tests
+    #[tokio::test]
+    async fn test_hash_batch_framed_output_round_trips() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_384).unwrap();
+        let inputs = vec![b"one".as_slice(), b"two".as_slice(), b"six".as_slice()];
+        let framed = hasher.hash_batch_framed_output(&inputs).await.unwrap();
+        assert_eq!(framed.len(), inputs.len() * (4 + 48));
+        assert_eq!(framed[..4], [0, 0, 0, 48]);
+
+        // Parse the records back off the wire
+        let mut rest = &framed[..];
+        for input in &inputs {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+            assert_eq!(&rest[4..4 + length], Sha3_384::digest(input).as_slice());
+            rest = &rest[4 + length..];
+        }
+        assert!(rest.is_empty());
+        assert!(hasher.hash_batch_framed_output(&[]).await.unwrap().is_empty());
+    }
+
+    // String map tests
+    #[tokio::test]
+    async fn test_hash_strings_to_map() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let map = hasher.hash_strings_to_map(&["alpha", "", "beta", "alpha"]).await.unwrap();
+
+        // "alpha" is hashed once
+        assert_eq!(map.len(), 3);
+        assert_eq!(hasher.session_stats().hashes, 3);
+        for s in ["alpha", "", "beta"] {
+            assert_eq!(map[s], hex::encode(Sha3_256::digest(s)));
+        }
+        assert_eq!(map[""], "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a");
+        assert!(hasher.hash_strings_to_map(&[]).await.unwrap().is_empty());
+    }
+
+    // Commitment tests
+    #[tokio::test]
+    async fn test_hash_commit_matches_two_pass() {
+        let two_pass = |prefix: &[u8], input: &[u8]| {
+            let first = Sha3_256::digest([prefix, input].concat());
+            Sha3_256::digest([prefix, input, &first].concat()).to_vec()
+        };
+        // 13-byte inputs leave every interleaved row unaligned
+        let owned: Vec<Vec<u8>> = (0..5u8).map(|i| vec![i; 13]).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let expected: Vec<Vec<u8>> = inputs.iter().map(|input| two_pass(b"", input)).collect();
+        assert_eq!(hasher.hash_commit(&inputs).await.unwrap(), expected);
+        assert!(hasher.hash_commit(&[]).await.unwrap().is_empty());
+        assert!(hasher.hash_commit(&[b"ab", b"abc"]).await.is_err());
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_fixed_prefix(b"domain".to_vec());
+        let expected: Vec<Vec<u8>> =
+            inputs.iter().map(|input| two_pass(b"domain", input)).collect();
+        assert_eq!(hasher.hash_commit(&inputs).await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_with_output_lengths() {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let inputs = [b"one".as_slice(), b"two".as_slice(), b"three".as_slice()];
+        let lengths = [16, 200, 0];
+        let result = hasher.hash_batch_with_output_lengths(&inputs, &lengths).await.unwrap();
+        for ((input, length), digest) in inputs.iter().zip(lengths).zip(&result) {
+            let mut expected = vec![0u8; length];
+            sha3::Shake256::default().chain(input).finalize_xof().read(&mut expected);
+            assert_eq!(digest, &expected);
+        }
+        assert!(hasher.hash_batch_with_output_lengths(&inputs, &[16]).await.is_err());
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let result = hasher.hash_batch_with_output_lengths(&inputs[..1], &[8]).await.unwrap();
+        assert_eq!(result[0], Sha3_256::digest(inputs[0])[..8]);
+        assert!(matches!(
+            hasher.hash_batch_with_output_lengths(&inputs[..1], &[33]).await,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(33)))
+        ));
+    }
+
+    // Framed hashing tests
+    #[tokio::test]
+    async fn test_hash_framed_matches_manual_encoding() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let fields = [b"associated data".as_slice(), b"".as_slice(), b"message".as_slice()];
+        let result = hasher.hash_framed(&fields).await.unwrap();
+
+        let mut framed = Vec::new();
+        for field in fields {
+            framed.push(1);
+            framed.push((field.len() * 8) as u8);
+            framed.extend_from_slice(field);
+        }
+        assert_eq!(result, Sha3_256::digest(&framed).to_vec());
+
+        // Moving bytes across a field boundary changes the digest
+        let shifted = hasher.hash_framed(&[b"associated", b" data", b"message"]).await.unwrap();
+        assert_ne!(result, shifted);
+    }
+
+    #[tokio::test]
+    async fn test_persistent_buffers_validated_against_device() {
+        let context = GpuContext::new().await.unwrap();
+        let limit = context.limits().max_storage_buffer_binding_size as usize;
+        let oversized = Some((2, limit, 32));
+        assert!(matches!(
+            GpuSha3Hasher::with_persistent_buffers(context, Sha3Variant::Sha3_256, oversized),
+            Err(GpuSha3Error::InvalidInputLength(length)) if length == limit
+        ));
+        let context = GpuContext::new().await.unwrap();
+        let oversized = Some((limit / 64 + 1, 8, 64));
+        assert!(matches!(
+            GpuSha3Hasher::with_persistent_buffers(context, Sha3Variant::Sha3_512, oversized),
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(64)))
+        ));
+
+        // Small per-input capacities still hash longer inputs through dynamic buffers
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::with_persistent_buffers(
+            context,
+            Sha3Variant::Sha3_256,
+            Some((4, 16, 32)),
+        )
+        .unwrap();
+        let long = [7u8; 300];
+        assert_eq!(hasher.hash_batch(&[&long]).await.unwrap(), Sha3_256::digest(long).to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_select_matches_full_batch() {
+        let owned: Vec<[u8; 8]> = (0..1200u64).map(u64::to_le_bytes).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let indices = [1199, 0, 7, 7, 600];
+
+        for variant in [Sha3Variant::Sha3_224, Sha3Variant::Sha3_512] {
+            let context = GpuContext::new().await.unwrap();
+            let hasher =
+                GpuSha3Hasher::new(context, variant).unwrap().with_fixed_prefix(b"tag".to_vec());
+            let full = hasher.hash_batch_vec(&inputs).await.unwrap();
+            let selected = hasher.hash_batch_select(&inputs, &indices).await.unwrap();
+            let expected: Vec<Vec<u8>> = indices.iter().map(|&i| full[i].clone()).collect();
+            assert_eq!(selected, expected);
+
+            assert!(hasher.hash_batch_select(&inputs, &[]).await.unwrap().is_empty());
+            assert!(matches!(
+                hasher.hash_batch_select(&inputs, &[3, 1200]).await,
+                Err(GpuSha3Error::InvalidInputLength(1200))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_hash_with_ids_keeps_ids_with_digests() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        // Repeated data under different ids, and a repeated id with different data
+        let items = [(7u64, b"alpha".as_slice()), (3, b"beta, longer"), (9, b"alpha"), (7, b"")];
+        let results = hasher.hash_with_ids(&items).await.unwrap();
+
+        assert_eq!(results.len(), items.len());
+        for ((id, data), (result_id, digest)) in items.iter().zip(&results) {
+            assert_eq!(id, result_id);
+            assert_eq!(digest, &Sha3_256::digest(data).to_vec());
+        }
+        assert!(hasher.hash_with_ids::<u64>(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hash_kv_pairs_is_order_independent() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let pairs = [(b"name".as_slice(), b"alice".as_slice()), (b"id", b"42"), (b"", b"")];
+        let digest = hasher.hash_kv_pairs(&pairs).await.unwrap();
+
+        let expected = Sha3_256::digest(
+            [&[1, 0, 1, 0][..], b"\x01\x02id\x01\x0242", b"\x01\x04name\x01\x05alice"].concat(),
+        );
+        assert_eq!(digest, expected.to_vec());
+
+        let reordered = [pairs[2], pairs[0], pairs[1]];
+        assert_eq!(hasher.hash_kv_pairs(&reordered).await.unwrap(), digest);
+
+        // Moving bytes between a key and its value changes the digest
+        let shifted = [(b"name".as_slice(), b"alice".as_slice()), (b"i", b"d42"), (b"", b"")];
+        assert_ne!(hasher.hash_kv_pairs(&shifted).await.unwrap(), digest);
+    }
+
+    // KMAC tests
+    #[tokio::test]
+    async fn test_kmac_matches_nist_samples() {
+        let key: Vec<u8> = (0x40..0x60).collect();
+        let short = [0u8, 1, 2, 3];
+        let long: Vec<u8> = (0..200).collect();
+        let tagged = b"My Tagged Application".as_slice();
+
+        let context = GpuContext::new().await.unwrap();
+        let kmac128 = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        // Samples #2 and #3 (tagged, short and long data) in one call: inputs of different
+        // lengths are grouped
+        let tags = kmac128.kmac(&key, &[&short, &long], 32, tagged).await.unwrap();
+        assert_eq!(
+            hex::encode(&tags[0]),
+            "3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5"
+        );
+        assert_eq!(
+            hex::encode(&tags[1]),
+            "1f5b4e6cca02209e0dcb5ca635b89a15e271ecc760071dfd805faa38f9729230"
+        );
+        // Sample #1 (untagged)
+        let untagged = kmac128.kmac(&key, &[&short], 32, b"").await.unwrap();
+        assert_eq!(
+            hex::encode(&untagged[0]),
+            "e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e"
+        );
+
+        let context = GpuContext::new().await.unwrap();
+        let kmac256 = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let tags = kmac256.kmac(&key, &[&short], 64, tagged).await.unwrap();
+        assert_eq!(
+            hex::encode(&tags[0]),
+            "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7\
+             f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd"
+        );
+        let tags = kmac256.kmac(&key, &[&long], 64, b"").await.unwrap();
+        assert_eq!(
+            hex::encode(&tags[0]),
+            "75358cf39e41494e949707927cee0af20a3ff553904c86b08f21cc414bcfd691\
+             589d27cf5e15369cbbff8b9a4c2eb17800855d0235ff635da82533ec6b759b69"
+        );
+
+        let context = GpuContext::new().await.unwrap();
+        let sha3 = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let result = sha3.kmac(&key, &[&short], 32, b"").await;
+        assert!(matches!(result, Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Sha3_256))));
+        let result = kmac256.kmac(&key, &[&short], 0, b"").await;
+        assert!(matches!(
+            result,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidOutputLength(0)))
+        ));
+    }
+
+    // HMAC tests
+    fn reference_hmac<D: Digest + sha3::digest::core_api::BlockSizeUser>(
+        key: &[u8],
+        message: &[u8],
+    ) -> Vec<u8> {
+        use hmac::Mac;
+
+        let mut mac = hmac::SimpleHmac::<D>::new_from_slice(key).unwrap();
+        mac.update(message);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_hmac_matches_cpu_reference() {
+        // RFC 4231-style cases: a short key, a short key with a longer message, and a key
+        // longer than every variant's block size, which must be hashed first
+        let long_message = b"Test Using Larger Than Block-Size Key - Hash Key First".as_slice();
+        let cases = [
+            ([0x0b; 20].as_slice(), vec![b"Hi There".as_slice()]),
+            (b"Jefe", vec![b"what do ya want for nothing?", b"Hi There", b""]),
+            (&[0xaa; 200], vec![long_message, &[0xdd; 500]]),
+        ];
+
+        for variant in [
+            Sha3Variant::Sha3_224,
+            Sha3Variant::Sha3_256,
+            Sha3Variant::Sha3_384,
+            Sha3Variant::Sha3_512,
+            Sha3Variant::Keccak256,
+        ] {
+            let context = GpuContext::new().await.unwrap();
+            let hasher = GpuSha3Hasher::new(context, variant).unwrap();
+            for (key, inputs) in &cases {
+                let tags = hasher.hmac(key, inputs).await.unwrap();
+                let expected: Vec<Vec<u8>> = inputs
+                    .iter()
+                    .map(|input| match variant {
+                        Sha3Variant::Sha3_224 => reference_hmac::<Sha3_224>(key, input),
+                        Sha3Variant::Sha3_256 => reference_hmac::<Sha3_256>(key, input),
+                        Sha3Variant::Sha3_384 => reference_hmac::<Sha3_384>(key, input),
+                        Sha3Variant::Sha3_512 => reference_hmac::<Sha3_512>(key, input),
+                        _ => reference_hmac::<Keccak256>(key, input),
+                    })
+                    .collect();
+                assert_eq!(tags, expected, "{variant:?} with a {}-byte key", key.len());
+            }
+        }
+
+        // A fixed prefix doesn't leak into the MAC
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_fixed_prefix(b"domain".to_vec());
+        let tags = hasher.hmac(b"Jefe", &[b"Hi There"]).await.unwrap();
+        assert_eq!(tags, vec![reference_hmac::<Sha3_256>(b"Jefe", b"Hi There")]);
+
+        let context = GpuContext::new().await.unwrap();
+        let shake = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let result = shake.hmac(b"Jefe", &[b"Hi There"]).await;
+        assert!(matches!(result, Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Shake128))));
+    }
+
+    // TupleHash tests
+    #[tokio::test]
+    async fn test_tuple_hash_matches_nist_samples() {
+        let x: Vec<u8> = vec![0x00, 0x01, 0x02];
+        let y: Vec<u8> = (0x10..0x16).collect();
+        let z: Vec<u8> = (0x20..0x29).collect();
+        let app = b"My Tuple App".as_slice();
+
+        let context = GpuContext::new().await.unwrap();
+        let tuple_hash128 = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        // Samples #2 and #3 (a pair and a triple, customized) in one call: tuples of
+        // different encoded lengths are grouped
+        let pair = [x.as_slice(), y.as_slice()];
+        let triple = [x.as_slice(), y.as_slice(), z.as_slice()];
+        let outputs = tuple_hash128.tuple_hash(&[&pair, &triple], 32, app).await.unwrap();
+        assert_eq!(
+            hex::encode(&outputs[0]),
+            "75cdb20ff4db1154e841d758e24160c54bae86eb8c13e7f5f40eb35588e96dfb"
+        );
+        assert_eq!(
+            hex::encode(&outputs[1]),
+            "e60f202c89a2631eda8d4c588ca5fd07f39e5151998deccf973adb3804bb6e84"
+        );
+        // Sample #1 (no customization)
+        let outputs = tuple_hash128.tuple_hash(&[&pair], 32, b"").await.unwrap();
+        assert_eq!(
+            hex::encode(&outputs[0]),
+            "c5d8786c1afb9b82111ab34b65b2c0048fa64e6d48e263264ce1707d3ffc8ed1"
+        );
+
+        // The encoding keeps tuples with equal concatenations apart
+        let (split_a, split_b) = ([b"ab".as_slice(), b"c"], [b"a".as_slice(), b"bc"]);
+        let outputs = tuple_hash128.tuple_hash(&[&split_a, &split_b], 32, b"").await.unwrap();
+        assert_ne!(outputs[0], outputs[1]);
+
+        let context = GpuContext::new().await.unwrap();
+        let tuple_hash256 = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let outputs = tuple_hash256.tuple_hash(&[&pair], 64, b"").await.unwrap();
+        assert_eq!(
+            hex::encode(&outputs[0]),
+            "cfb7058caca5e668f81a12a20a2195ce97a925f1dba3e7449a56f82201ec6073\
+             11ac2696b1ab5ea2352df1423bde7bd4bb78c9aed1a853c78672f9eb23bbe194"
+        );
+
+        let context = GpuContext::new().await.unwrap();
+        let sha3 = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let result = sha3.tuple_hash(&[&pair], 32, b"").await;
+        assert!(matches!(result, Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Sha3_256))));
+    }
+
+    // ParallelHash tests
+    #[tokio::test]
+    async fn test_parallel_hash_matches_nist_samples() {
+        let x: Vec<u8> = [0x00u8, 0x10, 0x20].iter().flat_map(|&row| row..row + 8).collect();
+        let data = b"Parallel Data".as_slice();
+
+        let context = GpuContext::new().await.unwrap();
+        let parallel_hash128 = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let output = parallel_hash128.parallel_hash(&x, 8, 32, b"").await.unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "ba8dc1d1d979331d3f813603c67f72609ab5e44b94a0b8f9af46514454a2b4f5"
+        );
+        let output = parallel_hash128.parallel_hash(&x, 8, 32, data).await.unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "fc484dcb3f84dceedc353438151bee58157d6efed0445a81f165e495795b7206"
+        );
+
+        let context = GpuContext::new().await.unwrap();
+        let parallel_hash256 = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let output = parallel_hash256.parallel_hash(&x, 8, 64, b"").await.unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "bc1ef124da34495e948ead207dd9842235da432d2bbc54b4c110e64c451105531b7f2a3e0ce055c0\
+             2805e7c2de1fb746af97a1dd01f43b824e31b87612410429"
+        );
+        let output = parallel_hash256.parallel_hash(&x, 8, 64, data).await.unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "cdf15289b54f6212b4bc270528b49526006dd9b54e2b6add1ef6900dda3963bb33a72491f236969c\
+             a8afaea29c682d47a393c065b38e29fae651a2091c833110"
+        );
+
+        let context = GpuContext::new().await.unwrap();
+        let sha3 = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let result = sha3.parallel_hash(&x, 8, 32, b"").await;
+        assert!(matches!(result, Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Sha3_256))));
+        let result = parallel_hash128.parallel_hash(&x, 0, 32, b"").await;
+        assert!(matches!(result, Err(GpuSha3Error::InvalidInputLength(0))));
+    }
+
+    #[tokio::test]
+    async fn test_parallel_hash_matches_tiny_keccak() {
+        use tiny_keccak::{Hasher, ParallelHash};
+
+        let data: Vec<u8> = (0..5000u32).map(|i| (i * 7 % 251) as u8).collect();
+        let context = GpuContext::new().await.unwrap();
+        let parallel_hash128 = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let context = GpuContext::new().await.unwrap();
+        let parallel_hash256 = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+
+        // Sample #3's shape, a short last block, blocks spanning several rate blocks, one
+        // block, and no blocks at all
+        for (len, block_size, output_len) in
+            [(72, 12, 32), (5000, 100, 48), (5000, 1024, 100), (300, 1000, 32), (0, 8, 32)]
+        {
+            let data = &data[..len];
+            for (hasher, mut reference) in [
+                (&parallel_hash128, ParallelHash::v128(b"Parallel Data", block_size)),
+                (&parallel_hash256, ParallelHash::v256(b"Parallel Data", block_size)),
+            ] {
+                reference.update(data);
+                let mut expected = vec![0u8; output_len];
+                reference.finalize(&mut expected);
+                let output =
+                    hasher.parallel_hash(data, block_size, output_len, b"Parallel Data").await;
+                assert_eq!(output.unwrap(), expected, "{len} bytes in {block_size}-byte blocks");
+            }
+        }
+    }
+
+    // cSHAKE tests
+    #[tokio::test]
+    async fn test_cshake_matches_nist_samples() {
+        use sha3_core::BatchHashParams;
+        let short = [0u8, 1, 2, 3];
+        let long: Vec<u8> = (0..200).collect();
+        let email = b"Email Signature".as_slice();
+
+        let context = GpuContext::new().await.unwrap();
+        let cshake128 = GpuSha3Hasher::new(context, Sha3Variant::CShake128).unwrap();
+        let outputs = cshake128.cshake(&[&short, &long], 32, b"", email).await.unwrap();
+        assert_eq!(
+            hex::encode(&outputs[0]),
+            "c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5"
+        );
+        assert_eq!(
+            hex::encode(&outputs[1]),
+            "c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b"
+        );
+        // The same strings through BatchHashParams
+        let params = BatchHashParams::new(Sha3Variant::CShake128, 1, short.len())
+            .with_output_length(32)
+            .with_customization(email);
+        let flat = cshake128.hash_batch_with_params(&[&short], &params).await.unwrap();
+        assert_eq!(flat, outputs[0]);
+
+        let context = GpuContext::new().await.unwrap();
+        let cshake256 = GpuSha3Hasher::new(context, Sha3Variant::CShake256).unwrap();
+        let outputs = cshake256.cshake(&[&short, &long], 64, b"", email).await.unwrap();
+        assert_eq!(
+            hex::encode(&outputs[0]),
+            "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd1\
+             64020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c"
+        );
+        assert_eq!(
+            hex::encode(&outputs[1]),
+            "07dc27b11e51fbac75bc7b3c1d983e8b4b85fb1defaf218912ac864302730917\
+             27f42b17ed1df63e8ec118f04b23633c1dfb1574c8fb55cb45da8e25afb092bb"
+        );
+        // A function name alone also switches to the cSHAKE header
+        let named = cshake256.cshake(&[&short], 64, b"KMAC", b"").await.unwrap();
+        let params = BatchHashParams::new(Sha3Variant::CShake256, 1, short.len())
+            .with_output_length(64)
+            .with_function_name("KMAC");
+        assert_eq!(named[0], compute::cpu_hash_batch(&[], &[&short], &params).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_cshake_without_strings_is_shake() {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+        use sha3_core::BatchHashParams;
+        let inputs: Vec<&[u8]> = vec![b"", b"abc", &[7u8; 200]];
+        let context = GpuContext::new().await.unwrap();
+        let cshake = GpuSha3Hasher::new(context, Sha3Variant::CShake128).unwrap();
+        let outputs = cshake.cshake(&inputs, 40, b"", b"").await.unwrap();
+        for (input, output) in inputs.iter().zip(&outputs) {
+            let mut reader = sha3::Shake128::default().chain(input).finalize_xof();
+            let mut expected = vec![0u8; 40];
+            reader.read(&mut expected);
+            assert_eq!(*output, expected);
+        }
+
+        // The plain batch path pads cSHAKE without strings as SHAKE too
+        let params = BatchHashParams::new(Sha3Variant::CShake128, 1, 3).with_output_length(40);
+        let flat = cshake.hash_batch_with_params(&[b"abc"], &params).await.unwrap();
+        assert_eq!(flat, outputs[1]);
+
+        let context = GpuContext::new().await.unwrap();
+        let sha3 = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let result = sha3.cshake(&inputs, 32, b"", b"x").await;
+        assert!(matches!(result, Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Sha3_256))));
+    }
+
+    // Domain separator override tests
+    #[tokio::test]
+    async fn test_domain_separator_override_matches_tiny_keccak() {
+        use sha3_core::BatchHashParams;
+        use tiny_keccak::Hasher;
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        // Around the 136-byte rate, where the padding spills into an extra block
+        for length in [0, 1, 135, 136, 300] {
+            let inputs: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; length]).collect();
+            let refs: Vec<&[u8]> = inputs.iter().map(Vec::as_slice).collect();
+            let expected: Vec<u8> = refs
+                .iter()
+                .flat_map(|input| {
+                    let mut keccak = tiny_keccak::Keccak::v256();
+                    keccak.update(input);
+                    let mut digest = [0u8; 32];
+                    keccak.finalize(&mut digest);
+                    digest
+                })
+                .collect();
+
+            // SHA3-256 padded with 0x01 is the original Keccak-256, on both paths
+            let params = BatchHashParams::new(Sha3Variant::Sha3_256, refs.len(), length)
+                .with_domain_separator(0x01);
+            assert_eq!(hasher.hash_batch_with_params(&refs, &params).await.unwrap(), expected);
+            assert_eq!(compute::cpu_hash_batch(&[], &refs, &params).unwrap(), expected);
+        }
+
+        // Without the override the variant's own padding is unchanged
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 1, 3);
+        assert_eq!(
+            hasher.hash_batch_with_params(&[b"abc"], &params).await.unwrap(),
+            Sha3_256::digest(b"abc").to_vec()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_domain_separator_override_squeezes_past_rate() {
+        use sha3_core::BatchHashParams;
+        use tiny_keccak::Hasher;
+
+        // SHAKE256 shares Keccak-256's 136-byte rate, so padded with 0x01 it is Keccak-256
+        // squeezed to any length
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let inputs: Vec<Vec<u8>> = (0..3u8).map(|i| vec![i; 200]).collect();
+        let refs: Vec<&[u8]> = inputs.iter().map(Vec::as_slice).collect();
+        let expected: Vec<u8> = refs
+            .iter()
+            .flat_map(|input| {
+                let mut keccak = tiny_keccak::Keccak::v256();
+                keccak.update(input);
+                let mut output = vec![0u8; 300];
+                keccak.finalize(&mut output);
+                output
+            })
+            .collect();
+
+        let params = BatchHashParams::new(Sha3Variant::Shake256, refs.len(), 200)
+            .with_output_length(300)
+            .with_domain_separator(0x01);
+        assert_eq!(hasher.hash_batch_with_params(&refs, &params).await.unwrap(), expected);
+        assert_eq!(compute::cpu_hash_batch(&[], &refs, &params).unwrap(), expected);
+
+        // A byte with the top bit set would clash with pad10*1's final bit, so it's rejected
+        let params = params.with_domain_separator(0x80);
+        assert!(matches!(
+            hasher.hash_batch_with_params(&refs, &params).await,
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidDomainSeparator(0x80)))
+        ));
+        assert!(matches!(
+            compute::cpu_hash_batch(&[], &refs, &params),
+            Err(GpuSha3Error::Core(sha3_core::Sha3Error::InvalidDomainSeparator(0x80)))
+        ));
+    }
+
+    // Raw permutation tests
+    #[tokio::test]
+    async fn test_permute_states_matches_keccak_f() {
+        // Keccak-f[1600] of the all-zero state (Keccak team's KeccakF-1600 test vector)
+        const ZERO_PERMUTED: [u64; 25] = [
+            0xF1258F7940E1DDE7,
+            0x84D5CCF933C0478A,
+            0xD598261EA65AA9EE,
+            0xBD1547306F80494D,
+            0x8B284E056253D057,
+            0xFF97A42D7F8E6FD4,
+            0x90FEE5A0A44647C4,
+            0x8C5BDA0CD6192E76,
+            0xAD30A6F71B19059C,
+            0x30935AB7D08FFC64,
+            0xEB5AA93F2317D635,
+            0xA9A6E6260D712103,
+            0x81A57C16DBCF555F,
+            0x43B831CD0347C826,
+            0x01F22F1A11A5569F,
+            0x05E5635A21D9AE61,
+            0x64BEFEF28CC970F2,
+            0x613670957BC46611,
+            0xB87C5A554FD00ECB,
+            0x8C3EE88A1CCF32C8,
+            0x940C7922AE3A2614,
+            0x1841F924A2C509E4,
+            0x16F53526E70465C2,
+            0x75F644E97F30A13B,
+            0xEAF1FF7B5CECA249,
+        ];
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let permuted = hasher.permute_states(&[[0u64; 25]]).await.unwrap();
+        assert_eq!(permuted, [ZERO_PERMUTED]);
+
+        // A batch spanning several workgroups agrees with the CPU permutation, in order
+        let states: Vec<[u64; 25]> = (0..300u64)
+            .map(|i| std::array::from_fn(|lane| i.wrapping_mul(0x9E3779B97F4A7C15) ^ lane as u64))
+            .collect();
+        let permuted = hasher.permute_states(&states).await.unwrap();
+        for (state, result) in states.iter().zip(&permuted) {
+            let mut expected = *state;
+            keccak::f1600(&mut expected);
+            assert_eq!(*result, expected);
+        }
+        assert!(hasher.permute_states(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hash_batch_from_state_continues_prefix() {
+        use sha3_core::BatchHashParams;
+
+        // State after absorbing two whole SHA3-256 rate blocks of prefix
+        let prefix: Vec<u8> = (0..272u16).map(|i| (i * 7) as u8).collect();
+        let mut lanes = [0u64; 25];
+        for block in prefix.chunks_exact(136) {
+            for (lane, bytes) in lanes.iter_mut().zip(block.chunks_exact(8)) {
+                *lane ^= u64::from_le_bytes(bytes.try_into().unwrap());
+            }
+            keccak::f1600(&mut lanes);
+        }
+        let mut state = [0u8; 200];
+        for (bytes, lane) in state.chunks_exact_mut(8).zip(lanes) {
+            bytes.copy_from_slice(&lane.to_le_bytes());
+        }
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let bodies: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 150]).collect();
+        let refs: Vec<&[u8]> = bodies.iter().map(Vec::as_slice).collect();
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 4, 150);
+        let result = hasher.hash_batch_from_state(&state, &refs, &params).await.unwrap();
+        let expected: Vec<u8> =
+            bodies.iter().flat_map(|body| Sha3_256::digest([&prefix[..], body].concat())).collect();
+        assert_eq!(result, expected);
+
+        // The zero state is plain hashing
+        let result = hasher.hash_batch_from_state(&[0; 200], &refs, &params).await.unwrap();
+        assert_eq!(result, hasher.hash_batch(&refs).await.unwrap());
+
+        // Inputs must match the params
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 4, 149);
+        assert!(hasher.hash_batch_from_state(&state, &refs, &params).await.is_err());
+        let params = BatchHashParams::new(Sha3Variant::Sha3_256, 3, 150);
+        assert!(hasher.hash_batch_from_state(&state, &refs, &params).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_absorb_and_squeeze_matches_xof_reader() {
+        use sha3::digest::{ExtendableOutput, Update, XofReader};
+
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake128).unwrap();
+        let seed = b"deterministic rng seed";
+        let stream = hasher.absorb_and_squeeze(seed, 1000).await.unwrap();
+
+        let mut expected = [0u8; 1000];
+        let mut xof = sha3::Shake128::default();
+        xof.update(seed);
+        xof.finalize_xof().read(&mut expected);
+        assert_eq!(stream, expected);
+
+        // A seed spanning several rate blocks, squeezed past one rate block
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Shake256).unwrap();
+        let seed: Vec<u8> = (0..400u16).map(|i| (i * 13) as u8).collect();
+        let stream = hasher.absorb_and_squeeze(&seed, 300).await.unwrap();
+        let mut expected = [0u8; 300];
+        let mut xof = sha3::Shake256::default();
+        xof.update(&seed);
+        xof.finalize_xof().read(&mut expected);
+        assert_eq!(stream, expected);
+
+        assert!(hasher.absorb_and_squeeze(&seed, 0).await.is_err());
+    }
+
+    // Padding block tests
+    #[tokio::test]
+    async fn test_rate_multiple_inputs_get_padding_block() {
+        use sha3_core::BatchHashParams;
+
+        let variants = [
+            Sha3Variant::Sha3_224,
+            Sha3Variant::Sha3_256,
+            Sha3Variant::Sha3_384,
+            Sha3Variant::Sha3_512,
+            Sha3Variant::Shake128,
+            Sha3Variant::Shake256,
+            Sha3Variant::Keccak256,
+            Sha3Variant::Keccak512,
+            Sha3Variant::CShake128,
+            Sha3Variant::CShake256,
+        ];
+        for variant in variants {
+            let rate = variant.rate_bytes();
+            let context = GpuContext::new().await.unwrap();
+            let hasher = GpuSha3Hasher::new(context, variant).unwrap();
+            // Exactly two rate blocks, so padding fills a third block of its own; an odd
+            // length next to it also ends the buffer on an unaligned word
+            for length in [2 * rate, 2 * rate + 1] {
+                let owned: Vec<Vec<u8>> =
+                    (0..4u8).map(|i| (0..length).map(|b| b as u8 ^ i).collect()).collect();
+                let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+                let mut params = BatchHashParams::new(variant, inputs.len(), length);
+                if variant.output_bytes() == 0 {
+                    params = params.with_output_length(32);
+                }
+                assert_eq!(params.prepared_shape().absorb_blocks(), 3);
+                let digests = hasher.hash_batch_with_params(&inputs, &params).await.unwrap();
+                let expected = compute::cpu_hash_batch(&[], &inputs, &params).unwrap();
+                assert_eq!(digests, expected, "{variant:?} at {length} bytes");
+            }
+        }
+    }
+
+    // Smallest digest tests
+    #[tokio::test]
+    async fn test_argmin_digest_matches_cpu_min() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_224).unwrap();
+        // Not a power of two, and spanning several workgroups
+        let owned: Vec<Vec<u8>> =
+            (0..1000).map(|i| format!("ticket-{i:04}").into_bytes()).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+
+        let (index, digest) = hasher.argmin_digest(&inputs).await.unwrap();
+        let expected = inputs
+            .iter()
+            .map(|input| Sha3_224::digest(input).to_vec())
+            .enumerate()
+            .min_by(|a, b| a.1.cmp(&b.1))
+            .unwrap();
+        assert_eq!((index, digest.clone()), expected);
+
+        // Ties go to the lowest index, wherever the copies sit in the reduction tree
+        let mut tied = inputs.clone();
+        tied.push(inputs[index]);
+        assert_eq!(hasher.argmin_digest(&tied).await.unwrap(), (index, digest.clone()));
+        tied.insert(1, inputs[index]);
+        assert_eq!(hasher.argmin_digest(&tied).await.unwrap(), (index.min(1), digest));
+
+        assert_eq!(hasher.argmin_digest(&[b"only"]).await.unwrap().0, 0);
+        assert!(hasher.argmin_digest(&[]).await.is_err());
+    }
+
+    // Truncation collision tests
+    #[tokio::test]
+    async fn test_find_truncation_collisions() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let owned: Vec<Vec<u8>> = (0..40).map(|i| format!("id-{i:02}").into_bytes()).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+
+        // 40 inputs over 256 one-byte prefixes: brute-force the expected pairs on the CPU
+        let digests: Vec<_> = inputs.iter().map(Sha3_256::digest).collect();
+        let mut expected = Vec::new();
+        for i in 0..inputs.len() {
+            for j in i + 1..inputs.len() {
+                if digests[i][0] == digests[j][0] {
+                    expected.push((i, j));
+                }
+            }
+        }
+        assert!(!expected.is_empty());
+        assert_eq!(hasher.find_truncation_collisions(&inputs, 1).await.unwrap(), expected);
+
+        // Full-length digests only collide for identical inputs
+        let repeated = vec![b"a".as_slice(), b"bb".as_slice(), b"a".as_slice()];
+        assert_eq!(hasher.find_truncation_collisions(&repeated, 32).await.unwrap(), vec![(0, 2)]);
+        assert!(hasher.find_truncation_collisions(&repeated, 33).await.is_err());
+    }
+
+    // Structure-of-arrays input tests
+    #[tokio::test]
+    async fn test_hash_batch_soa_matches_aos() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let (num_hashes, input_length) = (21, 50);
+        let rows: Vec<Vec<u8>> = (0..num_hashes)
+            .map(|i| (0..input_length).map(|b| (i * 31 + b * 7) as u8).collect())
+            .collect();
+        let row_refs: Vec<&[u8]> = rows.iter().map(|v| v.as_slice()).collect();
+
+        let mut columns = Vec::with_capacity(num_hashes * input_length);
+        for b in 0..input_length {
+            columns.extend(rows.iter().map(|row| row[b]));
+        }
+
+        let soa = hasher.hash_batch_soa(&columns, num_hashes, input_length).await.unwrap();
+        assert_eq!(soa, hasher.hash_batch(&row_refs).await.unwrap());
+        assert!(hasher.hash_batch_soa(&columns, num_hashes + 1, input_length).await.is_err());
+
+        // A shape whose size overflows can't wrap around to match the data
+        assert!(matches!(
+            hasher.hash_batch_soa(&[], usize::MAX / 2 + 1, 2).await,
+            Err(GpuSha3Error::InvalidInputLength(0))
+        ));
+    }
+
+    // 2D dispatch tests
+    #[tokio::test]
+    async fn test_2d_dispatch_matches_1d() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let context = GpuContext::new().await.unwrap();
+        // 1000 hashes = 4 workgroups, laid out as 2 rows of 3 (the last partly idle)
+        let hasher_2d = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_dispatch_2d_threshold(3);
+
+        let owned: Vec<Vec<u8>> = (0..1000u32).map(|i| i.to_le_bytes().repeat(4)).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+        assert_eq!(hasher_2d.hash_batch(&inputs).await.unwrap(), expected);
+    }
+
+    // Workgroup size tests
+    #[tokio::test]
+    async fn test_workgroup_size_override_matches_default() {
+        let context = GpuContext::new().await.unwrap();
+        // 1000 hashes = 16 workgroups of 64, laid out as 6 rows of 3 (the last partly idle)
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_workgroup_size(64)
+            .with_dispatch_2d_threshold(3);
+        assert_eq!(hasher.workgroup_size(), 64);
+        assert_eq!(hasher.debug_info().workgroup_size, 64);
+
+        let owned: Vec<Vec<u8>> = (0..1000u32).map(|i| i.to_le_bytes().repeat(4)).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+        let smallest = expected.chunks(32).enumerate().min_by_key(|&(_, d)| d).unwrap().0;
+        assert_eq!(hasher.argmin_digest(&inputs).await.unwrap().0, smallest);
+
+        // Out-of-range sizes are clamped to the device limits
+        let limit = hasher.context().limits().max_compute_invocations_per_workgroup;
+        let hasher = hasher.with_workgroup_size(0);
+        assert_eq!(hasher.workgroup_size(), 1);
+        assert_eq!(hasher.hash_batch(&inputs[..10]).await.unwrap(), expected[..320]);
+        assert!(hasher.with_workgroup_size(u32::MAX).workgroup_size() <= limit);
+    }
+
+    #[tokio::test]
+    async fn test_auto_tune_picks_measured_size() {
+        let context = GpuContext::new().await.unwrap();
+        let mut hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let size = hasher.auto_tune().await.unwrap();
+        assert!([64, 128, 256].contains(&size));
+        assert_eq!(hasher.workgroup_size(), size);
+
+        let owned: Vec<Vec<u8>> = (0..700u32).map(|i| i.to_le_bytes().repeat(3)).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+    }
+
+    // Blocking API tests
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn test_hash_batch_blocking_without_executor() {
+        let context = GpuContext::new_blocking().unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let inputs: Vec<&[u8]> = vec![b"abc", b"xyz"];
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+        assert_eq!(hasher.hash_batch_blocking(&inputs).unwrap(), expected);
+    }
+
+    // RustCrypto Digest tests
+    #[cfg(feature = "digest")]
+    #[test]
+    fn test_gpu_digest_matches_sha3_crate() {
+        use sha3_core::{Keccak256Marker, Sha3_256Marker, Sha3_512Marker};
+
+        assert_eq!(GpuSha3Digest::<Sha3_256Marker>::digest(b"abc"), Sha3_256::digest(b"abc"));
+        assert_eq!(GpuSha3Digest::<Sha3_512Marker>::digest(b""), Sha3_512::digest(b""));
+        assert_eq!(GpuSha3Digest::<Keccak256Marker>::digest(b"abc"), Keccak256::digest(b"abc"));
+
+        // Incremental updates, finalize_reset and reset behave like the CPU hasher
+        let message: Vec<u8> = (0..300u32).map(|i| i as u8).collect();
+        let mut gpu = GpuSha3Digest::<Sha3_256Marker>::new();
+        for chunk in message.chunks(37) {
+            gpu.update(chunk);
+        }
+        assert_eq!(gpu.finalize_reset(), Sha3_256::digest(&message));
+        gpu.update(b"discarded");
+        Digest::reset(&mut gpu);
+        gpu.update(b"abc");
+        assert_eq!(gpu.finalize(), Sha3_256::digest(b"abc"));
+
+        // An explicit hasher must match the marker's variant
+        let context = GpuContext::new_blocking().unwrap();
+        let hasher =
+            std::sync::Arc::new(GpuSha3Hasher::new(context, Sha3Variant::Sha3_384).unwrap());
+        let result = GpuSha3Digest::<Sha3_256Marker>::with_hasher(hasher);
+        assert!(matches!(result, Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Sha3_384))));
+    }
+
+    // Thread index mapping tests
+    #[cfg(feature = "debug-state")]
+    #[tokio::test]
+    async fn test_debug_ids_match_input_order() {
+        let context = GpuContext::new().await.unwrap();
+        // 600 hashes = 3 workgroups, laid out as 3 rows of 1 to cover the 2D mapping too
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+            .unwrap()
+            .with_dispatch_2d_threshold(1);
+        let owned: Vec<[u8; 4]> = (0..600u32).map(u32::to_le_bytes).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+
+        let (digests, ids) = hasher.hash_batch_debug_ids(&inputs).await.unwrap();
+        assert_eq!(ids, (0..600).collect::<Vec<u32>>());
+        assert_eq!(digests, hasher.hash_batch(&inputs).await.unwrap());
+    }
+
+    // Adapter selection tests
+    #[tokio::test]
+    async fn test_driver_info() {
+        let context = GpuContext::new().await.unwrap();
+        let info = context.driver_info();
+        assert!(!info.backend.is_empty());
+        assert_eq!(info.name, context.adapter_info().name);
+        assert!(info.to_string().contains(&info.backend));
+    }
+
+    #[tokio::test]
+    async fn test_capabilities_reflect_device() {
+        let context = GpuContext::new().await.unwrap();
+        let capabilities = context.capabilities();
+        let features = context.device().features();
+        assert_eq!(capabilities.has_int64, features.contains(wgpu::Features::SHADER_INT64));
+        // Granted only where the adapter offers it
+        if !context.adapter_features().contains(wgpu::Features::SHADER_INT64) {
+            assert!(!capabilities.has_int64);
+        }
+        assert_eq!(capabilities.max_buffer_size, context.memory_report().max_buffer_size);
+        assert!(capabilities.max_buffer_size > 0);
+        assert!((1..=256).contains(&capabilities.max_workgroup_size));
+        assert_eq!(
+            capabilities.is_fallback_adapter,
+            context.adapter_info().device_type == wgpu::DeviceType::Cpu
+        );
+        assert!(capabilities.to_string().contains("max workgroup size"));
+    }
+
+    #[tokio::test]
+    async fn test_hashing_without_int64_matches_reference() {
+        // Devices created without SHADER_INT64, and software fallback adapters (which may
+        // lack it), run the same u32-pair shader
+        let mut contexts =
+            vec![GpuContext::new_with_features(Some(wgpu::Features::empty())).await.unwrap()];
+        if let Some(index) = GpuContext::list_adapters()
+            .iter()
+            .position(|info| info.device_type == wgpu::DeviceType::Cpu)
+        {
+            if let Ok(context) = GpuContext::new_with_adapter_index(index).await {
+                assert!(context.capabilities().is_fallback_adapter);
+                contexts.push(context);
+            }
+        }
+        assert!(!contexts[0].capabilities().has_int64);
+
+        let owned: Vec<Vec<u8>> = (0..300u32).map(|i| i.to_le_bytes().repeat(50)).collect();
+        let inputs: Vec<&[u8]> = owned.iter().map(|v| v.as_slice()).collect();
+        let expected: Vec<u8> = inputs.iter().flat_map(Sha3_256::digest).collect();
+        for context in contexts {
+            let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+            assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+        }
+
+        let context = GpuContext::new_with_features(Some(wgpu::Features::empty())).await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Keccak512).unwrap();
+        let expected: Vec<u8> = inputs.iter().flat_map(Keccak512::digest).collect();
+        assert_eq!(hasher.hash_batch(&inputs).await.unwrap(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_check_adapter() {
+        use wgpu::{DownlevelFlags, Features, Instance, Limits, RequestAdapterOptions};
+        let instance = Instance::default();
+        let adapter = instance.request_adapter(&RequestAdapterOptions::default()).await.unwrap();
+        GpuContext::check_adapter(&adapter).unwrap();
+        assert!(GpuContext::required_features().is_empty());
+
+        let limits = Limits { max_storage_buffers_per_shader_stage: 2, ..adapter.limits() };
+        let flags = adapter.get_downlevel_capabilities().flags;
+        let err = context::check_capabilities(Features::empty(), &limits, flags).unwrap_err();
+        assert!(
+            matches!(&err, GpuSha3Error::AdapterUnsupported(msg) if msg.contains("max_storage_buffers_per_shader_stage"))
+        );
+        let err = context::check_capabilities(
+            Features::empty(),
+            &Limits::downlevel_webgl2_defaults(),
+            DownlevelFlags::empty(),
+        )
+        .unwrap_err();
+        assert!(matches!(&err, GpuSha3Error::AdapterUnsupported(msg) if msg.contains("compute")));
+    }
+
+    #[tokio::test]
+    async fn test_new_with_backend_pins_backend() {
+        use wgpu::{Backends, PowerPreference};
+
+        let backend = GpuContext::new().await.unwrap().adapter_info().backend;
+        for power in [PowerPreference::HighPerformance, PowerPreference::LowPower] {
+            let context =
+                GpuContext::new_with_backend(Backends::from(backend), power).await.unwrap();
+            assert_eq!(context.adapter_info().backend, backend);
+            let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+            assert_eq!(
+                hasher.hash_batch(&[b"abc"]).await.unwrap(),
+                Sha3_256::digest(b"abc").to_vec()
+            );
+        }
+
+        let result = GpuContext::new_with_backend(Backends::empty(), PowerPreference::None).await;
+        assert!(matches!(result, Err(GpuSha3Error::AdapterNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_new_with_adapter_index() {
+        let adapters = GpuContext::list_adapters();
+        assert!(!adapters.is_empty());
+
+        let index = adapters
+            .iter()
+            .position(|info| AdapterDescriptor::from(info) == GpuContext::best_adapter().unwrap())
+            .unwrap();
+        let context = GpuContext::new_with_adapter_index(index).await.unwrap();
+        assert_eq!(context.adapter_info().name, adapters[index].name);
+        assert_eq!(context.adapter_info().backend, adapters[index].backend);
+        assert_eq!(context.adapter_info().device_type, adapters[index].device_type);
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        assert_eq!(hasher.hash_batch(&[b"abc"]).await.unwrap(), Sha3_256::digest(b"abc").to_vec());
+
+        let result = GpuContext::new_with_adapter_index(adapters.len()).await;
+        assert!(matches!(result, Err(GpuSha3Error::AdapterNotFound(_))));
+    }
+
+    #[test]
+    fn test_rank_adapters_dedups_and_orders() {
+        use wgpu::{Backend, DeviceType};
+        let adapter = |name: &str, vendor, backend, device_type| AdapterDescriptor {
+            name: name.to_string(),
+            vendor,
+            backend,
+            device_type,
+        };
+        let adapters = [
+            adapter("llvmpipe", 0x10005, Backend::Vulkan, DeviceType::Cpu),
+            adapter("RTX 4090", 0x10de, Backend::Gl, DeviceType::DiscreteGpu),
+            adapter("Iris Xe", 0x8086, Backend::Vulkan, DeviceType::IntegratedGpu),
+            adapter("RTX 4090", 0x10de, Backend::Vulkan, DeviceType::DiscreteGpu),
+            adapter("llvmpipe", 0x10005, Backend::Gl, DeviceType::Cpu),
+            adapter("Iris Xe", 0x8086, Backend::Gl, DeviceType::IntegratedGpu),
+        ];
+
+        // One entry per device, through its native backend, hardware before software
+        assert_eq!(context::rank_adapters(&adapters), vec![3, 2, 0]);
+        assert!(context::rank_adapters(&[]).is_empty());
+    }
+
+    // Request batching tests
+    #[tokio::test]
+    async fn test_batching_hasher_coalesces_concurrent_requests() {
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        let batching =
+            BatchingHasher::new(hasher, 64, std::time::Duration::from_millis(20)).unwrap();
+
+        // Lengths 0..5 exercise inputs of different lengths sharing a batch
+        let owned: Vec<Vec<u8>> =
+            (0..100u32).map(|i| i.to_le_bytes()[..(i % 5) as usize].to_vec()).collect();
+        let digests = futures::future::try_join_all(owned.iter().map(|data| batching.hash(data)))
+            .await
+            .unwrap();
+        for (data, digest) in owned.iter().zip(&digests) {
+            assert_eq!(digest[..], Sha3_256::digest(data)[..]);
+        }
+
+        let stats = batching.hasher().session_stats();
+        assert_eq!(stats.hashes, 100);
+        assert!(stats.batches < 100, "{} batches for 100 requests", stats.batches);
+    }
+
+    // Multi-GPU tests
+    #[tokio::test]
+    async fn test_multi_gpu_hasher_partitions_batches() {
+        // The same GPU twice stands in for two adapters
+        let mut hashers = Vec::new();
+        for _ in 0..2 {
+            let context = GpuContext::new().await.unwrap();
+            hashers.push(GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap());
+        }
+        let multi = MultiGpuHasher::new(hashers).unwrap();
+        assert_eq!(multi.hashers().len(), 2);
+        assert_eq!(multi.variant(), Sha3Variant::Sha3_256);
+
+        let inputs: Vec<Vec<u8>> = (0..1001u16).map(|i| i.to_le_bytes().repeat(20)).collect();
+        for count in [1001, 1000, 1, 0] {
+            let refs: Vec<&[u8]> = inputs[..count].iter().map(Vec::as_slice).collect();
+            let expected: Vec<u8> = refs.iter().flat_map(Sha3_256::digest).collect();
+            assert_eq!(multi.hash_batch(&refs).await.unwrap(), expected, "{count} inputs");
+        }
+        // The first hasher takes the odd input out, and the lone input of the 1-input batch
+        let hashes: Vec<u64> =
+            multi.hashers().iter().map(|hasher| hasher.session_stats().hashes).collect();
+        assert_eq!(hashes, vec![501 + 500 + 1, 500 + 500]);
+
+        let mixed: [&[u8]; 2] = [b"a", b"bc"];
+        assert!(matches!(multi.hash_batch(&mixed).await, Err(GpuSha3Error::InvalidInputLength(_))));
+    }
+
+    #[tokio::test]
+    async fn test_multi_gpu_hasher_rejects_mismatched_hashers() {
+        assert!(matches!(MultiGpuHasher::new(Vec::new()), Err(GpuSha3Error::AdapterNotFound(_))));
+
+        let mut hashers = Vec::new();
+        for variant in [Sha3Variant::Sha3_256, Sha3Variant::Keccak256] {
+            let context = GpuContext::new().await.unwrap();
+            hashers.push(GpuSha3Hasher::new(context, variant).unwrap());
+        }
+        assert!(matches!(
+            MultiGpuHasher::new(hashers),
+            Err(GpuSha3Error::UnsupportedVariant(Sha3Variant::Keccak256))
+        ));
+
+        let multi = MultiGpuHasher::from_all_adapters(Sha3Variant::Sha3_256).await.unwrap();
+        assert!(!multi.hashers().is_empty());
+        assert_eq!(multi.hash_batch(&[b"abc"]).await.unwrap(), Sha3_256::digest(b"abc").to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_batches_use_staging_ring() {
+        let batches: Vec<Vec<Vec<u8>>> = (0..8u8)
+            .map(|b| (0..1000u16).map(|i| [b, i as u8, (i >> 8) as u8].repeat(5)).collect())
+            .collect();
+        let expected: Vec<Vec<u8>> =
+            batches.iter().map(|batch| batch.iter().flat_map(Sha3_256::digest).collect()).collect();
+
+        // Each in-flight batch reads back through its own staging buffer; once the ring is
+        // exhausted, further concurrent batches take dynamic buffers
+        for (staging_buffers, persistent_batches) in [(8, 8), (1, 1)] {
+            let context = GpuContext::new().await.unwrap();
+            let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256)
+                .unwrap()
+                .with_staging_buffers(staging_buffers);
+            assert_eq!(hasher.staging_buffers(), staging_buffers);
+
+            let refs: Vec<Vec<_>> =
+                batches.iter().map(|batch| batch.iter().map(Vec::as_slice).collect()).collect();
+            // Readbacks are held until every batch has been submitted
+            let held = hasher.hold_readbacks();
+            let release = async move {
+                tokio::task::yield_now().await;
+                drop(held);
+            };
+            let (results, ()) = futures::join!(
+                futures::future::join_all(refs.iter().map(|inputs| hasher.hash_batch(inputs))),
+                release
+            );
+            for (result, expected) in results.into_iter().zip(&expected) {
+                assert_eq!(&result.unwrap(), expected);
+            }
+            let stats = hasher.session_stats();
+            assert_eq!(
+                (stats.persistent_batches, stats.dynamic_batches),
+                (persistent_batches, 8 - persistent_batches)
+            );
+
+            // The ring is free again for the next batch
+            assert_eq!(hasher.hash_batch(&refs[0]).await.unwrap(), expected[0]);
+            assert_eq!(hasher.session_stats().persistent_batches, persistent_batches + 1);
+        }
+        let context = GpuContext::new().await.unwrap();
+        let hasher = GpuSha3Hasher::new(context, Sha3Variant::Sha3_256).unwrap();
+        assert_eq!(hasher.with_staging_buffers(0).staging_buffers(), 1);
+    }
+
     // Concurrent usage tests (from audit report)
     #[tokio::test]
     async fn test_concurrent_batch_hashing() {
@@ -0,0 +1,145 @@
+//! Splitting batches across several GPUs
+
+use sha3_core::{BatchHashParams, Sha3Variant};
+
+use crate::backend::{BackendFuture, Sha3Backend};
+use crate::compute::GpuSha3Hasher;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::context::{rank_adapters, AdapterDescriptor, GpuContext};
+use crate::error::GpuSha3Error;
+
+/// Batch hasher spreading each batch evenly over several [`GpuSha3Hasher`]s, typically one
+/// per GPU
+///
+/// A batch is cut into contiguous slices, one per hasher, which are hashed concurrently;
+/// the digests come back in input order, as from a single hasher. All hashers share one
+/// variant.
+pub struct MultiGpuHasher {
+    hashers: Vec<GpuSha3Hasher>,
+}
+
+impl MultiGpuHasher {
+    /// Spread batches over `hashers`
+    ///
+    /// Fails with `AdapterNotFound` if `hashers` is empty, and with `UnsupportedVariant` if
+    /// a hasher's variant differs from the first one's.
+    pub fn new(hashers: Vec<GpuSha3Hasher>) -> Result<Self, GpuSha3Error> {
+        let Some(first) = hashers.first() else {
+            return Err(GpuSha3Error::AdapterNotFound("No hashers to dispatch to".to_string()));
+        };
+        let variant = first.variant();
+        if let Some(other) = hashers.iter().find(|hasher| hasher.variant() != variant) {
+            return Err(GpuSha3Error::UnsupportedVariant(other.variant()));
+        }
+        Ok(Self { hashers })
+    }
+
+    /// Create one `variant` hasher on each GPU in the system
+    ///
+    /// Adapters come from [`GpuContext::list_adapters`], with a device reported through
+    /// several backends used once, through its best backend (as
+    /// [`GpuContext::best_adapter`] ranks them). Software adapters are used only when no
+    /// hardware one is available, and adapters that can't run the pipelines are skipped.
+    /// Fails with `AdapterNotFound` if no adapter is usable.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn from_all_adapters(variant: Sha3Variant) -> Result<Self, GpuSha3Error> {
+        let adapters: Vec<AdapterDescriptor> =
+            GpuContext::list_adapters().iter().map(AdapterDescriptor::from).collect();
+        let mut candidates = rank_adapters(&adapters);
+        if candidates.iter().any(|&i| adapters[i].device_type != wgpu::DeviceType::Cpu) {
+            candidates.retain(|&i| adapters[i].device_type != wgpu::DeviceType::Cpu);
+        }
+
+        let mut hashers = Vec::new();
+        for index in candidates {
+            match GpuContext::new_with_adapter_index(index).await {
+                Ok(context) => hashers.push(GpuSha3Hasher::new(context, variant)?),
+                Err(GpuSha3Error::AdapterUnsupported(_) | GpuSha3Error::DeviceCreation(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Self::new(hashers)
+    }
+
+    /// The hashers batches are spread over, in the order slices are assigned
+    pub fn hashers(&self) -> &[GpuSha3Hasher] {
+        &self.hashers
+    }
+
+    /// The SHA-3 variant every hasher computes
+    pub fn variant(&self) -> Sha3Variant {
+        self.hashers[0].variant()
+    }
+
+    /// Hash a batch of inputs (all must be the same length)
+    /// Returns a flattened vector of all output hashes
+    pub async fn hash_batch(&self, inputs: &[&[u8]]) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+        let input_length = inputs[0].len();
+        if !inputs.iter().all(|input| input.len() == input_length) {
+            return Err(GpuSha3Error::InvalidInputLength(input_length));
+        }
+        let params = BatchHashParams::new(self.variant(), inputs.len(), input_length);
+        self.hash_batch_with_params(inputs, &params).await
+    }
+
+    /// Hash a batch with custom parameters, split across the hashers
+    ///
+    /// Each hasher gets a contiguous slice of at most `ceil(n / hashers)` inputs, hashed
+    /// with `params` narrowed to that slice.
+    pub async fn hash_batch_with_params(
+        &self,
+        inputs: &[&[u8]],
+        params: &BatchHashParams,
+    ) -> Result<Vec<u8>, GpuSha3Error> {
+        if inputs.len() != params.num_hashes {
+            return Err(GpuSha3Error::InvalidInputLength(params.num_hashes));
+        }
+        if inputs.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let slices: Vec<_> = split_evenly(inputs, self.hashers.len()).collect();
+        let slice_params: Vec<BatchHashParams> = slices
+            .iter()
+            .map(|slice| BatchHashParams { num_hashes: slice.len(), ..params.clone() })
+            .collect();
+        let digests = futures::future::try_join_all(
+            self.hashers
+                .iter()
+                .zip(slices.iter().zip(&slice_params))
+                .map(|(hasher, (slice, params))| hasher.hash_batch_with_params(slice, params)),
+        )
+        .await?;
+        Ok(digests.concat())
+    }
+}
+
+/// Split `inputs` into at most `parts` contiguous slices whose lengths differ by at most one
+fn split_evenly<T>(inputs: &[T], parts: usize) -> impl Iterator<Item = &[T]> {
+    let (base, extra) = (inputs.len() / parts, inputs.len() % parts);
+    let mut rest = inputs;
+    (0..parts)
+        .map(move |i| {
+            let (slice, tail) = rest.split_at(base + usize::from(i < extra));
+            rest = tail;
+            slice
+        })
+        .filter(|slice| !slice.is_empty())
+}
+
+impl Sha3Backend for MultiGpuHasher {
+    fn hash_batch<'a>(
+        &'a self,
+        inputs: &'a [&'a [u8]],
+        params: &'a BatchHashParams,
+    ) -> BackendFuture<'a> {
+        Box::pin(self.hash_batch_with_params(inputs, params))
+    }
+
+    fn variant(&self) -> Sha3Variant {
+        MultiGpuHasher::variant(self)
+    }
+}
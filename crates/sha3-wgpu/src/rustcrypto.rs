@@ -0,0 +1,163 @@
+//! RustCrypto `Digest` support, so GPU hashing drops in wherever `impl Digest` is accepted
+
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+use digest::consts::{U28, U32, U48, U64};
+use digest::{FixedOutput, FixedOutputReset, HashMarker, Output, OutputSizeUser, Reset, Update};
+use sha3_core::{
+    FixedVariant, Keccak256Marker, Keccak512Marker, Sha3Variant, Sha3_224Marker, Sha3_256Marker,
+    Sha3_384Marker, Sha3_512Marker,
+};
+
+use crate::compute::{clear_host_copy, extend_host_copy, host_copy, GpuSha3Hasher, HostCopy};
+use crate::context::GpuContext;
+use crate::error::GpuSha3Error;
+
+/// A fixed-output variant `V` behind the RustCrypto `Digest` traits
+///
+/// `Digest` is synchronous, so updates are buffered in memory and `finalize` blocks while
+/// the whole message is hashed as a one-input batch. Hashers built with
+/// [`Default`] share one lazily created hasher per variant on the default adapter.
+///
+/// ```no_run
+/// use digest::Digest;
+/// use sha3_core::Sha3_256Marker;
+/// use sha3_wgpu::GpuSha3Digest;
+///
+/// let digest = GpuSha3Digest::<Sha3_256Marker>::digest(b"abc");
+/// assert_eq!(digest.len(), 32);
+/// ```
+pub struct GpuSha3Digest<V: FixedVariant> {
+    hasher: Arc<GpuSha3Hasher>,
+    buffer: HostCopy,
+    _variant: PhantomData<V>,
+}
+
+impl<V: FixedVariant> GpuSha3Digest<V> {
+    /// Hash with `hasher`, which must be a `V::VARIANT` hasher
+    ///
+    /// Any fixed prefix set on `hasher` precedes the data. Fails with `UnsupportedVariant`
+    /// if `hasher` hashes a different variant.
+    pub fn with_hasher(hasher: Arc<GpuSha3Hasher>) -> Result<Self, GpuSha3Error> {
+        if hasher.variant() != V::VARIANT {
+            return Err(GpuSha3Error::UnsupportedVariant(hasher.variant()));
+        }
+        Ok(Self { hasher, buffer: host_copy(Vec::new()), _variant: PhantomData })
+    }
+
+    /// Hash the buffered message, blocking until the digest is read back
+    ///
+    /// # Panics
+    ///
+    /// Panics if the GPU dispatch fails, as `FixedOutput` has no way to report errors.
+    fn hash_buffered(&self) -> Vec<u8> {
+        pollster::block_on(self.hasher.hash_batch(&[self.buffer.as_slice()]))
+            .unwrap_or_else(|e| panic!("GPU {:?} hash failed: {e}", V::VARIANT))
+    }
+}
+
+/// Hashers shared by `Default`-built digests, at most one per variant
+type SharedHashers = Mutex<Vec<Arc<GpuSha3Hasher>>>;
+
+/// The hasher `Default` builds for `variant`, created on first use
+///
+/// # Panics
+///
+/// Panics if no GPU context can be created.
+fn shared_hasher(variant: Sha3Variant) -> Arc<GpuSha3Hasher> {
+    static HASHERS: OnceLock<SharedHashers> = OnceLock::new();
+    let hashers = HASHERS.get_or_init(Default::default);
+    let find = |hashers: &[Arc<GpuSha3Hasher>]| {
+        hashers.iter().find(|hasher| hasher.variant() == variant).cloned()
+    };
+    if let Some(hasher) = find(&hashers.lock().unwrap_or_else(PoisonError::into_inner)) {
+        return hasher;
+    }
+    // Created without the lock held, so a failure here panics without poisoning it
+    let context = GpuContext::new_blocking()
+        .unwrap_or_else(|e| panic!("no GPU context for GpuSha3Digest: {e}"));
+    let hasher =
+        Arc::new(GpuSha3Hasher::new(context, variant).expect("fixed variants are supported"));
+    let mut hashers = hashers.lock().unwrap_or_else(PoisonError::into_inner);
+    // Another thread may have created one meanwhile; keep sharing a single hasher
+    if let Some(existing) = find(&hashers) {
+        return existing;
+    }
+    hashers.push(hasher.clone());
+    hasher
+}
+
+impl<V: FixedVariant> Default for GpuSha3Digest<V> {
+    /// Hash with the shared `V::VARIANT` hasher
+    ///
+    /// # Panics
+    ///
+    /// Panics if no GPU context can be created.
+    fn default() -> Self {
+        Self {
+            hasher: shared_hasher(V::VARIANT),
+            buffer: host_copy(Vec::new()),
+            _variant: PhantomData,
+        }
+    }
+}
+
+impl<V: FixedVariant> Clone for GpuSha3Digest<V> {
+    fn clone(&self) -> Self {
+        Self { hasher: self.hasher.clone(), buffer: self.buffer.clone(), _variant: PhantomData }
+    }
+}
+
+impl<V: FixedVariant> std::fmt::Debug for GpuSha3Digest<V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GpuSha3Digest")
+            .field("variant", &V::VARIANT)
+            .field("buffered_bytes", &self.buffer.len())
+            .finish()
+    }
+}
+
+impl<V: FixedVariant> Update for GpuSha3Digest<V> {
+    fn update(&mut self, data: &[u8]) {
+        extend_host_copy(&mut self.buffer, data);
+    }
+}
+
+impl<V: FixedVariant> Reset for GpuSha3Digest<V> {
+    fn reset(&mut self) {
+        clear_host_copy(&mut self.buffer);
+    }
+}
+
+impl<V: FixedVariant> HashMarker for GpuSha3Digest<V> {}
+
+macro_rules! impl_fixed_output {
+    ($($marker:ty => $size:ty,)*) => {$(
+        impl OutputSizeUser for GpuSha3Digest<$marker> {
+            type OutputSize = $size;
+        }
+
+        impl FixedOutput for GpuSha3Digest<$marker> {
+            fn finalize_into(self, out: &mut Output<Self>) {
+                out.copy_from_slice(&self.hash_buffered());
+            }
+        }
+
+        impl FixedOutputReset for GpuSha3Digest<$marker> {
+            fn finalize_into_reset(&mut self, out: &mut Output<Self>) {
+                out.copy_from_slice(&self.hash_buffered());
+                clear_host_copy(&mut self.buffer);
+            }
+        }
+    )*};
+}
+
+impl_fixed_output! {
+    Sha3_224Marker => U28,
+    Sha3_256Marker => U32,
+    Sha3_384Marker => U48,
+    Sha3_512Marker => U64,
+    Keccak256Marker => U32,
+    Keccak512Marker => U64,
+}
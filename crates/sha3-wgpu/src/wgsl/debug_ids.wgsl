@@ -0,0 +1,22 @@
+// Debug entry point appended to sha3.wgsl when the `debug-state` feature is enabled
+
+// Id of the invocation that hashed each input (read-write, `main_debug_ids` only)
+@group(1) @binding(1) var<storage, read_write> thread_ids: array<u32>;
+
+// Same as `main`, additionally recording which invocation produced each digest. The id is
+// derived from the workgroup id and local index rather than `flat_index`, so an indexing
+// bug in `flat_index` shows up as thread_ids[i] != i.
+@compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
+fn main_debug_ids(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>,
+    @builtin(workgroup_id) workgroup_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32
+) {
+    let hash_idx = flat_index(global_id, num_workgroups);
+    hash_one(hash_idx);
+    if (hash_idx < params.num_hashes) {
+        let workgroup = workgroup_id.y * num_workgroups.x + workgroup_id.x;
+        thread_ids[hash_idx] = workgroup * WORKGROUP_SIZE + local_index;
+    }
+}
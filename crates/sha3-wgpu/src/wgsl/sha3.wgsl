@@ -3,15 +3,16 @@
 // Note: WebGPU doesn't support u64, so we use vec2<u32> for 64-bit operations (high, low)
 //
 // Key Optimizations (vs original):
-// 1. **Packed u32 buffer: 8KB storage (4x reduction from 32KB u8-as-u32 waste)**
-//    - Original: array<u32, 8192> storing bytes as u32 = 32KB per thread
-//    - Optimized: array<u32, 2048> packing 4 bytes per u32 = 8KB per thread
-//    - **Major GPU occupancy improvement** (can run 4x more concurrent threads)
+// 1. **Inputs streamed from the storage buffer (no per-thread copy)**
+//    - Each rate block is read lane by lane straight from `inputs`, with the padding
+//      bytes synthesized on the fly, so there is no fixed-size private input array
+//    - No per-input size limit, and per-thread memory is just the state and temps
 //
-// 2. **Workgroup size: 256 threads (4x increase from 64)**
-//    - Optimal wave/warp utilization on modern GPUs (8 warps)
-//    - Maximum SM/CU occupancy for compute workloads
-//    - Better hiding of memory latency
+// 2. **Workgroup size: the `WORKGROUP_SIZE` override, 256 threads by default**
+//    - Set per pipeline by the host (`with_workgroup_size`, `auto_tune`), since the
+//      best size depends on the GPU
+//    - The default of 256 (8 warps) fills SM/CU occupancy on most modern GPUs and
+//      hides memory latency
 //
 // 3. **Sequential XORs in theta step** (reduced function call overhead)
 //    - Flattened nested XOR calls to sequential operations
@@ -25,13 +26,8 @@
 //    - Eliminated intermediate byte array allocations
 //    - Word-aligned access paths for better memory performance
 //
-// Expected Performance Impact:
-// - Memory per thread: 32KB → 8KB (4x reduction)
-// - Theoretical occupancy: 2-4x improvement (depends on GPU architecture)
-// - Combined estimated speedup: 2.5-4x over original implementation
-//
-// Time complexity: O(padded_len / rate_bytes) * O(24 rounds)
-// Space complexity: O(8KB) per thread for packed input buffer + O(400 bytes) for state/temps
+// Time complexity: O(absorb_blocks) * O(24 rounds)
+// Space complexity: O(400 bytes) per thread for state/temps
 // NOTE: 64-bit emulation using vec2<u32> adds ~2x overhead vs native u64 hardware
 
 const KECCAK_ROUNDS: u32 = 24u;
@@ -94,11 +90,34 @@ struct HashParams {
     input_length: u32,      // Length of each input in bytes
     rate_bytes: u32,        // Rate in bytes (depends on SHA-3 variant)
     output_bytes: u32,      // Output size in bytes
+    input_layout: u32,      // INPUT_LAYOUT_AOS or INPUT_LAYOUT_SOA
+    domain_separator: u32,  // Padding domain byte (0x06 SHA-3, 0x1F SHAKE, 0x01 Keccak)
+    absorb_blocks: u32,     // Rate blocks per input, including padding (precomputed on host)
+    padded_length: u32,     // absorb_blocks * rate_bytes; the final padding bit is in its last byte
 }
 
+// Inputs stored one after another: [hash0 bytes][hash1 bytes]...
+const INPUT_LAYOUT_AOS: u32 = 0u;
+// Inputs stored column-major: [byte0 of all hashes][byte1 of all hashes]...
+const INPUT_LAYOUT_SOA: u32 = 1u;
+
+// Threads per workgroup of the batch-wide entry points, set by the host at pipeline
+// creation (`GpuSha3Hasher::with_workgroup_size`)
+override WORKGROUP_SIZE: u32 = 256u;
+
 @group(0) @binding(0) var<storage, read> inputs: HashInput;
 @group(0) @binding(1) var<storage, read_write> outputs: HashOutput;
 @group(0) @binding(2) var<uniform> params: HashParams;
+// Sponge state every hash starts from (all zeros, or the state after absorbing a fixed prefix)
+@group(0) @binding(3) var<storage, read> initial_state: array<vec2<u32>, 25>;
+// Sponge state carried between streaming absorb dispatches (read-write, `absorb` only)
+@group(0) @binding(4) var<storage, read_write> stream_state: array<vec2<u32>, 25>;
+// Each input followed by its digest, for a second pass (read-write, `interleave_digests` only)
+@group(1) @binding(0) var<storage, read_write> interleaved: array<u32>;
+// Digest indices being reduced to the smallest digest's (read-write, `argmin_pass` only)
+@group(1) @binding(2) var<storage, read_write> argmin_indices: array<u32>;
+// Distance between the two slots each `argmin_pass` invocation compares
+@group(1) @binding(3) var<uniform> argmin_stride: u32;
 
 // Helper: XOR two 64-bit values (represented as vec2<u32>)
 fn xor_u64(a: vec2<u32>, b: vec2<u32>) -> vec2<u32> {
@@ -142,62 +161,6 @@ fn rotl_u64(x: vec2<u32>, n: u32) -> vec2<u32> {
     }
 }
 
-// Helper: Load 8 bytes from packed u32 buffer and convert to u64 (little-endian)
-// offset is the byte offset in the conceptual byte array
-fn load_u64_from_buffer(buffer: ptr<function, array<u32, 2048>>, byte_offset: u32) -> vec2<u32> {
-    // Load two u32 words (8 bytes total)
-    let word_idx = byte_offset / 4u;
-    let byte_in_word = byte_offset % 4u;
-
-    if (byte_in_word == 0u) {
-        // Aligned access - directly load two u32s
-        let low = (*buffer)[word_idx];
-        let high = (*buffer)[word_idx + 1u];
-        return vec2<u32>(high, low);
-    } else {
-        // Unaligned access - need to combine parts of 3 words
-        let w0 = (*buffer)[word_idx];
-        let w1 = (*buffer)[word_idx + 1u];
-        let w2 = (*buffer)[word_idx + 2u];
-
-        let shift_bits = byte_in_word * 8u;
-        let inv_shift = 32u - shift_bits;
-
-        let low = (w0 >> shift_bits) | (w1 << inv_shift);
-        let high = (w1 >> shift_bits) | (w2 << inv_shift);
-
-        return vec2<u32>(high, low);
-    }
-}
-
-// Helper: Store u64 to packed u32 buffer (little-endian)
-// byte_offset is the byte offset in the conceptual byte array
-fn store_u64_to_buffer(buffer: ptr<function, array<u32, 2048>>, byte_offset: u32, value: vec2<u32>) {
-    let word_idx = byte_offset / 4u;
-    let byte_in_word = byte_offset % 4u;
-
-    if (byte_in_word == 0u) {
-        // Aligned access - directly store two u32s
-        (*buffer)[word_idx] = value.y;      // low word
-        (*buffer)[word_idx + 1u] = value.x; // high word
-    } else {
-        // Unaligned access - need to read-modify-write 3 words
-        let shift_bits = byte_in_word * 8u;
-        let inv_shift = 32u - shift_bits;
-
-        let w0 = (*buffer)[word_idx];
-        let w2 = (*buffer)[word_idx + 2u];
-
-        // Create masks to preserve bits we're not writing
-        let low_mask = (1u << shift_bits) - 1u;
-        let high_mask = ~((1u << shift_bits) - 1u);
-
-        (*buffer)[word_idx] = (w0 & low_mask) | (value.y << shift_bits);
-        (*buffer)[word_idx + 1u] = (value.y >> inv_shift) | (value.x << shift_bits);
-        (*buffer)[word_idx + 2u] = (w2 & high_mask) | (value.x >> inv_shift);
-    }
-}
-
 // Keccak-f[1600] permutation
 // State is represented as 25 vec2<u32> values (5x5 array of 64-bit lanes)
 fn keccak_f1600(state: ptr<function, array<vec2<u32>, 25>>) {
@@ -256,143 +219,153 @@ fn keccak_f1600(state: ptr<function, array<vec2<u32>, 25>>) {
     }
 }
 
-// SHA-3 padding (pad10*1)
-// Optimized: Works with packed u32 buffer (4 bytes per u32)
-const MAX_INPUT_SIZE: u32 = 8192u;  // Max 8KB per input (reduced from 16KB for better GPU occupancy)
-const MAX_INPUT_WORDS: u32 = 2048u; // 8KB / 4 bytes per word
-
-fn apply_padding(
-    input_data: ptr<function, array<u32, 2048>>,  // Packed u32 buffer (8KB capacity)
-    input_len: u32,
-    rate_bytes: u32
-) -> u32 {
-    // Bounds check to prevent buffer overflow
-    if (input_len >= MAX_INPUT_SIZE) {
-        return 0u;  // Error: input too large
+// Word `word_idx` (4 bytes, little-endian) of input `hash_idx`; bytes past the end are 0.
+// Only the input's own bytes are loaded, so the padding block a rate-multiple message
+// needs is synthesized here and never occupies (or overruns) the input buffer.
+fn input_word(hash_idx: u32, word_idx: u32) -> u32 {
+    let first_byte = word_idx * 4u;
+    if (first_byte >= params.input_length) {
+        return 0u;
     }
 
-    // SHA-3 uses domain separation byte 0x06
-    // Write byte at position input_len
-    let word_idx = input_len / 4u;
-    let byte_in_word = input_len % 4u;
-    let shift = byte_in_word * 8u;
-    let mask = ~(0xFFu << shift);
-    (*input_data)[word_idx] = ((*input_data)[word_idx] & mask) | (0x06u << shift);
-
-    // Calculate padded length (must be multiple of rate)
-    var padded_len = input_len + 1u;
-    let rate_blocks = (padded_len + rate_bytes - 1u) / rate_bytes;
-    padded_len = rate_blocks * rate_bytes;
-
-    // Bounds check for padded length
-    if (padded_len > MAX_INPUT_SIZE) {
-        return 0u;  // Error: padded length exceeds buffer
+    var word = 0u;
+    if (params.input_layout == INPUT_LAYOUT_SOA) {
+        // Column-major case: byte b of this hash lives at b * num_hashes + hash_idx
+        for (var k = 0u; k < 4u; k = k + 1u) {
+            let b = first_byte + k;
+            if (b < params.input_length) {
+                let index = b * params.num_hashes + hash_idx;
+                let byte_val = (inputs.data[index / 4u] >> ((index % 4u) * 8u)) & 0xFFu;
+                word = word | (byte_val << (k * 8u));
+            }
+        }
+        return word;
     }
 
-    // Clear padding bytes (from input_len + 1 to padded_len - 1)
-    let start_byte = input_len + 1u;
-    let end_byte = padded_len - 1u;
-
-    // Clear partial word at start if needed
-    if (start_byte < end_byte && (start_byte % 4u) != 0u) {
-        let start_word = start_byte / 4u;
-        let start_bit = (start_byte % 4u) * 8u;
-        let clear_mask = (1u << start_bit) - 1u;
-        (*input_data)[start_word] &= clear_mask;
+    let offset = hash_idx * params.input_length + first_byte;
+    let byte_align = offset % 4u;
+    let remaining = params.input_length - first_byte;
+    if (byte_align == 0u) {
+        // Aligned case: direct word load
+        word = inputs.data[offset / 4u];
+    } else {
+        // Unaligned case: shift and combine two words. The second is only loaded when the
+        // input continues into it: at the end of the last input it lies past the buffer.
+        let shift_bits = byte_align * 8u;
+        word = inputs.data[offset / 4u] >> shift_bits;
+        if (remaining > 4u - byte_align) {
+            word = word | (inputs.data[offset / 4u + 1u] << (32u - shift_bits));
+        }
     }
 
-    // Clear complete words
-    let first_full_word = (start_byte + 3u) / 4u;
-    let last_full_word = end_byte / 4u;
-    for (var i = first_full_word; i < last_full_word; i = i + 1u) {
-        (*input_data)[i] = 0u;
+    // Mask off the next input's bytes in the final partial word
+    if (remaining < 4u) {
+        word = word & ((1u << (remaining * 8u)) - 1u);
     }
+    return word;
+}
 
-    // Clear partial word at end if needed (will be OR'd with 0x80 next)
-    if (end_byte % 4u != 3u) {
-        let end_word = end_byte / 4u;
-        let end_bit = ((end_byte % 4u) + 1u) * 8u;
-        let clear_mask = (1u << end_bit) - 1u;
-        (*input_data)[end_word] &= clear_mask;
+// Word `word_idx` of the padded message: the input followed by the domain separation
+// byte (0x06 SHA-3, 0x1F SHAKE, 0x01 original Keccak), zeros, and the final 0x80 bit of
+// pad10*1 in the last byte of the padded message
+fn padded_word(hash_idx: u32, word_idx: u32) -> u32 {
+    var word = input_word(hash_idx, word_idx);
+    let first_byte = word_idx * 4u;
+    if (params.input_length >= first_byte && params.input_length < first_byte + 4u) {
+        word = word | (params.domain_separator << ((params.input_length - first_byte) * 8u));
     }
+    let last_byte = params.padded_length - 1u;
+    if (last_byte >= first_byte && last_byte < first_byte + 4u) {
+        word = word | (0x80u << ((last_byte - first_byte) * 8u));
+    }
+    return word;
+}
 
-    // Set final bit (pad10*1 pattern)
-    let final_word_idx = (padded_len - 1u) / 4u;
-    let final_byte_in_word = (padded_len - 1u) % 4u;
-    let final_shift = final_byte_in_word * 8u;
-    (*input_data)[final_word_idx] |= 0x80u << final_shift;
-
-    return padded_len;
+// Large dispatches are laid out as a 2D grid of workgroup rows; flatten an
+// invocation's position in the grid to its index in the batch
+fn flat_index(global_id: vec3<u32>, num_workgroups: vec3<u32>) -> u32 {
+    return global_id.y * num_workgroups.x * WORKGROUP_SIZE + global_id.x;
 }
 
 // Main compute shader - processes one hash per thread
-// Optimized: Increased workgroup size for maximum occupancy
-@compute @workgroup_size(256, 1, 1)
-fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
-    let hash_idx = global_id.x;
+@compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>
+) {
+    hash_one(flat_index(global_id, num_workgroups));
+}
 
-    // Bounds check for hash index and input length
-    if (hash_idx >= params.num_hashes || params.input_length > MAX_INPUT_SIZE) {
+// XOR lane `lane` of the rate block at byte `offset` of the padded message into the state.
+// Lanes past the rate are skipped.
+fn absorb_lane(
+    state: ptr<function, array<vec2<u32>, 25>>,
+    lane: u32,
+    hash_idx: u32,
+    offset: u32,
+) {
+    if (lane * 8u >= params.rate_bytes) {
         return;
     }
+    // Rates are multiples of 8 bytes, so every lane starts on a word of the message
+    let word_idx = (offset + lane * 8u) / 4u;
+    let low = padded_word(hash_idx, word_idx);
+    let high = padded_word(hash_idx, word_idx + 1u);
+    (*state)[lane] = xor_u64((*state)[lane], vec2<u32>(high, low));
+}
 
-    // Initialize state (25 vec2<u32> values = 200 bytes)
-    var state: array<vec2<u32>, 25>;
-    for (var i = 0u; i < 25u; i = i + 1u) {
-        state[i] = vec2<u32>(0u, 0u);
-    }
-
-    // Load input data for this hash
-    // Optimized: Use packed u32 buffer (8KB as 2048 u32s instead of 32KB)
-    var input_buffer: array<u32, 2048>;  // Packed buffer: 8KB capacity
-    let input_offset = hash_idx * params.input_length;
-
-    // Optimized: Load words directly (avoids byte unpacking overhead)
-    let input_words = (params.input_length + 3u) / 4u;  // Round up to word count
-    let start_word = input_offset / 4u;
-    let byte_align = input_offset % 4u;
-
-    if (byte_align == 0u) {
-        // Aligned case: direct word copy
-        for (var i = 0u; i < input_words; i = i + 1u) {
-            input_buffer[i] = inputs.data[start_word + i];
-        }
-    } else {
-        // Unaligned case: need to shift and combine words
-        let shift_bits = byte_align * 8u;
-        let inv_shift = 32u - shift_bits;
+// XOR the rate block at byte `offset` of the padded message into the state.
+// Written out lane by lane (up to SHAKE128's 21) rather than as a loop: some drivers
+// (Mesa's llvmpipe) cap the total iterations of a data-dependent loop nested in another,
+// which the lane loop would exceed after a few hundred kilobytes of input.
+fn absorb_block(
+    state: ptr<function, array<vec2<u32>, 25>>,
+    hash_idx: u32,
+    offset: u32,
+) {
+    absorb_lane(state, 0u, hash_idx, offset);
+    absorb_lane(state, 1u, hash_idx, offset);
+    absorb_lane(state, 2u, hash_idx, offset);
+    absorb_lane(state, 3u, hash_idx, offset);
+    absorb_lane(state, 4u, hash_idx, offset);
+    absorb_lane(state, 5u, hash_idx, offset);
+    absorb_lane(state, 6u, hash_idx, offset);
+    absorb_lane(state, 7u, hash_idx, offset);
+    absorb_lane(state, 8u, hash_idx, offset);
+    absorb_lane(state, 9u, hash_idx, offset);
+    absorb_lane(state, 10u, hash_idx, offset);
+    absorb_lane(state, 11u, hash_idx, offset);
+    absorb_lane(state, 12u, hash_idx, offset);
+    absorb_lane(state, 13u, hash_idx, offset);
+    absorb_lane(state, 14u, hash_idx, offset);
+    absorb_lane(state, 15u, hash_idx, offset);
+    absorb_lane(state, 16u, hash_idx, offset);
+    absorb_lane(state, 17u, hash_idx, offset);
+    absorb_lane(state, 18u, hash_idx, offset);
+    absorb_lane(state, 19u, hash_idx, offset);
+    absorb_lane(state, 20u, hash_idx, offset);
+}
 
-        for (var i = 0u; i < input_words; i = i + 1u) {
-            let w0 = inputs.data[start_word + i];
-            let w1 = inputs.data[start_word + i + 1u];
-            input_buffer[i] = (w0 >> shift_bits) | (w1 << inv_shift);
-        }
+// Hash input `hash_idx` of the batch into its output slot
+fn hash_one(hash_idx: u32) {
+    // Bounds check for hash index
+    if (hash_idx >= params.num_hashes) {
+        return;
     }
 
-    // Clear remaining buffer (important for padding correctness)
-    for (var i = input_words; i < MAX_INPUT_WORDS; i = i + 1u) {
-        input_buffer[i] = 0u;
+    // Initialize state (25 vec2<u32> values = 200 bytes) from the shared initial state
+    var state: array<vec2<u32>, 25>;
+    for (var i = 0u; i < 25u; i = i + 1u) {
+        state[i] = initial_state[i];
     }
 
-    // Apply SHA-3 padding
-    let padded_len = apply_padding(&input_buffer, params.input_length, params.rate_bytes);
-
-    // Absorbing phase: XOR input into state and permute
-    // Optimized: Load u64 values directly from packed buffer
-    var offset = 0u;
-    while (offset < padded_len) {
-        // XOR rate bytes into state (in 64-bit lanes)
-        let num_lanes = params.rate_bytes / 8u;
-        for (var i = 0u; i < num_lanes; i = i + 1u) {
-            let byte_offset = offset + i * 8u;
-            let lane = load_u64_from_buffer(&input_buffer, byte_offset);
-            state[i] = xor_u64(state[i], lane);
-        }
+    // Absorbing phase: XOR each rate block into the state and permute. The block count
+    // and padding layout depend only on the input length, so the host precomputes them.
+    for (var block = 0u; block < params.absorb_blocks; block = block + 1u) {
+        absorb_block(&state, hash_idx, block * params.rate_bytes);
 
         // Apply Keccak-f permutation
         keccak_f1600(&state);
-
-        offset = offset + params.rate_bytes;
     }
 
     // Squeezing phase: Extract output
@@ -464,3 +437,131 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
         }
     }
 }
+
+// Streaming absorb - a single invocation XORs `params.input_length` bytes of whole rate
+// blocks into the persistent sponge state, permuting after each block. Padding and
+// squeezing are left to `main`, which finishes the hash from `stream_state` as its
+// initial state.
+@compute @workgroup_size(1, 1, 1)
+fn absorb() {
+    var state: array<vec2<u32>, 25>;
+    for (var i = 0u; i < 25u; i = i + 1u) {
+        state[i] = stream_state[i];
+    }
+
+    // Rates are multiples of 8 bytes, so every lane is word-aligned in the input
+    let num_lanes = params.rate_bytes / 8u;
+    let rate_words = params.rate_bytes / 4u;
+    let total_words = params.input_length / 4u;
+    for (var block = 0u; block < total_words; block = block + rate_words) {
+        for (var i = 0u; i < num_lanes; i = i + 1u) {
+            let word = block + i * 2u;
+            let lane = vec2<u32>(inputs.data[word + 1u], inputs.data[word]);
+            state[i] = xor_u64(state[i], lane);
+        }
+        keccak_f1600(&state);
+    }
+
+    for (var i = 0u; i < 25u; i = i + 1u) {
+        stream_state[i] = state[i];
+    }
+}
+
+// Raw permutation - applies Keccak-f[1600] to state `params.num_hashes` of a batch of
+// 200-byte states, each lane stored as a little-endian u64 (low word first). Only
+// `num_hashes` is read from the params.
+@compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
+fn permute(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>
+) {
+    let state_idx = flat_index(global_id, num_workgroups);
+    if (state_idx >= params.num_hashes) {
+        return;
+    }
+
+    let base = state_idx * 50u;
+    var state: array<vec2<u32>, 25>;
+    for (var i = 0u; i < 25u; i = i + 1u) {
+        state[i] = vec2<u32>(inputs.data[base + i * 2u + 1u], inputs.data[base + i * 2u]);
+    }
+    keccak_f1600(&state);
+    for (var i = 0u; i < 25u; i = i + 1u) {
+        outputs.hash[base + i * 2u] = state[i].y;
+        outputs.hash[base + i * 2u + 1u] = state[i].x;
+    }
+}
+
+// Byte `k` of digest `idx` in `outputs`
+fn digest_byte(idx: u32, k: u32) -> u32 {
+    let b = idx * params.output_bytes + k;
+    return (outputs.hash[b / 4u] >> ((b % 4u) * 8u)) & 0xFFu;
+}
+
+// Whether digest `a` sorts strictly before digest `b`, comparing bytes in order
+fn digest_less(a: u32, b: u32) -> bool {
+    for (var k = 0u; k < params.output_bytes; k = k + 1u) {
+        let x = digest_byte(a, k);
+        let y = digest_byte(b, k);
+        if (x != y) {
+            return x < y;
+        }
+    }
+    return false;
+}
+
+// Argmin reduction - one level of a pairwise tree over a finished `main` dispatch. Slot
+// `2 * stride * i` keeps whichever of it and slot `+ stride` indexes the smaller digest;
+// on ties the left slot, which always holds the lower index, wins. After the passes for
+// strides 1, 2, 4, ... slot 0 indexes the smallest digest.
+@compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
+fn argmin_pass(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>
+) {
+    let left = flat_index(global_id, num_workgroups) * 2u * argmin_stride;
+    let right = left + argmin_stride;
+    if (right >= params.num_hashes) {
+        return;
+    }
+    let a = argmin_indices[left];
+    let b = argmin_indices[right];
+    if (digest_less(b, a)) {
+        argmin_indices[left] = b;
+    }
+}
+
+// Digest interleave - builds `input || digest` rows from a finished `main` dispatch so a
+// second pass can hash them without a readback. One invocation per output word, so rows
+// that aren't word-aligned never share a word between invocations.
+@compute @workgroup_size(WORKGROUP_SIZE, 1, 1)
+fn interleave_digests(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(num_workgroups) num_workgroups: vec3<u32>
+) {
+    let word_idx = flat_index(global_id, num_workgroups);
+    let row_len = params.input_length + params.output_bytes;
+    let total_bytes = params.num_hashes * row_len;
+    if (word_idx * 4u >= total_bytes) {
+        return;
+    }
+
+    var word = 0u;
+    for (var k = 0u; k < 4u; k = k + 1u) {
+        let b = word_idx * 4u + k;
+        if (b < total_bytes) {
+            let row = b / row_len;
+            let col = b % row_len;
+            var byte_val: u32;
+            if (col < params.input_length) {
+                let index = row * params.input_length + col;
+                byte_val = (inputs.data[index / 4u] >> ((index % 4u) * 8u)) & 0xFFu;
+            } else {
+                let index = row * params.output_bytes + col - params.input_length;
+                byte_val = (outputs.hash[index / 4u] >> ((index % 4u) * 8u)) & 0xFFu;
+            }
+            word = word | (byte_val << (k * 8u));
+        }
+    }
+    interleaved[word_idx] = word;
+}
@@ -15,6 +15,10 @@ interface Sha3WasmHasher {
     inputs: Uint8Array[],
     outputLength: number,
   ): Promise<Uint8Array[]>;
+  hashBatchWithProgress(
+    inputs: Uint8Array[],
+    callback: (completed: number, total: number) => void,
+  ): Promise<Uint8Array[]>;
   getVariant(): string;
   getOutputSize(): number;
 }